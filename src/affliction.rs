@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use crate::check::Degree;
+use crate::effective::StatKind;
+use crate::engine::{
+    Action, ActionResponse, Filter, InvalidAction, Resolved, State, Tag, UpdateBuilder,
+};
+use crate::stats::{Save, Skill, Statistic};
+use crate::world::{CreatureId, World};
+
+/** Affliction
+ *  A poison or disease that moves through numbered stages with each save against it.
+ *  It is cured once its stage falls to 0.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Affliction {
+    pub(crate) name: String,
+    pub(crate) save: Save,
+    pub(crate) dc: i32,
+    pub(crate) stage: u8,
+    pub(crate) max_stage: u8,
+}
+
+impl Affliction {
+    // A newly contracted affliction starts at stage 1
+    pub(crate) fn new(name: &str, save: Save, dc: i32, max_stage: u8) -> Self {
+        Affliction {
+            name: String::from(name),
+            save,
+            dc,
+            stage: 1,
+            max_stage,
+        }
+    }
+}
+
+// Moves the named affliction by `change` stages, never past its last
+fn shift_stage(target: CreatureId, name: String, change: i32) -> Filter<World> {
+    Arc::new(move |mut state: State<World>| {
+        if let Some(creature) = state.base.creature_mut(target) {
+            for affliction in creature.afflictions.iter_mut().filter(|a| a.name == name) {
+                let stage = affliction.stage as i32 + change;
+                affliction.stage = stage.clamp(0, affliction.max_stage as i32) as u8;
+            }
+            creature.afflictions.retain(|a| a.stage > 0);
+        }
+        state
+    })
+}
+
+impl Action<World> {
+    /**
+     * The save an afflicted creature makes at the end of each of its turns,
+     * which `Engine::next_turn` has it make.
+     * Success reduces the stage by 1 and failure advances it by 1, double on a critical.
+     */
+    pub(crate) fn affliction_save(creature: CreatureId, name: &str) -> Self {
+        let name = String::from(name);
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            let Some(afflicted) = state.base.creature(creature) else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            let Some(affliction) = afflicted.afflictions.iter().find(|a| a.name == name) else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            let modifier = afflicted.save_modifier(affliction.save);
+            let stat = StatKind::Check(Statistic::Save(affliction.save));
+            let custom = &state.base.custom_conditions;
+            let degree = context.with_conditions(afflicted, custom, stat, |context| {
+                context.roll_check(modifier, affliction.dc)
+            });
+            let change = match degree {
+                Degree::CriticalSuccess => -2,
+                Degree::Success => -1,
+                Degree::Failure => 1,
+                Degree::CriticalFailure => 2,
+            };
+            let update = UpdateBuilder::new()
+                .filter(shift_stage(creature, name.clone(), change))
+                .target(creature)
+                .tag(Tag::Degree(degree))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+    }
+
+    /**
+     * A Medicine check against the affliction's DC to help the patient fight it off.
+     * Success reduces the stage by 1, a critical success by 2,
+     * and a critical failure advances it instead.
+     */
+    pub(crate) fn treat_affliction(healer: CreatureId, patient: CreatureId, name: &str) -> Self {
+        let name = String::from(name);
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            let Some(dc) = state
+                .base
+                .creature(patient)
+                .and_then(|c| c.afflictions.iter().find(|a| a.name == name))
+                .map(|a| a.dc)
+            else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            let name = name.clone();
+            let check = Action::skill_check(healer, Skill::Medicine, dc, move |degree, context| {
+                let change = match degree {
+                    Degree::CriticalSuccess => -2,
+                    Degree::Success => -1,
+                    Degree::Failure => return vec![],
+                    Degree::CriticalFailure => 1,
+                };
+                vec![UpdateBuilder::new()
+                    .filter(shift_stage(patient, name.clone(), change))
+                    .target(patient)
+                    .tag(Tag::Manipulate)
+                    .tag(Tag::Degree(degree))
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id())]
+            });
+            check.apply(state, context)
+        }))
+        .with_cost(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::FixedDice;
+    use crate::engine::Engine;
+    use crate::initiative::Initiative;
+    use crate::world::{Creature, WorldBuilder};
+
+    // A fighter two stages into a DC 15 poison, with a cleric beside them
+    fn engine(rolls: Vec<u8>) -> Engine<World> {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        let mut poison = Affliction::new("Giant Centipede Venom", Save::Fortitude, 15, 3);
+        poison.stage = 2;
+        fighter.afflictions.push(poison);
+        let world = WorldBuilder::new()
+            .add_creature(0, fighter)
+            .add_creature(1, Creature::new("Cleric", 18, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.dice = Box::new(FixedDice::new(rolls));
+        engine
+    }
+
+    fn run(engine: &mut Engine<World>, action: Action<World>) -> Vec<u8> {
+        engine.act(action);
+        while !engine.is_idle() {
+            engine.step();
+        }
+        let fighter = engine.state.base.creature(0).unwrap();
+        fighter.afflictions.iter().map(|a| a.stage).collect()
+    }
+
+    #[test]
+    fn failed_save_advances_the_affliction() {
+        let mut engine = engine(vec![8, 2]);
+        let save = || Action::affliction_save(0, "Giant Centipede Venom");
+        assert_eq!(run(&mut engine, save()), vec![3]);
+        // A critical failure can't go past the last stage
+        assert_eq!(run(&mut engine, save()), vec![3]);
+    }
+
+    #[test]
+    fn the_save_comes_as_the_afflicted_creatures_turn_ends() {
+        let mut engine = engine(vec![18]);
+        engine.state.base.initiative = Initiative::new(vec![(0, 20), (1, 15)]);
+        engine.next_turn();
+        while !engine.is_idle() {
+            engine.step();
+        }
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert_eq!(fighter.afflictions[0].stage, 1);
+    }
+
+    #[test]
+    fn successful_treatment_reduces_then_cures() {
+        let mut engine = engine(vec![16]);
+        let treat = || Action::treat_affliction(1, 0, "Giant Centipede Venom");
+        assert_eq!(run(&mut engine, treat()), vec![1]);
+        assert_eq!(run(&mut engine, treat()), Vec::<u8>::new());
+        assert_eq!(engine.state.meta.actions_remaining, 1);
+    }
+}
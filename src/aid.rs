@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use crate::check::Degree;
+use crate::engine::{
+    Action, ActionResponse, Context, InvalidAction, Meta, Resolved, State, Update, UpdateBuilder,
+};
+use crate::modifier::{Modifier, ModifierKind};
+use crate::stats::Skill;
+use crate::world::{CreatureId, World};
+
+// The DC of the check to Aid
+pub(crate) const AID_DC: i32 = 15;
+
+/** PreparedAid
+ *  A creature standing ready to help an ally with its next check of a skill.
+ *  It is spent, along with the aider's reaction, when the ally makes that check.
+ */
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PreparedAid {
+    pub(crate) aider: CreatureId,
+    pub(crate) ally: CreatureId,
+    pub(crate) skill: Skill,
+}
+
+// What the aid check's degree gives the ally; a plain failure gives nothing
+fn aid_bonus(degree: Degree) -> Option<Modifier> {
+    let bonus = match degree {
+        Degree::CriticalSuccess => 2,
+        Degree::Success => 1,
+        Degree::Failure => return None,
+        Degree::CriticalFailure => -1,
+    };
+    Some(Modifier::new(ModifierKind::Circumstance, bonus))
+}
+
+impl Action<World> {
+    // Prepares to Aid the ally's next check of the skill, taking the aider's action now
+    pub(crate) fn prepare_aid(aider: CreatureId, skill: Skill, ally: CreatureId) -> Self {
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            if state.base.creature(ally).is_none() {
+                return ActionResponse::Invalid(InvalidAction::NoValidTarget);
+            }
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(move |mut state: State<World>| {
+                    state.meta.aids.push(PreparedAid { aider, ally, skill });
+                    state
+                }))
+                .target(aider)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+        .with_cost(1)
+        .named("Aid")
+        .by(aider)
+    }
+}
+
+impl Context<'_> {
+    /**
+     * As `roll_check` for the actor's skill check, after anyone prepared to Aid it
+     * rolls their own check against `AID_DC` as a reaction. The aider's bonus
+     * or penalty counts towards the actor's roll, and the update returned spends the aid.
+     */
+    pub(crate) fn roll_aided(
+        &mut self,
+        state: &State<World>,
+        actor: CreatureId,
+        skill: Skill,
+        modifier: i32,
+        dc: i32,
+    ) -> (Degree, Option<Update<World>>) {
+        let meta = &state.meta;
+        let aid = meta.aids.iter().find(|aid| {
+            (aid.ally, aid.skill) == (actor, skill) && meta.reaction_available(aid.aider)
+        });
+        let Some((aider, helping)) = aid.and_then(|aid| {
+            state.base.creature(aid.aider).map(|creature| (aid.aider, creature))
+        }) else {
+            return (self.roll_check(modifier, dc), None);
+        };
+        // The aider's check is its own, without the actor's modifiers
+        let helping = helping.skill_modifier(skill);
+        let helped = self.crit_rules.roll_check(self.dice, helping, AID_DC);
+        let outer = self.modifiers.len();
+        self.modifiers.extend(aid_bonus(helped));
+        let degree = self.roll_check(modifier, dc);
+        self.modifiers.truncate(outer);
+        let spend = UpdateBuilder::new()
+            .filter(Arc::new(move |mut state: State<World>| {
+                let spent = (aider, actor, skill);
+                state.meta.aids.retain(|aid| (aid.aider, aid.ally, aid.skill) != spent);
+                state.meta.reactions_used.push(aider);
+                state
+            }))
+            .target(aider)
+            .resolved(Resolved::Resolved)
+            .build(self.next_id());
+        (degree, Some(spend))
+    }
+}
+
+impl Meta {
+    // Whether anyone is still waiting to Aid the creature's check
+    pub(crate) fn aided(&self, ally: CreatureId, skill: Skill) -> bool {
+        self.aids.iter().any(|aid| (aid.ally, aid.skill) == (ally, skill))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::FixedDice;
+    use crate::engine::{Engine, Tag};
+    use crate::world::{Creature, WorldBuilder};
+
+    // Whether the ally's Athletics check beats DC 10 after the aider rolls `aid`
+    fn aided_check(aid: u8, natural: u8) -> (bool, Engine<World>) {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Rogue", 16, 17))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.act(Action::prepare_aid(0, Skill::Athletics, 1));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert!(engine.state.meta.aided(1, Skill::Athletics));
+        engine.dice = Box::new(FixedDice::new(vec![aid, natural]));
+        engine.act(Action::skill_check(1, Skill::Athletics, 10, |degree, context| {
+            vec![UpdateBuilder::new()
+                .filter(Arc::new(|state| state))
+                .tag(Tag::Degree(degree))
+                .build(context.next_id())]
+        }));
+        engine.step();
+        let passed = engine.updates[0].tags.contains(&Tag::Degree(Degree::Success));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        (passed, engine)
+    }
+
+    #[test]
+    fn aid_helps_or_hinders_by_its_degree() {
+        // Both are +0, so the ally's 9 needs the aid's +1 and its 10 suffers the -1
+        let (passed, engine) = aided_check(15, 9);
+        assert!(passed);
+        assert!(!engine.state.meta.aided(1, Skill::Athletics));
+        assert!(!engine.state.meta.reaction_available(0));
+        assert!(!aided_check(2, 10).0);
+        assert!(aided_check(10, 10).0);
+    }
+}
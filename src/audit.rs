@@ -0,0 +1,103 @@
+use crate::engine::{Base, Engine, RuleTrace};
+
+// The first rule decision on replay that differs from the recording
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Divergence {
+    pub(crate) index: usize,                // Position in the trace
+    pub(crate) expected: Option<RuleTrace>, // None if the replay made extra decisions
+    pub(crate) actual: Option<RuleTrace>,   // None if the replay stopped short
+}
+
+impl<T: Base> Engine<T> {
+    /**
+     * Replays against a recorded trace: every rule decision from here on is traced,
+     * and `divergence` reports the first that doesn't match the recording.
+     * Starts from an empty trace, as the recording did.
+     */
+    pub(crate) fn audit(&mut self, recorded: Vec<RuleTrace>) {
+        self.tracing_enabled = true;
+        self.trace.clear();
+        self.audit = Some(recorded);
+    }
+
+    /**
+     * Where the live trace first parts from the recorded one, if it has.
+     * A replay still in progress only diverges by deciding differently,
+     * so check again once it has finished to catch one that stopped short.
+     */
+    pub(crate) fn divergence(&self) -> Option<Divergence> {
+        let recorded = self.audit.as_ref()?;
+        let index = (0..recorded.len().max(self.trace.len()))
+            .find(|&i| recorded.get(i) != self.trace.get(i))?;
+        Some(Divergence {
+            index,
+            expected: recorded.get(index).cloned(),
+            actual: self.trace.get(index).cloned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{Resolved, ResponseKind, RuleResponse, Tag, TestBase, Update};
+    use crate::metadata::Metadata;
+    use crate::tags::TagSet;
+
+    fn engine(rules: usize) -> Engine<TestBase> {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Skip));
+        for _ in 1..rules {
+            engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Manipulate)));
+        }
+        for id in 1..=2 {
+            engine.updates.push(Update {
+                filter: Arc::new(|state| state),
+                id,
+                target: 0,
+                tags: TagSet::new(),
+                applies: TagSet::new(),
+                resolved: Resolved::Resolved,
+                meta: Metadata::new(),
+                outcome: Default::default(),
+            });
+        }
+        engine
+    }
+
+    fn run(engine: &mut Engine<TestBase>) {
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    #[test]
+    fn replays_report_the_first_different_decision() {
+        let mut recording = engine(2);
+        recording.tracing_enabled = true;
+        run(&mut recording);
+        let recorded = recording.trace().to_vec();
+        #[cfg(feature = "serde")]
+        let recorded: Vec<RuleTrace> =
+            serde_json::from_str(&serde_json::to_string(&recorded).unwrap()).unwrap();
+
+        let mut faithful = engine(2);
+        faithful.audit(recorded.clone());
+        run(&mut faithful);
+        assert_eq!(faithful.divergence(), None);
+
+        // Without the second rule, the replay moves on to update 2 early
+        let mut altered = engine(1);
+        altered.audit(recorded);
+        run(&mut altered);
+        let divergence = altered.divergence().unwrap();
+        assert_eq!(divergence.index, 1);
+        let expected = divergence.expected.unwrap();
+        assert_eq!((expected.update_id, expected.rule_index), (1, 1));
+        assert_eq!(expected.response, ResponseKind::Attach);
+        let actual = divergence.actual.unwrap();
+        assert_eq!((actual.update_id, actual.response), (2, ResponseKind::Skip));
+    }
+}
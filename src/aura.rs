@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use crate::engine::{Filter, State, Tag};
+use crate::world::{Creature, CreatureId, World};
+
+/** Aura
+ *  An effect that reaches every creature within `radius` feet of its source,
+ *  the source included. The effect is a condition, attached while a creature
+ *  is in range and removed once it is not, as each turn starts.
+ *  Only what an aura gave is taken away: a creature that already had the condition
+ *  from elsewhere keeps it, as it does one raised past the aura's value since.
+ *  Overlapping auras with the same effect give it once; bonuses from different
+ *  effects then stack as modifiers do, so two status bonuses only count the best.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Aura {
+    pub(crate) source: CreatureId,
+    pub(crate) radius: i32,
+    pub(crate) effect: Tag,
+}
+
+impl World {
+    fn reaches(&self, aura: &Aura, id: CreatureId) -> bool {
+        match (self.creature(aura.source), self.creature(id)) {
+            (Some(source), Some(creature)) => {
+                source.position.distance(&creature.position) <= aura.radius
+            }
+            _ => false,
+        }
+    }
+
+    // The source and effect of each aura reaching the creature
+    fn reaching(&self, id: CreatureId) -> Vec<(CreatureId, Tag)> {
+        self.auras
+            .iter()
+            .filter(|aura| self.reaches(aura, id))
+            .map(|aura| (aura.source, aura.effect.clone()))
+            .collect()
+    }
+
+    /**
+     * Gives the creature the effect of every aura reaching it, and takes back what
+     * auras that no longer reach it gave, unless another aura still gives the same.
+     */
+    fn refresh(&self, id: CreatureId, creature: &mut Creature) {
+        let reaching = self.reaching(id);
+        let given = std::mem::take(&mut creature.from_auras);
+        let (kept, left) = given.into_iter().partition(|given| reaching.contains(given));
+        creature.from_auras = kept;
+        for (_, effect) in left {
+            if !creature.from_auras.iter().any(|(_, given)| *given == effect) {
+                // Worn down since is still the aura's, but raised past it is not
+                let gave = |c: &Tag| c.same_kind(&effect) && c.value() <= effect.value();
+                creature.conditions.retain(|condition| !gave(condition));
+            }
+        }
+        for (source, effect) in reaching {
+            let given = creature.from_auras.iter().any(|(_, given)| *given == effect);
+            let held = |c: &Tag| c.same_kind(&effect) && c.value() >= effect.value();
+            if !given && creature.conditions.iter().any(held) {
+                continue;
+            }
+            creature.add_condition(effect.clone());
+            if !creature.from_auras.contains(&(source, effect.clone())) {
+                creature.from_auras.push((source, effect));
+            }
+        }
+    }
+
+    // Creatures whose aura effects are out of date with where everyone stands
+    pub(crate) fn aura_changes(&self) -> Vec<CreatureId> {
+        self.creatures
+            .iter()
+            .filter(|(&id, creature)| {
+                let mut refreshed = (*creature).clone();
+                self.refresh(id, &mut refreshed);
+                refreshed != **creature
+            })
+            .map(|(&id, _)| id)
+            .collect()
+    }
+}
+
+// Brings the creature's aura effects up to date with where everyone stands
+pub(crate) fn refresh_auras(target: CreatureId) -> Filter<World> {
+    Arc::new(move |mut state: State<World>| {
+        if let Some(mut creature) = state.base.creature(target).cloned() {
+            state.base.refresh(target, &mut creature);
+            state.base.creatures.insert(target, creature);
+        }
+        state
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::condition::{ConditionDef, StatCategory};
+    use crate::effective::StatKind;
+    use crate::engine::{Action, Engine, Meta};
+    use crate::modifier::ModifierKind;
+    use crate::stats::Statistic;
+    use crate::world::{Creature, Position, WorldBuilder};
+
+    #[test]
+    fn auras_follow_creatures_in_and_out_of_range() {
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Paladin", 30, 20))
+            .build();
+        let mut squire = Creature::new("Squire", 12, 15);
+        squire.position = Position::new(4, 0);
+        world.creatures.insert(1, squire);
+        world.auras.push(Aura {
+            source: 0,
+            radius: 15,
+            effect: Tag::Frightened(1),
+        });
+        let mut engine = Engine::for_base(world);
+        let turn = |engine: &mut Engine<World>, position| {
+            engine.state.base.creature_mut(1).unwrap().position = position;
+            engine.act(Action::start_turn(1));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            engine.state.base.creature(1).unwrap().conditions.clone()
+        };
+        assert_eq!(turn(&mut engine, Position::new(4, 0)), vec![]);
+        assert_eq!(turn(&mut engine, Position::new(3, 0)), vec![Tag::Frightened(1)]);
+        assert_eq!(turn(&mut engine, Position::new(5, 1)), vec![]);
+        // The source stands in its own aura
+        assert!(engine.state.base.creature(0).unwrap().conditions.contains(&Tag::Frightened(1)));
+    }
+
+    #[test]
+    fn leaving_an_aura_only_takes_back_what_it_gave() {
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Paladin", 30, 20))
+            .add_creature(1, Creature::new("Squire", 12, 15).with_condition(Tag::Frightened(1)))
+            .add_creature(2, Creature::new("Page", 10, 14).with_condition(Tag::Frightened(2)))
+            .build();
+        world.auras.push(Aura {
+            source: 0,
+            radius: 15,
+            effect: Tag::Frightened(1),
+        });
+        // A condition held at the aura's value or more is not the aura's to take
+        assert_eq!(world.aura_changes(), vec![0]);
+        world.creature_mut(1).unwrap().position = Position::new(6, 0);
+        world.creature_mut(2).unwrap().position = Position::new(6, 0);
+        let state = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+        let state = [0, 1, 2].into_iter().fold(state, |state, id| refresh_auras(id)(state));
+        assert!(state.base.aura_changes().is_empty());
+        assert_eq!(state.base.creature(1).unwrap().conditions, vec![Tag::Frightened(1)]);
+        assert_eq!(state.base.creature(2).unwrap().conditions, vec![Tag::Frightened(2)]);
+    }
+
+    #[test]
+    fn overlapping_auras_take_the_best_bonus_of_each_type() {
+        let aura = |source, key: &str| Aura {
+            source,
+            radius: 10,
+            effect: Tag::Custom {
+                key: String::from(key),
+                value: 1,
+            },
+        };
+        let bonus = |kind| ConditionDef {
+            affects: StatCategory::Everything,
+            modifier: 1,
+            valued: false,
+            decrements_eot: false,
+            kind,
+        };
+        let perception = |auras: Vec<Aura>| {
+            let mut world = WorldBuilder::new()
+                .add_creature(0, Creature::new("Champion", 30, 20))
+                .add_creature(1, Creature::new("Bard", 20, 17))
+                .add_creature(2, Creature::new("Fighter", 20, 18))
+                .build();
+            world.creature_mut(2).unwrap().position = Position::new(1, 0);
+            let registry = &mut world.custom_conditions;
+            registry.register("heroism", bonus(ModifierKind::Status));
+            registry.register("courage", bonus(ModifierKind::Status));
+            registry.register("guidance", bonus(ModifierKind::Circumstance));
+            world.auras = auras;
+            let mut engine = Engine::for_base(world);
+            engine.act(Action::start_turn(2));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            engine.state.effective(2, StatKind::Check(Statistic::Perception), None).0
+        };
+        assert_eq!(perception(vec![aura(0, "heroism"), aura(1, "courage")]), 1);
+        assert_eq!(perception(vec![aura(0, "heroism"), aura(1, "guidance")]), 2);
+        // The same effect from two sources is still only one condition
+        assert_eq!(perception(vec![aura(0, "heroism"), aura(1, "heroism")]), 1);
+    }
+}
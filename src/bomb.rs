@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use crate::engine::{Action, ActionResponse, InvalidAction, Resolved, State, UpdateBuilder};
+use crate::world::{typed_damage, CreatureId, DamageType, World};
+
+impl Action<World> {
+    /**
+     * An alchemical bomb: direct damage to the target, and splash damage to it
+     * and every creature adjacent to it, friend or foe (the thrower included).
+     * The target's direct and splash damage count as one instance against resistance.
+     */
+    pub(crate) fn bomb(target: CreatureId, direct: i32, splash: i32, kind: DamageType) -> Self {
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            let Some(hit) = state.base.creature(target) else {
+                return ActionResponse::Invalid(InvalidAction::NoValidTarget);
+            };
+            let mut updates = vec![UpdateBuilder::new()
+                .filter(typed_damage(target, direct + splash, kind))
+                .target(target)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id())];
+            for (&id, creature) in state.base.creatures.iter() {
+                if creature.position.adjacent(&hit.position) {
+                    updates.push(
+                        UpdateBuilder::new()
+                            .filter(typed_damage(id, splash, kind))
+                            .target(id)
+                            .resolved(Resolved::Resolved)
+                            .build(context.next_id()),
+                    );
+                }
+            }
+            ActionResponse::Valid(updates)
+        }))
+        .with_cost(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+    use crate::resistance::{Resistance, ResistanceScope};
+    use crate::world::{Creature, Position};
+
+    #[test]
+    fn splash_reaches_adjacent_creatures_only() {
+        let mut world = World::default();
+        for (id, x) in [(0, 0), (1, 1), (2, 4)] {
+            let mut goblin = Creature::new("Goblin", 20, 16);
+            goblin.position = Position::new(x, 0);
+            world.creatures.insert(id, goblin);
+        }
+        let fire = Resistance::new(ResistanceScope::Type(DamageType::Fire), 2);
+        world.creature_mut(1).unwrap().resistances.push(fire);
+        let mut engine = Engine::for_base(world);
+        engine.act(Action::bomb(0, 6, 3, DamageType::Fire));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        let hp: Vec<i32> = engine.state.base.creatures.values().map(|c| c.hp).collect();
+        // 6 + 3 to the target, and the neighbour resists 2 of its 3 splash
+        assert_eq!(hp, vec![11, 19, 20]);
+    }
+}
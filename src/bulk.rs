@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use crate::engine::{
+    Action, ActionResponse, Filter, Resolved, Rule, RuleResponse, State, Tag, UpdateBuilder,
+};
+use crate::defense::Defense;
+use crate::field::Ability;
+use crate::world::{target_filter, Creature, CreatureId, World};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Item {
+    pub(crate) name: String,
+    pub(crate) bulk: i32, // In tenths of a Bulk, so a light item is 1
+    pub(crate) bonuses: Vec<(Defense, i32)>, // Item bonuses it grants while carried
+}
+
+impl Item {
+    pub(crate) fn new(name: &str, bulk: i32) -> Self {
+        Item {
+            name: String::from(name),
+            bulk,
+            bonuses: vec![],
+        }
+    }
+
+    pub(crate) fn with_bonus(mut self, defense: Defense, bonus: i32) -> Self {
+        self.bonuses.push((defense, bonus));
+        self
+    }
+}
+
+impl Creature {
+    // Carried Bulk, with fractions of a Bulk dropped
+    pub(crate) fn total_bulk(&self) -> i32 {
+        self.items.iter().map(|item| item.bulk).sum::<i32>() / 10
+    }
+
+    pub(crate) fn encumbered_threshold(&self) -> i32 {
+        5 + self.modifier(Ability::Strength)
+    }
+
+    // Past this the creature can't carry any more
+    pub(crate) fn max_bulk(&self) -> i32 {
+        10 + self.modifier(Ability::Strength)
+    }
+
+    pub(crate) fn is_encumbered(&self) -> bool {
+        self.total_bulk() > self.encumbered_threshold()
+    }
+
+    // Encumbered creatures take a 10 foot penalty, but can always move 5 feet
+    pub(crate) fn land_speed(&self) -> i32 {
+        if self.is_encumbered() {
+            (self.speed - 10).max(5)
+        } else {
+            self.speed
+        }
+    }
+}
+
+pub(crate) fn give(target: CreatureId, item: Item) -> Filter<World> {
+    Arc::new(move |mut state: State<World>| {
+        if let Some(creature) = state.base.creature_mut(target) {
+            creature.items.push(item.clone());
+        }
+        state
+    })
+}
+
+// Takes the first item with the given name
+pub(crate) fn take(target: CreatureId, name: &str) -> Filter<World> {
+    let name = String::from(name);
+    Arc::new(move |mut state: State<World>| {
+        if let Some(creature) = state.base.creature_mut(target) {
+            if let Some(at) = creature.items.iter().position(|item| item.name == name) {
+                creature.items.remove(at);
+            }
+        }
+        state
+    })
+}
+
+/**
+ * Watches each update's effect on its target's Bulk.
+ * Crossing the encumbered threshold attaches clumsy 1, and dropping back
+ * under it clears that clumsy 1 again. A worse clumsy comes from something else,
+ * like a spell, so it stays.
+ */
+pub(crate) fn encumbrance() -> Rule<World> {
+    Arc::new(|state, update| {
+        let Some(before) = state.base.creature(update.target) else {
+            return RuleResponse::Skip;
+        };
+        // The rule runs before the update, so look at what it would leave behind
+        let Some(after) = update.outcome(state).base.creature(update.target) else {
+            return RuleResponse::Skip;
+        };
+        let target = update.target;
+        match (before.is_encumbered(), after.is_encumbered()) {
+            (false, true) => RuleResponse::Apply(Tag::Clumsy(1)),
+            (true, false) => RuleResponse::Inject(Action::new(Arc::new(move |_, context| {
+                let clear = UpdateBuilder::new()
+                    .filter(target_filter(target, |creature| {
+                        creature.conditions.retain(|c| *c != Tag::Clumsy(1))
+                    }))
+                    .target(target)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![clear])
+            }))),
+            _ => RuleResponse::Skip,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dying::dying;
+    use crate::engine::Engine;
+    use crate::world::WorldBuilder;
+
+    fn carry(engine: &mut Engine<World>, filter: Filter<World>) {
+        let update = UpdateBuilder::new()
+            .filter(filter)
+            .resolved(Resolved::Resolved)
+            .build(engine.next_id());
+        engine.updates.push(update);
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    #[test]
+    fn bulk_past_the_threshold_encumbers() {
+        // +1 Strength, so encumbered past 6 Bulk
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.abilities[Ability::Strength as usize] = 12;
+        fighter.items = vec![Item::new("Backpack", 60), Item::new("Torch", 1)];
+        let world = WorldBuilder::new().add_creature(0, fighter).build();
+        let mut engine = Engine::for_base(world);
+        engine.rules.push(encumbrance());
+
+        carry(&mut engine, give(0, Item::new("Rations", 5)));
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert_eq!(fighter.total_bulk(), 6);
+        assert!(fighter.conditions.is_empty());
+
+        carry(&mut engine, give(0, Item::new("Rope", 5)));
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert_eq!(fighter.total_bulk(), 7);
+        assert_eq!(fighter.conditions, vec![Tag::Clumsy(1)]);
+        assert_eq!(fighter.land_speed(), 15);
+        assert_eq!(fighter.max_bulk(), 11);
+
+        carry(&mut engine, take(0, "Backpack"));
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert!(fighter.conditions.is_empty());
+        assert_eq!(fighter.land_speed(), 25);
+    }
+
+    #[test]
+    fn dropping_bulk_leaves_a_worse_clumsy_alone() {
+        let mut fighter = Creature::new("Fighter", 20, 18).with_condition(Tag::Clumsy(2));
+        fighter.items = vec![Item::new("Backpack", 60)];
+        let world = WorldBuilder::new().add_creature(0, fighter).build();
+        let mut engine = Engine::for_base(world);
+        engine.rules.push(encumbrance());
+
+        carry(&mut engine, give(0, Item::new("Rope", 5)));
+        assert!(engine.state.base.creature(0).unwrap().is_encumbered());
+        carry(&mut engine, take(0, "Backpack"));
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert_eq!(fighter.conditions, vec![Tag::Clumsy(2)]);
+    }
+
+    #[test]
+    fn rules_share_one_run_of_the_filter() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let world = WorldBuilder::new().add_creature(0, Creature::new("Fighter", 20, 18)).build();
+        let mut engine = Engine::for_base(world);
+        engine.rules.push(encumbrance());
+        engine.rules.push(dying());
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counted = runs.clone();
+        carry(
+            &mut engine,
+            Arc::new(move |state| {
+                counted.fetch_add(1, Ordering::Relaxed);
+                give(0, Item::new("Rope", 5))(state)
+            }),
+        );
+        // Once for both rules to compare against, and once as it commits
+        assert_eq!(runs.load(Ordering::Relaxed), 2);
+        assert_eq!(engine.state.base.creature(0).unwrap().items.len(), 1);
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use crate::combat::Weapon;
+use crate::engine::{
+    Action, ActionResponse, Resolved, Rule, RuleResponse, State, Tag, UpdateBuilder,
+};
+use crate::metadata::Value;
+use crate::reaction::Reaction;
+use crate::world::{CreatureId, World};
+
+// Marks an update as disrupting the spell of the creature it names; see `disrupt_casting`
+const DISRUPTS: &str = "disrupts";
+
+impl Action<World> {
+    /**
+     * Casts the spell: its chain starts with an update tagged `Tag::Casting`,
+     * so reactions to spellcasting get their chance before any of its effects apply.
+     * The spell keeps its own cost, targeting and name.
+     */
+    pub(crate) fn cast(caster: CreatureId, rank: u8, spell: Action<World>) -> Self {
+        let generator = spell.generator.clone();
+        Action {
+            generator: Arc::new(move |state, context| {
+                // Only the first time through; answers to the spell's prompts come after it
+                if !context.answers.is_empty() {
+                    return generator(state, context);
+                }
+                let casting = UpdateBuilder::new()
+                    .filter(Arc::new(|state: State<World>| state))
+                    .target(caster)
+                    .tag(Tag::Casting { caster, rank })
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id());
+                let first = |updates: Vec<_>| [casting].into_iter().chain(updates).collect();
+                match generator(state, context) {
+                    ActionResponse::Valid(updates) => ActionResponse::Valid(first(updates)),
+                    ActionResponse::Partial(updates, input) => {
+                        ActionResponse::Partial(first(updates), input)
+                    }
+                    ActionResponse::RequestInput(input) => {
+                        ActionResponse::Partial(first(vec![]), input)
+                    }
+                    invalid => invalid,
+                }
+            }),
+            ..spell
+        }
+        .by(caster)
+    }
+}
+
+/**
+ * Reverts a spell's chain at any update marked as disrupting it, so none of
+ * the rest of the spell applies. Whatever already has, like the damage of
+ * the Strike doing the disrupting, stays.
+ */
+pub(crate) fn disrupt_casting() -> Rule<World> {
+    Arc::new(|_, update| match update.meta_value(DISRUPTS) {
+        Some(_) => {
+            let lost = Action::new(Arc::new(|_, _| ActionResponse::Valid(vec![])));
+            RuleResponse::Revert(lost)
+        }
+        None => RuleResponse::Skip,
+    })
+}
+
+/**
+ * Disrupting Strike: a Strike with the weapon, for the owner's reaction, against
+ * an enemy that starts casting within its reach. A hit disrupts the spell,
+ * if `disrupt_casting` is among the engine's rules.
+ */
+pub(crate) fn disrupting_strike(owner: CreatureId, weapon: Weapon) -> Reaction<World> {
+    let reach = weapon.reach;
+    Reaction {
+        owner,
+        name: String::from("Disrupting Strike"),
+        trigger: Tag::Casting {
+            caster: owner,
+            rank: 0,
+        },
+        eligible: Arc::new(move |state, trigger| {
+            let Tag::Casting { caster, .. } = trigger else {
+                return false;
+            };
+            let (Some(reacting), Some(casting)) =
+                (state.base.creature(owner), state.base.creature(*caster))
+            else {
+                return false;
+            };
+            let distance = reacting.position.distance(&casting.position);
+            reacting.is_enemy(casting) && distance <= reacting.melee_reach(reach)
+        }),
+        build: Arc::new(move |_, trigger| {
+            let Tag::Casting { caster, .. } = *trigger else {
+                unreachable!("Disrupting Strike only triggers on spellcasting");
+            };
+            let strike = Action::strike(owner, caster, weapon.clone()).with_cost(0);
+            let generator = strike.generator.clone();
+            Action {
+                generator: Arc::new(move |state, context| match generator(state, context) {
+                    ActionResponse::Valid(mut updates) => {
+                        if updates.iter().any(|update| update.tags.contains(&Tag::Hit)) {
+                            let disruption = UpdateBuilder::new()
+                                .filter(Arc::new(|state: State<World>| state))
+                                .target(caster)
+                                .meta(DISRUPTS, Value::Int(caster as i32))
+                                .resolved(Resolved::Resolved)
+                                .build(context.next_id());
+                            updates.push(disruption);
+                        }
+                        ActionResponse::Valid(updates)
+                    }
+                    other => other,
+                }),
+                ..strike
+            }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::WeaponGroup;
+    use crate::dice::FixedDice;
+    use crate::engine::Engine;
+    use crate::world::{damage, Creature, Position, WorldBuilder};
+
+    fn cast_at_the_fighter(use_reaction: bool) -> (i32, i32) {
+        let mut wizard = Creature::new("Wizard", 16, 15);
+        wizard.team = 1;
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, wizard)
+            .set_position(1, Position { x: 1, y: 0 })
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.rules.push(disrupt_casting());
+        let club = Weapon::new("Club", WeaponGroup::Club, 1, 6);
+        engine.reactions.push(disrupting_strike(0, club));
+        engine.dice = Box::new(FixedDice::new(vec![15, 4]));
+        let spell = Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(damage(0, 5))
+                .target(0)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }));
+        engine.act(Action::cast(1, 1, spell));
+        while engine.window.is_none() && !engine.is_idle() {
+            engine.step();
+        }
+        let offered = engine.window.clone().unwrap_or_default();
+        assert_eq!(offered.len(), 1);
+        assert_eq!(offered[0].name, "Disrupting Strike");
+        if use_reaction {
+            engine.use_reaction(0);
+        } else {
+            engine.decline_reactions();
+        }
+        while !engine.is_idle() {
+            engine.step();
+        }
+        let hp = |id| engine.state.base.creature(id).unwrap().hp;
+        (hp(0), hp(1))
+    }
+
+    #[test]
+    fn a_disrupting_hit_stops_the_spell() {
+        // Let through, the spell deals its 5 damage
+        assert_eq!(cast_at_the_fighter(false), (15, 16));
+        // The Strike hits for 4, and the spell never lands
+        assert_eq!(cast_at_the_fighter(true), (20, 12));
+    }
+}
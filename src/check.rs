@@ -0,0 +1,474 @@
+use std::sync::Arc;
+
+use crate::crit::CritRules;
+use crate::dice::Dice;
+use crate::effective::StatKind;
+use crate::engine::{
+    Action, ActionResponse, Context, InvalidAction, Resolved, State, Tag, Update, UpdateBuilder,
+};
+use crate::fortune::Luck;
+use crate::modifier::combine;
+use crate::oneshot::NextRoll;
+use crate::stats::{spell_dc, Save, Skill, Statistic};
+use crate::world::{CreatureId, World};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Degree {
+    CriticalFailure,
+    Failure,
+    Success,
+    CriticalSuccess,
+}
+
+impl Degree {
+    pub(crate) fn up(self) -> Self {
+        match self {
+            Degree::CriticalFailure => Degree::Failure,
+            Degree::Failure => Degree::Success,
+            _ => Degree::CriticalSuccess,
+        }
+    }
+
+    pub(crate) fn down(self) -> Self {
+        match self {
+            Degree::CriticalSuccess => Degree::Success,
+            Degree::Success => Degree::Failure,
+            _ => Degree::CriticalFailure,
+        }
+    }
+}
+
+/**
+ * Beating the DC by 10 is a critical success, missing it by 10 a critical failure.
+ * A natural 20 then improves the result a step, a natural 1 worsens it.
+ * These are the rules as written; see `CritRules` for house rules.
+ */
+pub(crate) fn degree_of_success(natural: u8, total: i32, dc: i32) -> Degree {
+    CritRules::default().degree(natural, total, dc)
+}
+
+// For checks where the natural result doesn't matter, like flat and recovery checks
+pub(crate) fn degree_of_success_no_nat(total: i32, dc: i32) -> Degree {
+    CritRules::default().degree_no_nat(total, dc)
+}
+
+impl Context<'_> {
+    /**
+     * As `CritRules::roll_check`, with the action's own modifiers stacked on top,
+     * under the engine's rules.
+     * The degree is then adjusted for the creature rolling; see `Context::adjusted`.
+     */
+    pub(crate) fn roll_check(&mut self, modifier: i32, dc: i32) -> Degree {
+        let degree = self.roll_unadjusted(modifier, dc);
+        self.adjusted(self.level, degree)
+    }
+
+    fn roll_unadjusted(&mut self, modifier: i32, dc: i32) -> Degree {
+        let modifier = modifier + combine(&self.modifiers);
+        match self.luck {
+            Luck::Single => self.crit_rules.roll_check(self.dice, modifier, dc),
+            luck => {
+                let natural = luck.keep(self.dice.d20(), self.dice.d20());
+                self.crit_rules.degree(natural, natural as i32 + modifier, dc)
+            }
+        }
+    }
+
+    // Rolls a (modifier, dc) check for each entry in one batch, as areas do, before adjustment
+    pub(crate) fn roll_checks(&mut self, checks: &[(i32, i32)]) -> Vec<Degree> {
+        let bonus = combine(&self.modifiers);
+        let naturals = self.dice.roll_many(&vec![(1, 20); checks.len()]);
+        naturals
+            .into_iter()
+            .zip(checks)
+            .map(|(natural, (modifier, dc))| {
+                self.crit_rules.degree(natural as u8, natural as i32 + modifier + bonus, *dc)
+            })
+            .collect()
+    }
+}
+
+// Whether a bare d20 meets the DC, for flat checks made in the middle of an action
+pub(crate) fn roll_flat_check(dice: &mut dyn Dice, dc: u8) -> bool {
+    dice.d20() >= dc
+}
+
+impl<T: 'static> Action<T> {
+    /**
+     * A flat check: a bare d20 against the DC, like DC 5 to target a concealed creature.
+     * It only passes or fails, so there are no critical results and no natural 20s;
+     * a DC over 20 can't be passed at all.
+     */
+    pub(crate) fn flat_check(
+        dc: u8,
+        on_pass: impl Fn(&mut Context<'_>) -> Vec<Update<T>> + Send + Sync + 'static,
+        on_fail: impl Fn(&mut Context<'_>) -> Vec<Update<T>> + Send + Sync + 'static,
+    ) -> Self {
+        Action::new(Arc::new(move |_, context| {
+            let updates = match roll_flat_check(context.dice, dc) {
+                true => on_pass(context),
+                false => on_fail(context),
+            };
+            ActionResponse::Valid(updates)
+        }))
+    }
+}
+
+// Whether an area's targets each roll their save, or one roll decides for them all
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SaveMode {
+    Individual,
+    Shared, // The first target rolls, and everyone gets its degree
+}
+
+impl Action<World> {
+    /**
+     * Each target rolls `save` against the caster's spell DC, in order.
+     * `on_result` turns a target's degree into the updates it suffers.
+     */
+    pub(crate) fn save(
+        caster: CreatureId,
+        targets: Vec<CreatureId>,
+        save: Save,
+        on_result: impl Fn(CreatureId, Degree) -> Vec<Update<World>> + Send + Sync + 'static,
+    ) -> Self {
+        Action::area(caster, targets, save, SaveMode::Individual, on_result)
+    }
+
+    // As `save`, choosing whether the targets share a single roll
+    pub(crate) fn area(
+        caster: CreatureId,
+        targets: Vec<CreatureId>,
+        save: Save,
+        mode: SaveMode,
+        on_result: impl Fn(CreatureId, Degree) -> Vec<Update<World>> + Send + Sync + 'static,
+    ) -> Self {
+        Action::new(Arc::new(move |state, context| {
+            let Some(caster) = state.base.creature(caster) else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            let dc = spell_dc(caster);
+            // Each target's conditions stack with the action's modifiers, which the batch adds
+            let shared = combine(&context.modifiers);
+            let stat = StatKind::Check(Statistic::Save(save));
+            let custom = &state.base.custom_conditions;
+            let (saving, checks): (Vec<_>, Vec<(i32, i32)>) = targets
+                .iter()
+                .filter_map(|&target| state.base.creature(target).map(|c| (target, c)))
+                .map(|(target, creature)| {
+                    let mut modifiers = context.modifiers.clone();
+                    modifiers.extend(creature.condition_modifiers(stat, custom));
+                    let conditions = combine(&modifiers) - shared;
+                    ((target, creature.level), (creature.save_modifier(save) + conditions, dc))
+                })
+                .unzip();
+            let degrees = match (mode, checks.first()) {
+                (SaveMode::Shared, Some(&(modifier, dc))) => {
+                    vec![context.roll_unadjusted(modifier, dc); checks.len()]
+                }
+                _ => context.roll_checks(&checks),
+            };
+            // A shared roll is still adjusted for each target in turn
+            let updates = saving
+                .into_iter()
+                .zip(degrees)
+                .flat_map(|((target, level), degree)| {
+                    on_result(target, context.adjusted(Some(level), degree))
+                })
+                .collect();
+            ActionResponse::Valid(updates)
+        }))
+    }
+
+    /**
+     * The actor rolls `skill` against a fixed DC.
+     * `on_result` turns the degree into the updates that follow.
+     */
+    pub(crate) fn skill_check(
+        actor: CreatureId,
+        skill: Skill,
+        dc: i32,
+        on_result: impl Fn(Degree, &mut Context<'_>) -> Vec<Update<World>> + Send + Sync + 'static,
+    ) -> Self {
+        Action::new(Arc::new(move |state, context| {
+            let Some(creature) = state.base.creature(actor) else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            let stat = StatKind::Check(Statistic::Skill(skill));
+            let custom = &state.base.custom_conditions;
+            let (degree, aid) = context.with_conditions(creature, custom, stat, |context| {
+                context.roll_aided(state, actor, skill, creature.skill_modifier(skill), dc)
+            });
+            ActionResponse::Valid(on_result(degree, context).into_iter().chain(aid).collect())
+        }))
+    }
+
+    /**
+     * The actor's skill against the target's defense, 10 + its modifier in `defense`,
+     * such as Athletics against Fortitude to Grapple. A one-shot modifier waiting for
+     * a check with the skill is used up.
+     */
+    pub(crate) fn opposed_check(
+        actor: CreatureId,
+        target: CreatureId,
+        skill: Skill,
+        defense: Statistic,
+        on_result: impl Fn(Degree, &mut Context<'_>) -> Vec<Update<World>> + Send + Sync + 'static,
+    ) -> Self {
+        Action::new(Arc::new(move |state, context| {
+            let (Some(acting), Some(defending)) =
+                (state.base.creature(actor), state.base.creature(target))
+            else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            let dc = 10 + defending.statistic_modifier(defense);
+            let stat = StatKind::Check(Statistic::Skill(skill));
+            let custom = &state.base.custom_conditions;
+            let (degree, used) = context.with_conditions(acting, custom, stat, |context| {
+                let roll = NextRoll::Check(Statistic::Skill(skill));
+                context.roll_consuming(&state.meta, actor, roll, acting.skill_modifier(skill), dc)
+            });
+            ActionResponse::Valid(on_result(degree, context).into_iter().chain(used).collect())
+        }))
+    }
+
+    // Intimidation against the target's Will DC; success frightens 1, a critical success 2
+    pub(crate) fn demoralize(actor: CreatureId, target: CreatureId) -> Self {
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            let Some(dc) = state.base.creature(target).map(|c| c.save_dc(Save::Will)) else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            let check = Action::skill_check(actor, Skill::Intimidation, dc, move |degree, context| {
+                let frightened = match degree {
+                    Degree::CriticalSuccess => 2,
+                    Degree::Success => 1,
+                    _ => return vec![],
+                };
+                vec![UpdateBuilder::new()
+                    .filter(Arc::new(|state| state))
+                    .target(target)
+                    .applies(Tag::Frightened(frightened))
+                    .tag(Tag::Degree(degree))
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id())]
+            });
+            check.apply(state, context)
+        }))
+        .with_cost(1)
+        .named("Demoralize")
+        .by(actor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::Metadata;
+    use crate::tags::TagSet;
+    use crate::dice::FixedDice;
+    use crate::engine::{Engine, TestBase};
+    use crate::field::Ability;
+    use crate::modifier::{Modifier, ModifierKind};
+    use crate::stats::Proficiency;
+    use crate::world::{Creature, WorldBuilder};
+
+    #[test]
+    fn degrees_step_with_naturals() {
+        assert_eq!(degree_of_success(10, 25, 15), Degree::CriticalSuccess);
+        assert_eq!(degree_of_success(10, 15, 15), Degree::Success);
+        assert_eq!(degree_of_success(10, 14, 15), Degree::Failure);
+        assert_eq!(degree_of_success(2, 5, 15), Degree::CriticalFailure);
+        assert_eq!(degree_of_success(20, 14, 15), Degree::Success);
+        assert_eq!(degree_of_success(1, 15, 15), Degree::Failure);
+    }
+
+    #[test]
+    fn naturals_can_be_ignored() {
+        assert_eq!(degree_of_success(20, 15, 15), Degree::CriticalSuccess);
+        assert_eq!(degree_of_success_no_nat(20, 15), Degree::Success);
+        assert_eq!(degree_of_success_no_nat(1, 1), Degree::Success);
+    }
+
+    #[test]
+    fn flat_checks_only_pass_or_fail() {
+        let passes = |natural, dc| {
+            let mut engine = Engine::for_base(TestBase::default());
+            engine.dice = Box::new(FixedDice::new(vec![natural]));
+            let tagged = |degree| {
+                move |context: &mut Context<'_>| {
+                    vec![UpdateBuilder::new()
+                        .filter(Arc::new(|state| state))
+                        .tag(Tag::Degree(degree))
+                        .build(context.next_id())]
+                }
+            };
+            engine.act(Action::flat_check(dc, tagged(Degree::Success), tagged(Degree::Failure)));
+            engine.step();
+            engine.updates[0].tags.contains(&Tag::Degree(Degree::Success))
+        };
+        assert!(passes(11, 11));
+        assert!(!passes(10, 11));
+        assert!(passes(20, 5));
+        assert!(!passes(20, 21));
+    }
+
+    #[test]
+    fn save_uses_caster_dc_and_each_targets_modifier() {
+        let mut wizard = Creature::new("Wizard", 30, 17);
+        wizard.level = 5;
+        wizard.key_ability = Ability::Intelligence;
+        wizard.abilities[Ability::Intelligence as usize] = 18;
+        wizard.proficiencies.insert(Statistic::Spell, Proficiency::Expert);
+        let mut rogue = Creature::new("Rogue", 20, 19);
+        rogue.abilities[Ability::Dexterity as usize] = 18;
+        rogue.proficiencies.insert(Statistic::Save(Save::Reflex), Proficiency::Trained);
+        let goblin = Creature::new("Goblin", 8, 16);
+
+        let world = WorldBuilder::new()
+            .add_creature(0, wizard)
+            .add_creature(1, rogue)
+            .add_creature(2, goblin)
+            .build();
+        let mut engine = Engine::for_base(world);
+        // Both roll 16 against DC 23: the rogue's +7 succeeds, the goblin's +0 fails
+        engine.dice = Box::new(FixedDice::new(vec![16, 16]));
+        engine.act(Action::save(0, vec![1, 2], Save::Reflex, |target, degree| {
+            vec![Update {
+                filter: Arc::new(move |mut state: State<World>| {
+                    let damage = if degree >= Degree::Success { 3 } else { 6 };
+                    state.base.creature_mut(target).unwrap().hp -= damage;
+                    state
+                }),
+                id: target,
+                target,
+                tags: TagSet::new(),
+                applies: TagSet::new(),
+                resolved: Resolved::Resolved,
+                meta: Metadata::new(),
+                outcome: Default::default(),
+            }]
+        }));
+        for _ in 0..3 {
+            engine.step();
+        }
+        assert_eq!(engine.state.base.creature(1).unwrap().hp, 17);
+        assert_eq!(engine.state.base.creature(2).unwrap().hp, 2);
+    }
+
+    #[test]
+    fn shared_saves_roll_once() {
+        let rolls = |mode| {
+            let mut world = World::default();
+            for id in 0..4 {
+                world.creatures.insert(id, Creature::new("Goblin", 8, 16));
+            }
+            let mut engine = Engine::for_base(world);
+            engine.act(Action::area(0, vec![1, 2, 3], Save::Reflex, mode, |_, _| vec![]));
+            engine.step();
+            engine.dice.position()
+        };
+        assert_eq!(rolls(SaveMode::Individual), 3);
+        assert_eq!(rolls(SaveMode::Shared), 1);
+    }
+
+    // A +6 Intimidation bard demoralizes a Will +2 (DC 12) goblin
+    fn demoralize(natural: u8) -> Vec<Tag> {
+        let mut bard = Creature::new("Bard", 16, 17);
+        bard.abilities[Ability::Charisma as usize] = 16;
+        bard.proficiencies.insert(Statistic::Skill(Skill::Intimidation), Proficiency::Trained);
+        let mut goblin = Creature::new("Goblin", 8, 16);
+        goblin.abilities[Ability::Wisdom as usize] = 14;
+        let world = WorldBuilder::new()
+            .add_creature(0, bard)
+            .add_creature(1, goblin)
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.dice = Box::new(FixedDice::new(vec![natural]));
+        engine.act(Action::demoralize(0, 1));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        engine.state.base.creature(1).unwrap().conditions.clone()
+    }
+
+    #[test]
+    fn demoralize_frightens_by_degree() {
+        assert_eq!(demoralize(2), vec![]); // 8, a failure
+        assert_eq!(demoralize(7), vec![Tag::Frightened(1)]); // 13, a success
+        assert_eq!(demoralize(17), vec![Tag::Frightened(2)]); // 23, a critical success
+    }
+
+    #[test]
+    fn opposed_check_beats_the_targets_defense() {
+        let mut monk = Creature::new("Monk", 18, 18);
+        monk.abilities[Ability::Strength as usize] = 16;
+        monk.proficiencies.insert(Statistic::Skill(Skill::Athletics), Proficiency::Trained);
+        let mut ogre = Creature::new("Ogre", 50, 17);
+        ogre.abilities[Ability::Constitution as usize] = 18;
+        let world = WorldBuilder::new()
+            .add_creature(0, monk)
+            .add_creature(1, ogre)
+            .build();
+        let mut engine = Engine::for_base(world);
+        let bonus = Modifier::new(ModifierKind::Circumstance, 4);
+        for skill in [Skill::Athletics, Skill::Acrobatics] {
+            engine.state.meta.add_one_shot(0, NextRoll::Check(Statistic::Skill(skill)), bonus);
+        }
+        // +6 Athletics and the +4 waiting for it roll 4 for 14 against the ogre's Fortitude DC
+        engine.dice = Box::new(FixedDice::new(vec![4]));
+        let grapple = Action::opposed_check(
+            0,
+            1,
+            Skill::Athletics,
+            Statistic::Save(Save::Fortitude),
+            |degree, context| {
+                vec![UpdateBuilder::new()
+                    .filter(Arc::new(|state| state))
+                    .target(1)
+                    .tag(Tag::Degree(degree))
+                    .build(context.next_id())]
+            },
+        );
+        engine.act(grapple);
+        engine.step();
+        assert!(engine.updates[0].tags.contains(&Tag::Degree(Degree::Success)));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        let waiting: Vec<NextRoll> = engine.state.meta.one_shots.iter().map(|o| o.roll).collect();
+        assert_eq!(waiting, vec![NextRoll::Check(Statistic::Skill(Skill::Acrobatics))]);
+    }
+
+    #[test]
+    fn contributed_modifiers_stack_onto_the_check() {
+        let world = WorldBuilder::new().add_creature(0, Creature::new("Ranger", 20, 18)).build();
+        let mut engine = Engine::for_base(world);
+        engine.dice = Box::new(FixedDice::new(vec![10]));
+        let check = |contributors: usize, dc: i32| {
+            let mut action = Action::skill_check(0, Skill::Survival, dc, |degree, context| {
+                vec![UpdateBuilder::new()
+                    .filter(Arc::new(|state| state))
+                    .tag(Tag::Degree(degree))
+                    .build(context.next_id())]
+            });
+            for _ in 0..contributors {
+                let penalty = Modifier::new(ModifierKind::Circumstance, -2);
+                action = action.with_modifier(Arc::new(move |_| penalty));
+            }
+            action
+        };
+        let degree = |engine: &mut Engine<World>, action| {
+            engine.act(action);
+            engine.step();
+            engine.updates[0].tags.iter().find_map(|tag| match tag {
+                Tag::Degree(degree) => Some(*degree),
+                _ => None,
+            })
+        };
+        assert_eq!(degree(&mut engine, check(0, 10)), Some(Degree::Success));
+        assert_eq!(degree(&mut engine, check(1, 10)), Some(Degree::Failure));
+        // Two circumstance penalties don't stack, so 10 - 2 still makes DC 8
+        assert_eq!(degree(&mut engine, check(2, 8)), Some(Degree::Success));
+    }
+}
@@ -0,0 +1,522 @@
+use std::sync::Arc;
+
+use crate::check::{roll_flat_check, Degree};
+use crate::cover::CONCEALED_DC;
+use crate::damage::{roll_damage, DamageCategory};
+use crate::effective::StatKind;
+use crate::engine::{
+    Action, ActionResponse, Filter, InvalidAction, Meta, Resolved, Rule, RuleResponse, State, Tag,
+    UpdateBuilder,
+};
+use crate::field::Ability;
+use crate::modifier::{Modifier, ModifierKind};
+use crate::oneshot::NextRoll;
+use crate::stats::{Proficiency, Statistic};
+use crate::world::{damage, Creature, CreatureId, World};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(dead_code)] // Every group in the rules, whether or not any weapon here is in it yet
+pub(crate) enum WeaponGroup {
+    Axe,
+    Bow,
+    Brawling,
+    Club,
+    Flail,
+    Hammer,
+    Knife,
+    Pick,
+    Polearm,
+    Spear,
+    Sword,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Weapon {
+    pub(crate) name: String,
+    pub(crate) group: WeaponGroup,
+    pub(crate) dice: u8, // Number of damage dice
+    pub(crate) die: u8,  // Sides on each damage die
+    pub(crate) reach: i32, // In feet; reach weapons threaten 10
+    pub(crate) range: Option<i32>, // Range increment in feet, for ranged weapons
+    pub(crate) deadly: Option<u8>, // Sides on the extra die a critical hit rolls
+}
+
+impl Weapon {
+    pub(crate) fn new(name: &str, group: WeaponGroup, dice: u8, die: u8) -> Self {
+        Weapon {
+            name: String::from(name),
+            group,
+            dice,
+            die,
+            reach: 5,
+            range: None,
+            deadly: None,
+        }
+    }
+
+    pub(crate) fn with_reach(mut self, feet: i32) -> Self {
+        self.reach = feet;
+        self
+    }
+
+    // Makes it a ranged weapon, fired at targets up to the range increment away
+    pub(crate) fn with_range(mut self, feet: i32) -> Self {
+        self.range = Some(feet);
+        self
+    }
+
+    // Gives it the deadly trait: a critical hit also rolls one die of this size
+    pub(crate) fn with_deadly(mut self, die: u8) -> Self {
+        self.deadly = Some(die);
+        self
+    }
+
+    // What an attack with it rolls, so clumsy lowers a bow shot and enfeebled a sword swing
+    pub(crate) fn attack_stat(&self) -> StatKind {
+        match self.range {
+            Some(_) => StatKind::RangedAttack(self.group),
+            None => StatKind::Check(Statistic::Weapon(self.group)),
+        }
+    }
+}
+
+impl Creature {
+    // Dexterity aims a ranged weapon, and Strength swings the rest
+    pub(crate) fn attack_modifier(&self, weapon: &Weapon) -> i32 {
+        let ability = match weapon.range {
+            Some(_) => Ability::Dexterity,
+            None => Ability::Strength,
+        };
+        self.modifier(ability) + self.proficiency_bonus(Statistic::Weapon(weapon.group))
+    }
+}
+
+// The Strike `Action::strike` and `Action::strike_as` build, taking the penalty for `attack`
+fn strike(
+    attacker: CreatureId,
+    target: CreatureId,
+    weapon: Weapon,
+    attack: Option<usize>, // Counted from the turn's attacks so far, if not given
+) -> Action<World> {
+    let (reach, range) = (weapon.reach, weapon.range);
+    let strike = Action::new(Arc::new(move |state: &State<World>, context| {
+        let (Some(attacking), Some(defending)) =
+            (state.base.creature(attacker), state.base.creature(target))
+        else {
+            return ActionResponse::Invalid(InvalidAction::BadPredicate);
+        };
+        let mut updates = vec![];
+        if defending.is_concealed() {
+            let passed = roll_flat_check(context.dice, CONCEALED_DC);
+            updates.push(
+                UpdateBuilder::new()
+                    .filter(counted(attacker, !passed, Arc::new(|state| state)))
+                    .target(target)
+                    .tag(Tag::FlatCheck {
+                        dc: CONCEALED_DC,
+                        passed,
+                    })
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id()),
+            );
+            if !passed {
+                return ActionResponse::Valid(updates);
+            }
+        }
+        let ac = defending.ac_against_attack(attacker, range.is_none());
+        let penalty = match attack {
+            Some(attack) => multiple_attack_penalty(attack),
+            None => state.meta.multiple_attack_penalty(attacker),
+        };
+        let modifier = attacking.attack_modifier(&weapon) + penalty.bonus;
+        let stat = weapon.attack_stat();
+        let custom = &state.base.custom_conditions;
+        let (degree, used) = context.with_conditions(attacking, custom, stat, |context| {
+            context.roll_consuming(&state.meta, attacker, NextRoll::Attack, modifier, ac)
+        });
+        let rolled = match degree {
+            Degree::Success | Degree::CriticalSuccess => {
+                let rolled = roll_damage(
+                    context.dice,
+                    weapon.dice,
+                    weapon.die,
+                    attacking.modifier(Ability::Strength),
+                    None,
+                    DamageCategory::Direct,
+                );
+                if degree == Degree::CriticalSuccess {
+                    Some(context.crit_rules.critical(context.dice, rolled))
+                } else {
+                    Some(rolled)
+                }
+            }
+            _ => None,
+        };
+        let deadly = match (degree, weapon.deadly) {
+            (Degree::CriticalSuccess, Some(die)) => {
+                Some(roll_damage(context.dice, 1, die, 0, None, DamageCategory::Direct))
+            }
+            _ => None,
+        };
+        let amount = rolled.iter().chain(&deadly).map(|rolled| rolled.total).sum();
+        let mut builder = UpdateBuilder::new();
+        if rolled.is_some() {
+            builder = builder.tag(Tag::Hit);
+        }
+        for rolled in rolled.into_iter().chain(deadly) {
+            builder = builder.tag(Tag::Damage(rolled));
+        }
+        let update = builder
+            .filter(counted(attacker, true, damage(target, amount)))
+            .target(target)
+            .tag(Tag::Attack {
+                attacker,
+                target,
+                group: weapon.group,
+                dice: weapon.dice,
+                weapon: weapon.name.clone(),
+            })
+            .tag(Tag::Degree(degree))
+            .resolved(Resolved::Resolved)
+            .build(context.next_id());
+        updates.push(update);
+        ActionResponse::Valid(updates.into_iter().chain(used).collect())
+    }))
+    .with_cost(1);
+    match range {
+        Some(range) => strike.with_target(attacker, target, range),
+        None => strike.with_melee_target(attacker, target, reach),
+    }
+    .named("Strike")
+}
+
+impl Action<World> {
+    /**
+     * A single-action Strike against the target's AC, melee unless the weapon has a range,
+     * 2 lower if the target is flat-footed to the attacker or prone against a melee attack.
+     * A concealed target first needs a DC 5 flat check, whose update comes
+     * before the attack; failing it loses the attack without a roll.
+     * The damage update carries the attack and its degree for rules to read,
+     * and `Tag::Hit` if it hit, for on-hit riders to key off.
+     * It takes the multiple attack penalty for the attacks already made this turn.
+     */
+    pub(crate) fn strike(attacker: CreatureId, target: CreatureId, weapon: Weapon) -> Self {
+        strike(attacker, target, weapon, None)
+    }
+
+    // As `strike`, but at the penalty for the turn's `attack`th attack, whatever came before
+    pub(crate) fn strike_as(
+        attacker: CreatureId,
+        target: CreatureId,
+        weapon: Weapon,
+        attack: usize,
+    ) -> Self {
+        strike(attacker, target, weapon, Some(attack))
+    }
+
+    /**
+     * Double Slice: a Strike with each weapon in turn, for two actions.
+     * Both take the penalty for `attack`, the attack the activity starts on,
+     * rather than the second taking the next one as a separate Strike would.
+     */
+    pub(crate) fn double_slice(
+        attacker: CreatureId,
+        target: CreatureId,
+        weapons: [Weapon; 2],
+        attack: usize,
+    ) -> Self {
+        Action::repeat(
+            2,
+            Box::new(move |i| Action::strike_as(attacker, target, weapons[i].clone(), attack)),
+        )
+        .named("Double Slice")
+    }
+}
+
+// The penalty on a turn's attacks after the first: -5 for the second, -10 from the third on
+pub(crate) fn multiple_attack_penalty(attack: usize) -> Modifier {
+    Modifier::new(ModifierKind::Untyped, -5 * attack.min(2) as i32)
+}
+
+// Counts an attack towards the penalty as the filter applies, if `counts`
+fn counted(attacker: CreatureId, counts: bool, filter: Filter<World>) -> Filter<World> {
+    Arc::new(move |state| {
+        let mut state = filter(state);
+        if counts {
+            *state.meta.attacks_this_turn.entry(attacker).or_insert(0) += 1;
+        }
+        state
+    })
+}
+
+impl Meta {
+    /**
+     * What the creature's next attack takes. Every attack this turn counts,
+     * even one lost to a flat check, and nothing else done in between resets it;
+     * only the next turn starting does.
+     */
+    pub(crate) fn multiple_attack_penalty(&self, attacker: CreatureId) -> Modifier {
+        multiple_attack_penalty(self.attacks_this_turn.get(&attacker).copied().unwrap_or(0))
+    }
+}
+
+/**
+ * On a critical hit by an attacker with at least Expert proficiency,
+ * swords leave the target flat-footed and picks deal 2 extra damage per weapon die.
+ * The other groups' effects aren't modelled yet.
+ */
+pub(crate) fn critical_specialization() -> Rule<World> {
+    Arc::new(|state, update| {
+        if !update.tags.contains(&Tag::Degree(Degree::CriticalSuccess)) {
+            return RuleResponse::Skip;
+        }
+        let Some((attacker, group, dice)) = update.tags.iter().find_map(|tag| match tag {
+            Tag::Attack {
+                attacker,
+                group,
+                dice,
+                ..
+            } => Some((*attacker, *group, *dice)),
+            _ => None,
+        }) else {
+            return RuleResponse::Skip;
+        };
+        let expert = state
+            .base
+            .creature(attacker)
+            .is_some_and(|c| c.proficiency(Statistic::Weapon(group)) >= Proficiency::Expert);
+        if !expert {
+            return RuleResponse::Skip;
+        }
+        let target = update.target;
+        match group {
+            WeaponGroup::Sword => RuleResponse::Apply(Tag::Flatfooted(None)),
+            WeaponGroup::Pick => RuleResponse::Inject(Action::new(Arc::new(move |_, context| {
+                let extra = UpdateBuilder::new()
+                    .filter(damage(target, 2 * dice as i32))
+                    .target(target)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![extra])
+            }))),
+            _ => RuleResponse::Skip,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::FixedDice;
+    use crate::engine::Engine;
+    use crate::world::{Position, WorldBuilder};
+
+    // A fighter with +4 Strength and the given proficiency swings at a 30 HP, AC 16 target
+    fn crit(group: WeaponGroup, proficiency: Proficiency) -> Engine<World> {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.abilities[Ability::Strength as usize] = 18;
+        fighter.proficiencies.insert(Statistic::Weapon(group), proficiency);
+        let world = WorldBuilder::new()
+            .add_creature(0, fighter)
+            .add_creature(1, Creature::new("Ogre", 30, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.rules.push(critical_specialization());
+        engine.dice = Box::new(FixedDice::new(vec![20, 5]));
+        engine.act(Action::strike(0, 1, Weapon::new("Weapon", group, 1, 8)));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        engine
+    }
+
+    #[test]
+    fn expert_sword_crit_leaves_the_target_flatfooted() {
+        let engine = crit(WeaponGroup::Sword, Proficiency::Expert);
+        let ogre = engine.state.base.creature(1).unwrap();
+        assert_eq!(ogre.hp, 30 - 18);
+        assert_eq!(ogre.conditions, vec![Tag::Flatfooted(None)]);
+    }
+
+    #[test]
+    fn flatfooted_from_a_source_only_helps_that_attacker() {
+        // A 15 against AC 16 only hits once the AC drops by 2
+        let hits = |conditions: Vec<Tag>, attacker| {
+            let mut ogre = Creature::new("Ogre", 30, 16);
+            ogre.conditions = conditions;
+            let world = WorldBuilder::new()
+                .add_creature(0, Creature::new("Fighter", 20, 18))
+                .add_creature(1, Creature::new("Rogue", 20, 18))
+                .add_creature(2, ogre)
+                .build();
+            let mut engine = Engine::for_base(world);
+            engine.dice = Box::new(FixedDice::new(vec![15, 3]));
+            engine.act(Action::strike(attacker, 2, Weapon::new("Club", WeaponGroup::Club, 1, 6)));
+            engine.step();
+            engine.updates[0].tags.contains(&Tag::Degree(Degree::Success))
+        };
+        assert!(hits(vec![Tag::Flatfooted(Some(0))], 0));
+        assert!(!hits(vec![Tag::Flatfooted(Some(0))], 1));
+        // Flat-footed from being prone has no source
+        assert!(hits(vec![Tag::Flatfooted(None)], 0));
+        assert!(hits(vec![Tag::Flatfooted(None)], 1));
+    }
+
+    #[test]
+    fn clumsy_lowers_a_ranged_attack_and_enfeebled_does_not() {
+        // +4 Dexterity and trained: a 9 hits AC 16 unless something takes a point off
+        let hits = |condition: Tag| {
+            let mut archer = Creature::new("Archer", 20, 18).with_condition(condition);
+            archer.abilities[Ability::Dexterity as usize] = 18;
+            archer.proficiencies.insert(Statistic::Weapon(WeaponGroup::Bow), Proficiency::Trained);
+            let world = WorldBuilder::new()
+                .add_creature(0, archer)
+                .add_creature(1, Creature::new("Ogre", 30, 16))
+                .build();
+            let mut engine = Engine::for_base(world);
+            engine.dice = Box::new(FixedDice::new(vec![9, 3]));
+            let bow = Weapon::new("Shortbow", WeaponGroup::Bow, 1, 6).with_range(60);
+            engine.act(Action::strike(0, 1, bow));
+            engine.step();
+            engine.updates[0].tags.contains(&Tag::Degree(Degree::Success))
+        };
+        assert!(hits(Tag::Enfeebled(2)));
+        assert!(!hits(Tag::Clumsy(1)));
+    }
+
+    #[test]
+    fn strike_damage_is_itemized_on_the_update() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 30, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.dice = Box::new(FixedDice::new(vec![18, 3, 5]));
+        engine.act(Action::strike(0, 1, Weapon::new("Greatclub", WeaponGroup::Club, 2, 6)));
+        engine.step();
+        let rolled = engine.updates[0].tags.iter().find_map(|tag| match tag {
+            Tag::Damage(rolled) => Some(rolled.to_string()),
+            _ => None,
+        });
+        assert_eq!(rolled.as_deref(), Some("2d6 = [3,5] = 8"));
+    }
+
+    #[test]
+    fn on_hit_rules_can_shove_the_target() {
+        let shoved = |rolls: Vec<u8>| {
+            let mut world = WorldBuilder::new()
+                .add_creature(0, Creature::new("Monk", 20, 18))
+                .build();
+            let mut ogre = Creature::new("Ogre", 30, 16);
+            ogre.position = Position::new(1, 0);
+            world.creatures.insert(1, ogre);
+            let mut engine = Engine::for_base(world);
+            // Pushes whatever was hit 5 feet further away
+            engine.rules.push(Arc::new(|_, update| match update.tags.contains(&Tag::Hit) {
+                true => RuleResponse::Inject(Action::forced_move(
+                    update.target,
+                    vec![Position::new(2, 0)],
+                )),
+                false => RuleResponse::Skip,
+            }));
+            engine.dice = Box::new(FixedDice::new(rolls));
+            engine.act(Action::strike(0, 1, Weapon::new("Fist", WeaponGroup::Brawling, 1, 4)));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            engine.state.base.creature(1).unwrap().position
+        };
+        assert_eq!(shoved(vec![17, 2]), Position::new(2, 0));
+        assert_eq!(shoved(vec![3, 2]), Position::new(1, 0));
+    }
+
+    #[test]
+    fn double_slice_strikes_twice_at_the_same_penalty() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 60, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        // A 16 only hits AC 16 with no penalty at all
+        engine.dice = Box::new(FixedDice::new(vec![16, 4]));
+        let weapons = [
+            Weapon::new("Longsword", WeaponGroup::Sword, 1, 8),
+            Weapon::new("Shortsword", WeaponGroup::Sword, 1, 6),
+        ];
+        engine.act(Action::double_slice(0, 1, weapons, 0));
+        engine.step();
+        let attacks: Vec<(String, bool)> = engine
+            .updates
+            .iter()
+            .filter_map(|update| {
+                update.tags.iter().find_map(|tag| match tag {
+                    Tag::Attack { weapon, .. } => {
+                        Some((weapon.clone(), update.tags.contains(&Tag::Hit)))
+                    }
+                    _ => None,
+                })
+            })
+            .collect();
+        assert_eq!(
+            attacks,
+            vec![(String::from("Longsword"), true), (String::from("Shortsword"), true)]
+        );
+        assert_eq!(engine.state.meta.actions_remaining, 1);
+    }
+
+    #[test]
+    fn moving_between_strikes_keeps_the_penalty() {
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 60, 16))
+            .build();
+        world.creature_mut(1).unwrap().position = Position::new(1, 0);
+        let mut engine = Engine::for_base(world);
+        // A 16 hits AC 16, but an 18 at -5 doesn't
+        engine.dice = Box::new(FixedDice::new(vec![16, 4, 18]));
+        let club = || Weapon::new("Club", WeaponGroup::Club, 1, 6);
+        let mut penalties = vec![];
+        for action in [
+            Action::strike(0, 1, club()),
+            Action::stride(0, vec![Position::new(0, 1)]),
+            Action::strike(0, 1, club()),
+        ] {
+            penalties.push(engine.state.meta.multiple_attack_penalty(0).bonus);
+            engine.act(action);
+            while !engine.is_idle() {
+                engine.step();
+            }
+        }
+        // The Stride in between leaves the second Strike at -5
+        assert_eq!(penalties, vec![0, -5, -5]);
+        let degrees: Vec<Degree> = engine
+            .log()
+            .iter()
+            .flat_map(|entry| entry.tags.iter())
+            .filter_map(|tag| match tag {
+                Tag::Degree(degree) => Some(*degree),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(degrees, vec![Degree::Success, Degree::Failure]);
+        assert_eq!(engine.state.base.creature(0).unwrap().position, Position::new(0, 1));
+        assert_eq!(engine.state.meta.attacks_this_turn[&0], 2);
+        engine.state.meta.reset_turn();
+        assert_eq!(engine.state.meta.multiple_attack_penalty(0).bonus, 0);
+    }
+
+    #[test]
+    fn trained_sword_crit_has_no_specialization() {
+        let engine = crit(WeaponGroup::Sword, Proficiency::Trained);
+        let ogre = engine.state.base.creature(1).unwrap();
+        assert_eq!(ogre.hp, 30 - 18);
+        assert!(ogre.conditions.is_empty());
+    }
+
+    #[test]
+    fn expert_pick_crit_deals_extra_damage() {
+        let engine = crit(WeaponGroup::Pick, Proficiency::Expert);
+        assert_eq!(engine.state.base.creature(1).unwrap().hp, 30 - 18 - 2);
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use crate::effective::StatKind;
+use crate::engine::Tag;
+use crate::field::Ability;
+use crate::modifier::{Modifier, ModifierKind};
+
+// Which stats a custom condition's modifier applies to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum StatCategory {
+    Everything, // AC and every check, like frightened
+    ArmorClass,
+    Ability(Ability), // The stats based on the ability, like clumsy for Dexterity
+}
+
+/** ConditionDef
+ *  What a condition defined by content does, for `Tag::Custom` to follow.
+ *  It gives a `kind` modifier of `modifier` for each point of its value, or just
+ *  `modifier` if it has no value: negative for a penalty like hexed's,
+ *  positive for a bonus like those auras give.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct ConditionDef {
+    pub(crate) affects: StatCategory,
+    pub(crate) modifier: i32,
+    pub(crate) valued: bool,
+    pub(crate) decrements_eot: bool, // Goes down by one at the end of the creature's turn
+    pub(crate) kind: ModifierKind,   // Status for most conditions, so they don't stack
+}
+
+impl ConditionDef {
+    fn applies_to(&self, stat: StatKind) -> bool {
+        match self.affects {
+            StatCategory::Everything => true,
+            StatCategory::ArmorClass => stat == StatKind::ArmorClass,
+            StatCategory::Ability(ability) => stat.based_on(&[ability]),
+        }
+    }
+}
+
+// Custom conditions by key; tags with a key nothing defines do nothing
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct ConditionRegistry {
+    defs: BTreeMap<String, ConditionDef>,
+}
+
+impl ConditionRegistry {
+    pub(crate) fn register(&mut self, key: &str, def: ConditionDef) {
+        self.defs.insert(String::from(key), def);
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&ConditionDef> {
+        self.defs.get(key)
+    }
+
+    // What the custom condition does to the stat, if it is registered and affects it
+    pub(crate) fn modifier(&self, condition: &Tag, stat: StatKind) -> Option<Modifier> {
+        let Tag::Custom { key, value } = condition else {
+            return None;
+        };
+        let def = self.get(key).filter(|def| def.applies_to(stat))?;
+        let points = if def.valued { *value as i32 } else { 1 };
+        Some(Modifier::new(def.kind, def.modifier * points))
+    }
+
+    // Whether the condition is a custom one that fades at the end of its holder's turn
+    pub(crate) fn decrements(&self, condition: &Tag) -> bool {
+        match condition {
+            Tag::Custom { key, .. } => self.get(key).is_some_and(|def| def.decrements_eot),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Meta, State};
+    use crate::stats::{Save, Skill, Statistic};
+    use crate::world::{Creature, WorldBuilder};
+
+    #[test]
+    fn custom_conditions_follow_their_definition() {
+        let hexed = || Tag::Custom {
+            key: String::from("hexed"),
+            value: 2,
+        };
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18).with_condition(hexed()))
+            .build();
+        world.custom_conditions.register(
+            "hexed",
+            ConditionDef {
+                affects: StatCategory::Ability(Ability::Wisdom),
+                modifier: -1,
+                valued: true,
+                decrements_eot: true,
+                kind: ModifierKind::Status,
+            },
+        );
+        let mut state = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+        let will = StatKind::Check(Statistic::Save(Save::Will));
+        assert_eq!(
+            state.effective(0, will, None).1,
+            vec![(String::from("base"), 0), (String::from("hexed 2"), -2)]
+        );
+        assert_eq!(state.effective(0, StatKind::ArmorClass, None).0, 18);
+        // Only the worst status penalty counts, built-in or not
+        let fighter = state.base.creature_mut(0).unwrap();
+        fighter.add_condition(Tag::Frightened(1));
+        let perception = StatKind::Check(Statistic::Perception);
+        assert_eq!(state.effective(0, perception, None).0, -2);
+        let athletics = StatKind::Check(Statistic::Skill(Skill::Athletics));
+        assert_eq!(state.effective(0, athletics, None).0, -1);
+
+        let registry = state.base.custom_conditions.clone();
+        let fighter = state.base.creature_mut(0).unwrap();
+        fighter.end_turn(&registry);
+        let lowered = Tag::Custom {
+            key: String::from("hexed"),
+            value: 1,
+        };
+        assert_eq!(fighter.conditions, vec![lowered]);
+        fighter.end_turn(&registry);
+        assert!(fighter.conditions.is_empty());
+
+        state.base.custom_conditions.register(
+            "warded",
+            ConditionDef {
+                affects: StatCategory::ArmorClass,
+                modifier: 1,
+                valued: false,
+                decrements_eot: false,
+                kind: ModifierKind::Status,
+            },
+        );
+        let warded = Tag::Custom {
+            key: String::from("warded"),
+            value: 0,
+        };
+        state.base.creature_mut(0).unwrap().add_condition(warded);
+        let (ac, itemized) = state.effective(0, StatKind::ArmorClass, None);
+        assert_eq!(ac, 19);
+        assert_eq!(itemized[1], (String::from("warded"), 1));
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use crate::engine::{
+    priority_of, Action, Base, Engine, Rule, RuleResponse, State, Tag, Update,
+};
+use crate::tags::TagSet;
+
+// A problem with the actions and rules loaded into an engine
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ContentError {
+    DuplicateAction(String),
+    DuplicateRule(String),
+    UnknownRule { action: String, rule: String }, // The action needs a rule nothing provides
+    UndeclaredTag { action: String, tag: Tag }, // Found while linting, see `Action::declares`
+}
+
+/** RuleDescriptor
+ *  One rule as a debugging tool would show it. Rules are asked about each update
+ *  in list order; priority only decides which of their interrupts wins.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct RuleDescriptor {
+    pub(crate) index: usize,          // Its place in the evaluation order
+    pub(crate) label: Option<String>, // Unlabelled rules were added without a name
+    pub(crate) priority: i32,
+    pub(crate) trigger: Option<Tag>, // The only kind of tag it looks at, if limited to one
+}
+
+// What the list holds for the rule, for the side tables keyed by rule
+fn entry_for<'a, T, V>(list: &'a [(Rule<T>, V)], rule: &Rule<T>) -> Option<&'a V> {
+    list.iter().find(|(r, _)| Arc::ptr_eq(r, rule)).map(|(_, entry)| entry)
+}
+
+impl<T> Action<T> {
+    pub(crate) fn needs_rule(mut self, name: &str) -> Self {
+        self.rules_needed.push(String::from(name));
+        self
+    }
+
+    /**
+     * Declares a kind of tag the action's updates may carry, whatever its value,
+     * so tools can say what it does without running it.
+     * Once an action declares any, a linting engine flags every other kind it generates.
+     */
+    pub(crate) fn declares(mut self, tag: Tag) -> Self {
+        self.declared_tags.get_or_insert_with(TagSet::new).insert(tag);
+        self
+    }
+}
+
+// Every tag the updates carry that the action didn't declare, if it declared any
+pub(crate) fn undeclared_tags<T>(action: &Action<T>, updates: &[Update<T>]) -> Vec<ContentError> {
+    let Some(declared) = &action.declared_tags else {
+        return vec![];
+    };
+    let name = action.name.clone().unwrap_or_else(|| String::from("unnamed"));
+    updates
+        .iter()
+        .flat_map(|update| update.tags.iter())
+        .filter(|tag| !declared.iter().any(|d| d.same_kind(tag)))
+        .map(|tag| ContentError::UndeclaredTag {
+            action: name.clone(),
+            tag: tag.clone(),
+        })
+        .collect()
+}
+
+impl<T: Base> Engine<T> {
+    pub(crate) fn add_named_rule(&mut self, name: &str, rule: Rule<T>) {
+        self.rules.push(rule.clone());
+        self.rule_names.push((rule, String::from(name)));
+    }
+
+    // Every rule in the order the engine asks them, for showing why rules interact as they do
+    pub(crate) fn rule_order(&self) -> Vec<RuleDescriptor> {
+        self.rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| RuleDescriptor {
+                index,
+                label: entry_for(&self.rule_names, rule).cloned(),
+                priority: priority_of(&self.priorities, rule),
+                trigger: entry_for(&self.rule_triggers, rule).cloned(),
+            })
+            .collect()
+    }
+
+    /**
+     * Checks the registry and named rules before anything is run:
+     * every name must be unique, and every rule an action needs must be present.
+     * All the problems are reported together, in registry order.
+     */
+    pub(crate) fn validate_content(&self) -> Result<(), Vec<ContentError>> {
+        let mut errors = vec![];
+        let mut rules = BTreeSet::new();
+        // Named rules that have since been removed no longer count
+        let present = self
+            .rule_names
+            .iter()
+            .filter(|(rule, _)| self.rules.iter().any(|r| Arc::ptr_eq(r, rule)));
+        for (_, name) in present {
+            if !rules.insert(name.as_str()) {
+                errors.push(ContentError::DuplicateRule(name.clone()));
+            }
+        }
+        let mut actions = BTreeSet::new();
+        for (name, action) in self.registry.iter() {
+            if !actions.insert(name.as_str()) {
+                errors.push(ContentError::DuplicateAction(name.clone()));
+            }
+            for rule in action.rules_needed.iter() {
+                if !rules.contains(rule.as_str()) {
+                    errors.push(ContentError::UnknownRule {
+                        action: name.clone(),
+                        rule: rule.clone(),
+                    });
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl<T: Base + 'static> Engine<T> {
+    /**
+     * Adds a named rule with its priority, as `add_rule_with_priority`.
+     * Given a trigger, the rule only sees updates carrying a tag of that kind
+     * and skips the rest.
+     */
+    pub(crate) fn add_labelled_rule(
+        &mut self,
+        label: &str,
+        rule: Rule<T>,
+        priority: i32,
+        trigger: Option<Tag>,
+    ) {
+        let rule = match trigger.clone() {
+            Some(trigger) => Arc::new(move |state: &State<T>, update: &Update<T>| {
+                match update.tags.iter().any(|tag| tag.same_kind(&trigger)) {
+                    true => rule(state, update),
+                    false => RuleResponse::Skip,
+                }
+            }),
+            None => rule,
+        };
+        self.add_rule_with_priority(rule.clone(), priority);
+        self.rule_names.push((rule.clone(), String::from(label)));
+        if let Some(trigger) = trigger {
+            self.rule_triggers.push((rule, trigger));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{ActionResponse, Resolved, RuleResponse, State, TestBase, UpdateBuilder};
+
+    fn engine() -> Engine<TestBase> {
+        Engine::for_base(TestBase::default())
+    }
+
+    fn action() -> Action<TestBase> {
+        Action::new(Arc::new(|_, _| ActionResponse::Valid(vec![])))
+    }
+
+    #[test]
+    fn linting_flags_undeclared_tags() {
+        let mut engine = engine();
+        engine.linting = true;
+        let demoralize = || {
+            Action::new(Arc::new(|_, context| {
+                let update = UpdateBuilder::new()
+                    .filter(Arc::new(|state| state))
+                    .tag(Tag::Frightened(2))
+                    .tag(Tag::Manipulate)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![update])
+            }))
+            .named("Demoralize")
+            .declares(Tag::Frightened(1))
+        };
+        for action in [demoralize(), demoralize().declares(Tag::Manipulate)] {
+            engine.act(action);
+            while !engine.is_idle() {
+                engine.step();
+            }
+        }
+        assert_eq!(
+            engine.lints,
+            vec![ContentError::UndeclaredTag {
+                action: String::from("Demoralize"),
+                tag: Tag::Manipulate,
+            }]
+        );
+    }
+
+    #[test]
+    fn rules_are_listed_in_evaluation_order() {
+        let mut engine = engine();
+        let skip = || -> Rule<TestBase> { Arc::new(|_, _| RuleResponse::Skip) };
+        let frighten = |_: &State<TestBase>, _: &Update<TestBase>| {
+            RuleResponse::Attach(Tag::Frightened(1))
+        };
+        engine.add_labelled_rule("Counterspell", skip(), 5, None);
+        engine.rules.push(skip());
+        engine.add_labelled_rule("Dread", Arc::new(frighten), -1, Some(Tag::Manipulate));
+        let order = engine.rule_order();
+        assert_eq!(order.iter().map(|rule| rule.index).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(
+            order.iter().map(|rule| rule.label.clone()).collect::<Vec<_>>(),
+            vec![Some(String::from("Counterspell")), None, Some(String::from("Dread"))]
+        );
+        assert_eq!(order.iter().map(|rule| rule.priority).collect::<Vec<_>>(), vec![5, 0, -1]);
+        assert_eq!(order[2].trigger, Some(Tag::Manipulate));
+
+        // Dread only frightens on manipulating updates
+        for (tag, frightened) in [(Tag::Manipulate, true), (Tag::Clumsy(1), false)] {
+            engine.act(Action::new(Arc::new(move |_, context| {
+                let update = UpdateBuilder::new()
+                    .filter(Arc::new(|state| state))
+                    .tag(tag.clone())
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![update])
+            })));
+            engine.step();
+            engine.step();
+            assert_eq!(engine.updates[0].tags.contains(&Tag::Frightened(1)), frightened);
+        }
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let mut engine = engine();
+        engine.add_named_rule("Attack of Opportunity", Arc::new(|_, _| RuleResponse::Skip));
+        engine.register("Strike", action().needs_rule("Attack of Opportunity"));
+        assert_eq!(engine.validate_content(), Ok(()));
+
+        engine.register("Trip", action().needs_rule("Prone"));
+        engine.register("Strike", action());
+        assert_eq!(
+            engine.validate_content(),
+            Err(vec![
+                ContentError::UnknownRule {
+                    action: String::from("Trip"),
+                    rule: String::from("Prone"),
+                },
+                ContentError::DuplicateAction(String::from("Strike")),
+            ])
+        );
+    }
+}
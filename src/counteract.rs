@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use crate::check::Degree;
+use crate::effective::StatKind;
+use crate::engine::{
+    Action, ActionResponse, InvalidAction, Resolved, State, Tag, Update, UpdateBuilder,
+};
+use crate::metadata::Value;
+use crate::stats::Statistic;
+use crate::world::{target_filter, Creature, CreatureId, World};
+
+/** MagicalEffect
+ *  A spell or other magic on a creature, kept by id so it can be counteracted
+ *  on its own. Its condition is on the creature for as long as it lasts.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct MagicalEffect {
+    pub(crate) id: usize,
+    pub(crate) rank: u8, // The rank of the spell, or half the level of whatever made it
+    pub(crate) dc: i32,  // What a counteract check must beat, usually its caster's spell DC
+    pub(crate) condition: Tag,
+}
+
+impl Creature {
+    pub(crate) fn add_effect(&mut self, effect: MagicalEffect) {
+        self.add_condition(effect.condition.clone());
+        self.effects.push(effect);
+    }
+
+    /**
+     * Ends the effect, and the condition it gave if nothing else still gives it.
+     * Another effect of the same kind leaves its own condition in place, and a held
+     * condition different from the effect's came from somewhere else, so it stays too.
+     */
+    pub(crate) fn remove_effect(&mut self, id: usize) {
+        let Some(at) = self.effects.iter().position(|effect| effect.id == id) else {
+            return;
+        };
+        let effect = self.effects.remove(at);
+        let Some(held) = self.conditions.iter().position(|c| *c == effect.condition) else {
+            return;
+        };
+        let rest = self.effects.iter().map(|other| &other.condition);
+        match rest.filter(|c| c.same_kind(&effect.condition)).max() {
+            Some(best) => self.conditions[held] = best.clone(),
+            None => {
+                self.conditions.remove(held);
+            }
+        }
+    }
+}
+
+impl<F> UpdateBuilder<F> {
+    // Marks the update as coming from a magical effect, for rules that care which
+    pub(crate) fn effect(self, id: usize, rank: u8) -> Self {
+        self.meta("effect_id", Value::Int(id as i32))
+            .meta("source_rank", Value::Int(rank as i32))
+    }
+}
+
+impl<T> Update<T> {
+    // The magical effect this update belongs to and its rank, if it has one
+    pub(crate) fn effect_source(&self) -> Option<(usize, u8)> {
+        let id = self.meta_int("effect_id")?;
+        let rank = self.meta_int("source_rank")?;
+        Some((id as usize, rank as u8))
+    }
+}
+
+// Whether a counteract check's degree is enough for an effect of `target` rank
+pub(crate) fn counteracts(degree: Degree, counteract_rank: u8, target: u8) -> bool {
+    match degree {
+        Degree::CriticalSuccess => target <= counteract_rank + 3,
+        Degree::Success => target <= counteract_rank + 1,
+        Degree::Failure => target < counteract_rank,
+        Degree::CriticalFailure => false,
+    }
+}
+
+impl Action<World> {
+    // Puts the magical effect on the target
+    pub(crate) fn magical_effect(target: CreatureId, effect: MagicalEffect) -> Self {
+        Action::new(Arc::new(move |state, context| {
+            if state.base.creature(target).is_none() {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            }
+            let cast = effect.clone();
+            let update = UpdateBuilder::new()
+                .filter(target_filter(target, move |creature| creature.add_effect(cast.clone())))
+                .target(target)
+                .effect(effect.id, effect.rank)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+    }
+
+    /**
+     * The caster tries to end one magical effect, wherever it is, with a spell
+     * check against the effect's DC. How well it rolls decides how high a rank
+     * it can end, compared with `counteract_rank`.
+     */
+    pub(crate) fn counteract(
+        caster: CreatureId,
+        target_effect: usize,
+        counteract_rank: u8,
+    ) -> Self {
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            let holder = state.base.creatures.iter().find_map(|(&id, creature)| {
+                let effect = creature.effects.iter().find(|effect| effect.id == target_effect);
+                effect.map(|effect| (id, effect))
+            });
+            let (Some(countering), Some((holder, effect))) =
+                (state.base.creature(caster), holder)
+            else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            let stat = StatKind::Check(Statistic::Spell);
+            let custom = &state.base.custom_conditions;
+            let degree = context.with_conditions(countering, custom, stat, |context| {
+                context.roll_check(countering.statistic_modifier(Statistic::Spell), effect.dc)
+            });
+            let ended = counteracts(degree, counteract_rank, effect.rank);
+            let update = UpdateBuilder::new()
+                .filter(target_filter(holder, move |creature| {
+                    if ended {
+                        creature.remove_effect(target_effect);
+                    }
+                }))
+                .target(holder)
+                .tag(Tag::Degree(degree))
+                .effect(effect.id, effect.rank)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+        .named("Counteract")
+        .by(caster)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::FixedDice;
+    use crate::engine::Engine;
+    use crate::field::Ability;
+    use crate::stats::Proficiency;
+    use crate::world::WorldBuilder;
+
+    #[test]
+    fn only_a_good_enough_check_ends_the_effect() {
+        let mut wizard = Creature::new("Wizard", 16, 15);
+        wizard.key_ability = Ability::Intelligence;
+        wizard.abilities[Ability::Intelligence as usize] = 18;
+        wizard.proficiencies.insert(Statistic::Spell, Proficiency::Trained);
+        let world = WorldBuilder::new()
+            .add_creature(0, wizard)
+            .add_creature(1, Creature::new("Fighter", 20, 18))
+            .build();
+        let mut engine = Engine::for_base(world);
+        let fear = MagicalEffect {
+            id: 4,
+            rank: 2,
+            dc: 18,
+            condition: Tag::Frightened(2),
+        };
+        engine.act(Action::magical_effect(1, fear));
+        engine.step();
+        assert_eq!(engine.updates[0].effect_source(), Some((4, 2)));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        // +7 to the check: a 5 fails, and a rank 1 counteract can't end rank 2 on a failure
+        engine.dice = Box::new(FixedDice::new(vec![5, 12]));
+        let mut frightened = vec![];
+        for _ in 0..2 {
+            engine.act(Action::counteract(0, 4, 1));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            let fighter = engine.state.base.creature(1).unwrap();
+            frightened.push((fighter.effects.len(), fighter.conditions.clone()));
+        }
+        assert_eq!(frightened, vec![(1, vec![Tag::Frightened(2)]), (0, vec![])]);
+        engine.act(Action::counteract(0, 4, 1));
+        engine.step();
+        assert_eq!(engine.rejected, Some(InvalidAction::BadPredicate));
+    }
+
+    #[test]
+    fn ending_an_effect_keeps_conditions_from_elsewhere() {
+        let effect = |id, condition| MagicalEffect { id, rank: 1, dc: 15, condition };
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.add_effect(effect(0, Tag::Enfeebled(1)));
+        fighter.add_effect(effect(1, Tag::Enfeebled(2)));
+        // Demoralized on top of a weaker fear spell
+        fighter.add_effect(effect(2, Tag::Frightened(1)));
+        fighter.add_condition(Tag::Frightened(2));
+
+        fighter.remove_effect(1);
+        fighter.remove_effect(2);
+        assert_eq!(fighter.conditions, vec![Tag::Enfeebled(1), Tag::Frightened(2)]);
+        fighter.remove_effect(0);
+        assert_eq!(fighter.conditions, vec![Tag::Frightened(2)]);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::engine::Tag;
+use crate::world::Creature;
+
+// How much of a creature something stands in front of
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum CoverLevel {
+    Lesser,
+    Standard,
+    Greater,
+}
+
+impl CoverLevel {
+    // The circumstance bonus it gives to AC
+    pub(crate) fn bonus(&self) -> i32 {
+        match self {
+            CoverLevel::Lesser => 1,
+            CoverLevel::Standard => 2,
+            CoverLevel::Greater => 4,
+        }
+    }
+
+    pub(crate) fn name(&self) -> String {
+        format!("{} cover", format!("{self:?}").to_lowercase())
+    }
+}
+
+// Attackers must pass this flat check to target a concealed creature at all
+pub(crate) const CONCEALED_DC: u8 = 5;
+
+impl Creature {
+    pub(crate) fn is_concealed(&self) -> bool {
+        self.conditions.contains(&Tag::Concealed)
+    }
+
+    // Only the best cover counts, as for any circumstance bonus
+    pub(crate) fn cover_bonus(&self) -> i32 {
+        self.conditions
+            .iter()
+            .filter_map(|condition| match condition {
+                Tag::Cover(level) => Some(level.bonus()),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::dice::FixedDice;
+    use crate::effective::StatKind;
+    use crate::engine::{Action, Engine};
+    use crate::world::{World, WorldBuilder};
+
+    fn engine(goblin: Creature) -> Engine<World> {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, goblin)
+            .build();
+        Engine::for_base(world)
+    }
+
+    #[test]
+    fn attacks_on_the_concealed_need_a_flat_check() {
+        let goblin = Creature::new("Goblin", 30, 10).with_condition(Tag::Concealed);
+        let club = || Weapon::new("Club", WeaponGroup::Club, 1, 6);
+        let mut hits = vec![];
+        // A 4 loses the attack before it's rolled; a 5 lets the 15 hit
+        for rolls in [vec![4, 15, 6], vec![5, 15, 6]] {
+            let mut engine = engine(goblin.clone());
+            engine.dice = Box::new(FixedDice::new(rolls));
+            engine.act(Action::strike(0, 1, club()));
+            engine.step();
+            let check = engine.updates[0].tags.iter().find_map(|tag| match tag {
+                Tag::FlatCheck { dc, passed } => Some((*dc, *passed)),
+                _ => None,
+            });
+            while !engine.is_idle() {
+                engine.step();
+            }
+            hits.push((check, engine.state.base.creature(1).unwrap().hp));
+        }
+        assert_eq!(hits, vec![(Some((5, false)), 30), (Some((5, true)), 24)]);
+    }
+
+    #[test]
+    fn standard_cover_raises_ac_by_2() {
+        let cover = Tag::Cover(CoverLevel::Standard);
+        let goblin = Creature::new("Goblin", 8, 16).with_condition(cover);
+        assert_eq!(goblin.ac_against(0), 18);
+        let engine = engine(goblin);
+        let (ac, itemized) = engine.state.effective(1, StatKind::ArmorClass, None);
+        assert_eq!(ac, 18);
+        assert_eq!(itemized[1], (String::from("standard cover"), 2));
+        // Raising a shield behind cover doesn't stack with it
+        let goblin = engine.state.base.creature(1).unwrap().clone();
+        assert_eq!(goblin.with_condition(Tag::ShieldRaised).ac_against(0), 18);
+        // Better cover replaces worse
+        let goblin = Creature::new("Goblin", 8, 16).with_condition(Tag::Cover(CoverLevel::Lesser));
+        assert_eq!(goblin.ac_against(0), 17);
+        assert_eq!(goblin.with_condition(Tag::Cover(CoverLevel::Greater)).ac_against(0), 20);
+    }
+}
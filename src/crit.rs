@@ -0,0 +1,104 @@
+use crate::check::Degree;
+use crate::damage::DamageBreakdown;
+use crate::dice::Dice;
+
+/** CritRules
+ *  How a table decides and deals critical results. The defaults are the rules as written;
+ *  house rules can crit on a lower natural roll, double only a crit's dice, or
+ *  drop the 10-over-the-DC critical altogether.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct CritRules {
+    pub(crate) threshold: u8, // A natural roll of this or higher improves the result a step
+    pub(crate) double_modifiers: bool, // Otherwise a critical hit rolls twice the dice instead
+    pub(crate) by_ten: bool, // Beating or missing the DC by 10 moves the result a step
+}
+
+impl Default for CritRules {
+    fn default() -> Self {
+        CritRules {
+            threshold: 20,
+            double_modifiers: true,
+            by_ten: true,
+        }
+    }
+}
+
+impl CritRules {
+    // The degree before any natural roll is taken into account
+    pub(crate) fn degree_no_nat(&self, total: i32, dc: i32) -> Degree {
+        match total >= dc {
+            true if self.by_ten && total >= dc + 10 => Degree::CriticalSuccess,
+            true => Degree::Success,
+            false if self.by_ten && total <= dc - 10 => Degree::CriticalFailure,
+            false => Degree::Failure,
+        }
+    }
+
+    pub(crate) fn degree(&self, natural: u8, total: i32, dc: i32) -> Degree {
+        let degree = self.degree_no_nat(total, dc);
+        if natural >= self.threshold {
+            degree.up()
+        } else if natural == 1 {
+            degree.down()
+        } else {
+            degree
+        }
+    }
+
+    // Rolls a d20, adds the modifier and compares against the DC
+    pub(crate) fn roll_check(&self, dice: &mut dyn Dice, modifier: i32, dc: i32) -> Degree {
+        // Staged first, so the roll a host was shown is the roll that counts
+        let staged = dice.peek();
+        let natural = dice.d20();
+        debug_assert!(
+            staged.is_none_or(|staged| staged == natural),
+            "the dice rolled what they didn't show"
+        );
+        self.degree(natural, natural as i32 + modifier, dc)
+    }
+
+    // Makes a hit's damage critical: doubled, or with as many dice again rolled on top
+    pub(crate) fn critical(&self, dice: &mut dyn Dice, rolled: DamageBreakdown) -> DamageBreakdown {
+        if self.double_modifiers {
+            return rolled.doubled();
+        }
+        let mut rolled = rolled;
+        let extra: Vec<u8> = rolled.rolls.iter().map(|_| dice.roll(rolled.die)).collect();
+        rolled.total += extra.iter().map(|&r| r as i32).sum::<i32>();
+        rolled.rolls.extend(extra);
+        rolled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::{roll_damage, DamageCategory};
+    use crate::dice::FixedDice;
+
+    #[test]
+    fn house_rules_change_what_crits_and_how_hard() {
+        let rules = CritRules {
+            threshold: 19,
+            double_modifiers: false,
+            by_ten: false,
+        };
+        // A 19 now improves a hit, but 10 over the DC alone doesn't
+        assert_eq!(rules.degree(19, 25, 15), Degree::CriticalSuccess);
+        assert_eq!(rules.degree(12, 25, 15), Degree::Success);
+        assert_eq!(rules.degree(12, 4, 15), Degree::Failure);
+        assert_eq!(CritRules::default().degree(19, 25, 15), Degree::CriticalSuccess);
+        assert_eq!(CritRules::default().degree(19, 20, 15), Degree::Success);
+
+        let mut dice = FixedDice::new(vec![3, 5, 2, 6, 3, 5]);
+        let hit = |dice: &mut FixedDice| roll_damage(dice, 2, 6, 4, None, DamageCategory::Direct);
+        let rolled = hit(&mut dice);
+        let dice_only = rules.critical(&mut dice, rolled);
+        assert_eq!(dice_only.to_string(), "4d6+4 = [3,5,2,6]+4 = 20");
+        let rolled = hit(&mut dice);
+        let doubled = CritRules::default().critical(&mut dice, rolled);
+        assert_eq!(doubled.to_string(), "2d6+4 = ([3,5]+4)x2 = 24");
+    }
+}
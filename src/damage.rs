@@ -0,0 +1,215 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::check::Degree;
+use crate::dice::Dice;
+use crate::engine::{Action, ActionResponse, Filter, InvalidAction, Resolved, Tag, UpdateBuilder};
+use crate::effective::StatKind;
+use crate::stats::{Save, Statistic};
+use crate::world::{damage, typed_damage, CreatureId, DamageType, World};
+
+// Persistent and splash damage are itemized apart from the hit itself
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum DamageCategory {
+    Direct,
+    #[allow(dead_code)] // No hit itemizes persistent damage apart yet
+    Persistent,
+    Splash,
+}
+
+/** DamageBreakdown
+ *  One damage roll as it was made: each die face, the modifier, and the total.
+ *  Displays like "2d6+4 fire = [3,5]+4 = 12" for a combat log.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct DamageBreakdown {
+    pub(crate) category: DamageCategory,
+    pub(crate) kind: Option<DamageType>, // None when the source has no damage type yet
+    pub(crate) die: u8,
+    pub(crate) rolls: Vec<u8>,
+    pub(crate) modifier: i32,
+    pub(crate) multiplier: i32, // 2 on a critical hit
+    pub(crate) total: i32,
+    pub(crate) nonlethal: bool, // Knocks the target out at 0 HP instead of leaving it dying
+}
+
+impl DamageBreakdown {
+    // Doubles the total, as a critical hit does
+    pub(crate) fn doubled(mut self) -> Self {
+        self.multiplier *= 2;
+        self.total *= 2;
+        self
+    }
+
+    pub(crate) fn nonlethal(mut self) -> Self {
+        self.nonlethal = true;
+        self
+    }
+}
+
+// Rolls `count` dice of `die` sides through `dice`, remembering every face
+pub(crate) fn roll_damage(
+    dice: &mut dyn Dice,
+    count: u8,
+    die: u8,
+    modifier: i32,
+    kind: Option<DamageType>,
+    category: DamageCategory,
+) -> DamageBreakdown {
+    let rolls: Vec<u8> = (0..count).map(|_| dice.roll(die)).collect();
+    let total = rolls.iter().map(|&r| r as i32).sum::<i32>() + modifier;
+    DamageBreakdown {
+        category,
+        kind,
+        die,
+        rolls,
+        modifier,
+        multiplier: 1,
+        total,
+        nonlethal: false,
+    }
+}
+
+// The fraction of the damage taken on a basic save
+pub(crate) fn basic_save(degree: Degree) -> (i32, i32) {
+    match degree {
+        Degree::CriticalSuccess => (0, 1),
+        Degree::Success => (1, 2),
+        Degree::Failure => (1, 1),
+        Degree::CriticalFailure => (2, 1),
+    }
+}
+
+// Deals `amount` scaled by the fraction, rounding down as halving does
+pub(crate) fn scale_damage(
+    target: CreatureId,
+    amount: i32,
+    numerator: i32,
+    denominator: i32,
+    kind: Option<DamageType>,
+) -> Filter<World> {
+    let amount = amount * numerator / denominator;
+    match kind {
+        Some(kind) => typed_damage(target, amount, kind),
+        None => damage(target, amount),
+    }
+}
+
+impl Action<World> {
+    /**
+     * The target rolls `defense` against the DC and takes `base_damage` scaled by
+     * the degree: none, half, all or double. The update carries the degree.
+     */
+    pub(crate) fn basic_save(
+        target: CreatureId,
+        defense: Save,
+        dc: i32,
+        base_damage: i32,
+        kind: DamageType,
+    ) -> Self {
+        Action::new(Arc::new(move |state, context| {
+            let Some(creature) = state.base.creature(target) else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            let stat = StatKind::Check(Statistic::Save(defense));
+            let custom = &state.base.custom_conditions;
+            let degree = context.with_conditions(creature, custom, stat, |context| {
+                context.roll_check(creature.save_modifier(defense), dc)
+            });
+            let (numerator, denominator) = basic_save(degree);
+            let update = UpdateBuilder::new()
+                .filter(scale_damage(target, base_damage, numerator, denominator, Some(kind)))
+                .target(target)
+                .tag(Tag::Degree(degree))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+    }
+}
+
+impl fmt::Display for DamageBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifier = match self.modifier {
+            0 => String::new(),
+            m => format!("{m:+}"),
+        };
+        write!(f, "{}d{}{modifier}", self.rolls.len(), self.die)?;
+        if let Some(kind) = self.kind {
+            write!(f, " {}", format!("{kind:?}").to_lowercase())?;
+        }
+        if self.category != DamageCategory::Direct {
+            write!(f, " {}", format!("{:?}", self.category).to_lowercase())?;
+        }
+        let faces: Vec<String> = self.rolls.iter().map(|r| r.to_string()).collect();
+        let rolled = format!("[{}]{modifier}", faces.join(","));
+        match self.multiplier {
+            1 => write!(f, " = {rolled} = {}", self.total),
+            m => write!(f, " = ({rolled})x{m} = {}", self.total),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::FixedDice;
+    use crate::engine::{Engine, Meta, State};
+    use crate::world::{Creature, WorldBuilder};
+
+    #[test]
+    fn breakdown_keeps_each_face_and_the_total() {
+        let mut dice = FixedDice::new(vec![3, 5]);
+        let fire = roll_damage(&mut dice, 2, 6, 4, Some(DamageType::Fire), DamageCategory::Direct);
+        assert_eq!(fire.rolls, vec![3, 5]);
+        assert_eq!(fire.total, 12);
+        assert_eq!(fire.to_string(), "2d6+4 fire = [3,5]+4 = 12");
+
+        let acid = Some(DamageType::Acid);
+        let splash = roll_damage(&mut dice, 1, 4, 0, acid, DamageCategory::Splash);
+        assert_eq!(splash.to_string(), "1d4 acid splash = [3] = 3");
+        assert_eq!(splash.doubled().to_string(), "1d4 acid splash = ([3])x2 = 6");
+    }
+
+    #[test]
+    fn basic_saves_scale_the_damage() {
+        let world = WorldBuilder::new().add_creature(0, Creature::new("Rogue", 30, 18)).build();
+        let state = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+        let taken = |degree| {
+            let (numerator, denominator) = basic_save(degree);
+            let after = scale_damage(0, 7, numerator, denominator, None)(state.clone());
+            30 - after.base.creature(0).unwrap().hp
+        };
+        assert_eq!(taken(Degree::Success), 3);
+        assert_eq!(taken(Degree::CriticalFailure), 14);
+        assert_eq!(taken(Degree::CriticalSuccess), 0);
+        assert_eq!(taken(Degree::Failure), 7);
+    }
+
+    #[test]
+    fn basic_save_actions_roll_for_the_degree() {
+        // A +0 Reflex against DC 15: 5 crit fails, 10 fails, 15 succeeds, 20 crit succeeds
+        let taken = |natural| {
+            let world = WorldBuilder::new()
+                .add_creature(0, Creature::new("Goblin", 30, 16))
+                .build();
+            let mut engine = Engine::for_base(world);
+            engine.dice = Box::new(FixedDice::new(vec![natural]));
+            engine.act(Action::basic_save(0, Save::Reflex, 15, 9, DamageType::Fire));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            30 - engine.state.base.creature(0).unwrap().hp
+        };
+        assert_eq!(taken(20), 0);
+        assert_eq!(taken(15), 4);
+        assert_eq!(taken(10), 9);
+        assert_eq!(taken(5), 18);
+    }
+}
@@ -0,0 +1,224 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::field::{Ability, Field, UpdateId};
+use crate::stats::{Proficiency, Save, Statistic};
+use crate::world::Creature;
+
+// What a creature defends with: its armor class or one of its saves
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Defense {
+    ArmorClass,
+    Save(Save),
+}
+
+impl Defense {
+    pub(crate) const ALL: [Defense; 4] = [
+        Defense::ArmorClass,
+        Defense::Save(Save::Fortitude),
+        Defense::Save(Save::Reflex),
+        Defense::Save(Save::Will),
+    ];
+
+    pub(crate) fn ability(&self) -> Ability {
+        match self {
+            Defense::ArmorClass => Ability::Dexterity,
+            Defense::Save(save) => save.ability(),
+        }
+    }
+
+    pub(crate) fn statistic(&self) -> Statistic {
+        match self {
+            Defense::ArmorClass => Statistic::Armor,
+            Defense::Save(save) => Statistic::Save(*save),
+        }
+    }
+
+    fn index(&self) -> usize {
+        Defense::ALL.iter().position(|defense| defense == self).unwrap_or(0)
+    }
+}
+
+// What a defense is worked out from; a cached value holds for as long as these do
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Inputs {
+    score: u8,
+    proficiency: Option<Proficiency>,
+    level: i32,
+    item: i32,
+    listed: i32, // The stat block's AC, for AC alone
+}
+
+fn inputs(creature: &Creature, defense: Defense) -> Inputs {
+    Inputs {
+        score: creature.abilities[defense.ability() as usize],
+        proficiency: creature.proficiencies.get(&defense.statistic()).copied(),
+        level: creature.level,
+        item: creature.item_bonus(defense),
+        listed: match defense {
+            Defense::ArmorClass => creature.ac,
+            Defense::Save(_) => 0,
+        },
+    }
+}
+
+impl Creature {
+    // Item bonuses don't stack, so only the best item counts
+    pub(crate) fn item_bonus(&self, defense: Defense) -> i32 {
+        self.items
+            .iter()
+            .flat_map(|item| item.bonuses.iter())
+            .filter(|(to, _)| *to == defense)
+            .map(|(_, bonus)| *bonus)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/**
+ * Ability modifier + proficiency bonus (which includes level) + item bonus, and 10 more for AC.
+ * A creature with no armor proficiency, as from a stat block, keeps its listed AC
+ * and only adds the item bonus to it.
+ */
+pub(crate) fn defense_field(defense: Defense) -> Field<Creature, i32> {
+    Field::new_dyn(Arc::new(move |creature: &Creature, _| {
+        let item = creature.item_bonus(defense);
+        let worked_out = creature.modifier(defense.ability())
+            + creature.proficiency_bonus(defense.statistic())
+            + item;
+        match defense {
+            Defense::Save(_) => worked_out,
+            Defense::ArmorClass if creature.proficiencies.contains_key(&Statistic::Armor) => {
+                10 + worked_out
+            }
+            Defense::ArmorClass => creature.ac + item,
+        }
+    }))
+}
+
+/** Defenses
+ *  A creature's AC and saves as derived fields, each cached with the inputs it
+ *  was worked out from. Reading one only recomputes it once its own ability score,
+ *  proficiency, level or item bonus has changed, so raising DEX leaves Fortitude be.
+ *  It only ever says what the rest of the creature does, so comparing, hashing
+ *  and saving a creature all leave it out.
+ */
+pub(crate) struct Defenses {
+    fields: [Field<Creature, i32>; 4], // Indexed like `Defense::ALL`
+    cache: Mutex<[Option<(Inputs, i32)>; 4]>, // Locked so a defense can be read through `&self`
+}
+
+impl Defenses {
+    pub(crate) fn new() -> Self {
+        Defenses {
+            fields: Defense::ALL.map(defense_field),
+            cache: Mutex::new([None; 4]),
+        }
+    }
+
+    pub(crate) fn get(&self, defense: Defense, creature: &Creature) -> i32 {
+        let at = defense.index();
+        let now = inputs(creature, defense);
+        let cached = self.cache()[at];
+        match cached {
+            Some((from, value)) if from == now => value,
+            _ => {
+                let value = self.fields[at].at(UpdateId::MAX, creature);
+                self.cache()[at] = Some((now, value));
+                value
+            }
+        }
+    }
+
+    // Whether the next `get` would reuse the cached value
+    pub(crate) fn is_cached(&self, defense: Defense, creature: &Creature) -> bool {
+        self.cache()[defense.index()].is_some_and(|(from, _)| from == inputs(creature, defense))
+    }
+
+    fn cache(&self) -> MutexGuard<'_, [Option<(Inputs, i32)>; 4]> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Clone for Defenses {
+    fn clone(&self) -> Self {
+        Defenses {
+            fields: self.fields.clone(),
+            cache: Mutex::new(*self.cache()),
+        }
+    }
+}
+
+impl Default for Defenses {
+    fn default() -> Self {
+        Defenses::new()
+    }
+}
+
+impl PartialEq for Defenses {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Defenses {}
+
+impl Hash for Defenses {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl fmt::Debug for Defenses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Defenses").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::Item;
+
+    #[test]
+    fn only_the_defenses_a_change_touches_are_recomputed() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.abilities[Ability::Dexterity as usize] = 14;
+        fighter.abilities[Ability::Constitution as usize] = 16;
+        fighter.proficiencies.insert(Statistic::Armor, Proficiency::Trained);
+        fighter.proficiencies.insert(Statistic::Save(Save::Fortitude), Proficiency::Expert);
+        fighter.items.push(Item::new("Breastplate", 20).with_bonus(Defense::ArmorClass, 4));
+        let fortitude = Defense::Save(Save::Fortitude);
+        let defenses = Defenses::new();
+        assert!(!defenses.is_cached(Defense::ArmorClass, &fighter));
+        assert_eq!(defenses.get(Defense::ArmorClass, &fighter), 10 + 2 + 3 + 4);
+        assert_eq!(defenses.get(fortitude, &fighter), 3 + 5);
+
+        fighter.abilities[Ability::Dexterity as usize] = 16;
+        assert!(!defenses.is_cached(Defense::ArmorClass, &fighter));
+        assert!(defenses.is_cached(fortitude, &fighter));
+        assert_eq!(defenses.get(Defense::ArmorClass, &fighter), 10 + 3 + 3 + 4);
+        assert!(defenses.is_cached(Defense::ArmorClass, &fighter));
+
+        // Levelling up reaches every trained defense through its proficiency
+        fighter.level = 2;
+        assert!(!defenses.is_cached(fortitude, &fighter));
+        assert_eq!(defenses.get(fortitude, &fighter), 3 + 6);
+    }
+
+    #[test]
+    fn item_bonuses_reach_saves_and_attacks() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.proficiencies.insert(Statistic::Save(Save::Will), Proficiency::Trained);
+        fighter.items.push(Item::new("Resilient Rune", 0).with_bonus(Defense::Save(Save::Will), 1));
+        assert_eq!(fighter.save_modifier(Save::Will), 3 + 1);
+        assert_eq!(fighter.save_dc(Save::Will), 10 + 3 + 1);
+
+        // Without an armor proficiency the listed AC stands, with items on top
+        assert_eq!(fighter.ac_against(1), 18);
+        fighter.items.push(Item::new("Ring of Protection", 0).with_bonus(Defense::ArmorClass, 1));
+        assert_eq!(fighter.ac_against(1), 19);
+        fighter.proficiencies.insert(Statistic::Armor, Proficiency::Trained);
+        assert_eq!(fighter.ac_against(1), 10 + 3 + 1);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::check::Degree;
+use crate::engine::Context;
+
+/** DegreeAdjustment
+ *  An effect that moves a check's result a step after the degree is worked out,
+ *  like a feat turning a failed save into a success. Steps stop at the ends;
+ *  a critical success can't improve further.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum DegreeAdjustment {
+    Up(Option<Degree>),   // A step better; only from that degree, if one is given
+    Down(Option<Degree>), // A step worse; likewise
+    Incapacitation(i32),  // A step better for any creature above this level
+}
+
+impl DegreeAdjustment {
+    // The roller's level is unknown outside `Context::with_conditions`, so incapacitation waits
+    pub(crate) fn adjust(self, level: Option<i32>, degree: Degree) -> Degree {
+        let from = |only: Option<Degree>| only.is_none_or(|only| only == degree);
+        match self {
+            DegreeAdjustment::Up(only) if from(only) => degree.up(),
+            DegreeAdjustment::Down(only) if from(only) => degree.down(),
+            DegreeAdjustment::Incapacitation(effect) if level.is_some_and(|l| l > effect) => {
+                degree.up()
+            }
+            _ => degree,
+        }
+    }
+}
+
+impl Context<'_> {
+    /**
+     * The degree after every adjustment the action carries, in the order they were added,
+     * except incapacitation, which comes last so nothing can take its step back.
+     */
+    pub(crate) fn adjusted(&self, level: Option<i32>, degree: Degree) -> Degree {
+        let adjustments = self.adjustments.iter();
+        let (last, first): (Vec<&DegreeAdjustment>, _) =
+            adjustments.partition(|a| matches!(a, DegreeAdjustment::Incapacitation(_)));
+        first.into_iter().chain(last).fold(degree, |degree, a| a.adjust(level, degree))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::check::SaveMode;
+    use crate::dice::FixedDice;
+    use crate::engine::{Action, Engine, Resolved, State, Tag, UpdateBuilder};
+    use crate::stats::Save;
+    use crate::world::{Creature, World, WorldBuilder};
+
+    #[test]
+    fn incapacitation_helps_a_higher_level_target_save() {
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Wizard", 16, 14))
+            .build();
+        let mut ogre = Creature::new("Ogre", 50, 17);
+        ogre.level = 5;
+        world.creatures.insert(1, ogre.clone());
+        ogre.level = 1;
+        world.creatures.insert(2, ogre);
+        let mut engine = Engine::for_base(world);
+        engine.dice = Box::new(FixedDice::exact(vec![2, 2]));
+        // Paralyze as a rank 2 spell, so a 4th level effect
+        let paralyze = Action::area(0, vec![1, 2], Save::Will, SaveMode::Individual, |t, degree| {
+            vec![UpdateBuilder::new()
+                .filter(Arc::new(|state: State<World>| state))
+                .target(t)
+                .tag(Tag::Degree(degree))
+                .resolved(Resolved::Resolved)
+                .build(t)]
+        })
+        .with_degree_adjustment(DegreeAdjustment::Incapacitation(4));
+        engine.act(paralyze);
+        engine.step();
+        let degree = |at: usize| engine.updates[at].tags.iter().find_map(|tag| match tag {
+            Tag::Degree(degree) => Some(*degree),
+            _ => None,
+        });
+        // Both roll a natural 2 and fail, but the level 5 ogre is above the effect and succeeds
+        assert_eq!(degree(0), Some(Degree::Success));
+        assert_eq!(degree(1), Some(Degree::Failure));
+    }
+
+    #[test]
+    fn steps_can_wait_for_a_degree_and_stop_at_the_ends() {
+        let rescue = DegreeAdjustment::Up(Some(Degree::Failure));
+        assert_eq!(rescue.adjust(None, Degree::Failure), Degree::Success);
+        assert_eq!(rescue.adjust(None, Degree::CriticalFailure), Degree::CriticalFailure);
+        let (up, down) = (DegreeAdjustment::Up(None), DegreeAdjustment::Down(None));
+        assert_eq!(up.adjust(None, Degree::CriticalSuccess), Degree::CriticalSuccess);
+        assert_eq!(down.adjust(None, Degree::Success), Degree::Failure);
+        assert_eq!(down.adjust(None, Degree::CriticalFailure), Degree::CriticalFailure);
+    }
+}
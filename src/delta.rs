@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use crate::engine::Engine;
+use crate::initiative::Initiative;
+use crate::world::{Creature, CreatureId, World};
+
+/** WorldDelta
+ *  What changed in the world between two versions, to send instead of all of it:
+ *  each creature that changed in full, the ids of those that left, and the
+ *  initiative if it moved on. The rest of the world is assumed to stay put.
+ */
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct WorldDelta {
+    pub(crate) from: u64, // The version the receiver must be at
+    pub(crate) to: u64,
+    pub(crate) creatures: BTreeMap<CreatureId, Creature>,
+    pub(crate) removed: Vec<CreatureId>,
+    pub(crate) initiative: Option<Initiative>,
+}
+
+impl World {
+    // Brings a copy of the world at the delta's `from` version up to its `to`
+    pub(crate) fn apply_delta(&mut self, delta: &WorldDelta) {
+        for (id, creature) in delta.creatures.iter() {
+            self.creatures.insert(*id, creature.clone());
+        }
+        for id in delta.removed.iter() {
+            self.creatures.remove(id);
+        }
+        if let Some(initiative) = &delta.initiative {
+            self.initiative = initiative.clone();
+        }
+    }
+}
+
+impl Engine<World> {
+    /**
+     * Everything that changed since the world was at `version`, each commit being one.
+     * None if the history no longer has that version, as once it has been undone
+     * past, and so the receiver needs the whole world again.
+     */
+    pub(crate) fn delta_since(&self, version: u64) -> Option<WorldDelta> {
+        let now = &self.state;
+        let then = match version == now.meta.version {
+            true => now,
+            false => &self.history.iter().rev().find(|(state, _)| state.meta.version == version)?.0,
+        };
+        let (old, new) = (&then.base.creatures, &now.base.creatures);
+        let creatures = new
+            .iter()
+            .filter(|(id, creature)| old.get(id) != Some(creature))
+            .map(|(id, creature)| (*id, creature.clone()))
+            .collect();
+        let removed = old.keys().filter(|id| !new.contains_key(id)).copied().collect();
+        let moved = then.base.initiative != now.base.initiative;
+        Some(WorldDelta {
+            from: version,
+            to: now.meta.version,
+            creatures,
+            removed,
+            initiative: moved.then(|| now.base.initiative.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{Action, ActionResponse, Resolved, UpdateBuilder};
+    use crate::world::{damage, WorldBuilder};
+
+    #[test]
+    fn a_delta_carries_only_the_creatures_that_changed() {
+        let mut world = World::default();
+        for id in 0..10 {
+            world.creatures.insert(id, Creature::new(&format!("Goblin {id}"), 8, 16));
+        }
+        let mut receiver = world.clone();
+        let mut engine = Engine::for_base(world);
+        let version = engine.state.meta.version;
+        engine.act(Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(damage(3, 5))
+                .target(3)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        })));
+        while !engine.is_idle() {
+            engine.step();
+        }
+
+        let delta = engine.delta_since(version).unwrap();
+        assert_eq!((delta.from, delta.to), (0, 1));
+        assert_eq!(delta.creatures.keys().copied().collect::<Vec<_>>(), vec![3]);
+        assert!(delta.removed.is_empty() && delta.initiative.is_none());
+        #[cfg(feature = "serde")]
+        let delta: WorldDelta = {
+            let json = serde_json::to_string(&delta).unwrap();
+            assert!(json.contains("Goblin 3") && !json.contains("Goblin 4"));
+            serde_json::from_str(&json).unwrap()
+        };
+        receiver.apply_delta(&delta);
+        assert_eq!(receiver, engine.state.base);
+        assert!(engine.delta_since(7).is_none());
+    }
+
+    #[test]
+    fn an_undone_version_is_not_reused() {
+        let world = WorldBuilder::new().add_creature(0, Creature::new("Goblin", 8, 16)).build();
+        let mut engine = Engine::for_base(world);
+        engine.apply_filter(damage(0, 3));
+        let undone = engine.state.meta.version;
+        assert!(engine.undo());
+        engine.apply_filter(damage(0, 5));
+        assert_ne!(engine.state.meta.version, undone);
+        // A receiver still at the undone version has to start over
+        assert!(engine.delta_since(undone).is_none());
+        let delta = engine.delta_since(0).unwrap();
+        assert_eq!((delta.from, delta.to), (0, 2));
+        assert_eq!(delta.creatures[&0].hp, 3);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::engine::{Base, Engine, Tag};
+use crate::tags::TagSet;
+
+impl<T: Base> Engine<T> {
+    /**
+     * Makes `dependent` follow `trigger` on every update: attaching a tag of the
+     * trigger's kind attaches the dependent too, and detaching it takes the dependent away.
+     * For interactions like "while frightened, you are also flat-footed".
+     */
+    pub(crate) fn derive_tag(&mut self, trigger: Tag, dependent: Tag) {
+        self.derived.push((trigger, dependent));
+    }
+}
+
+// Brings along or takes away the dependents of a tag being attached or detached
+pub(crate) fn follow(derived: &[(Tag, Tag)], changed: &Tag, attached: bool, tags: &mut TagSet) {
+    for (_, dependent) in derived.iter().filter(|(trigger, _)| trigger.same_kind(changed)) {
+        if attached {
+            tags.insert(dependent.clone());
+        } else {
+            tags.remove(dependent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{Resolved, RuleResponse, TestBase, Update};
+    use crate::metadata::Metadata;
+
+    #[test]
+    fn dependent_tags_track_their_trigger() {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.derive_tag(Tag::Frightened(0), Tag::Flatfooted(None));
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Frightened(1))));
+        engine.rules.push(Arc::new(|_, update| {
+            assert!(update.tags.contains(&Tag::Flatfooted(None)));
+            RuleResponse::Detach(Tag::Frightened(0))
+        }));
+        engine.rules.push(Arc::new(|_, update| {
+            assert!(update.tags.is_empty());
+            RuleResponse::Skip
+        }));
+        engine.updates.push(Update {
+            filter: Arc::new(|state| state),
+            id: 1,
+            target: 0,
+            tags: TagSet::new(),
+            applies: TagSet::new(),
+            resolved: Resolved::Unresolved,
+            meta: Metadata::new(),
+            outcome: Default::default(),
+        });
+        engine.step();
+        assert_eq!(engine.update, 1);
+    }
+}
@@ -0,0 +1,334 @@
+/** Dice
+ *  Every random number the engine uses comes through here,
+ *  so swapping the implementation makes a whole encounter reproducible.
+ */
+pub(crate) trait Dice: Send + Sync {
+    // A result from 1 to `sides`
+    fn roll(&mut self, sides: u8) -> u8;
+
+    fn d20(&mut self) -> u8 {
+        self.roll(20)
+    }
+
+    /**
+     * What the next `d20` will be, without rolling it, so a host can start animating
+     * the roll before the result lands. Dice that can't know ahead say None.
+     */
+    fn peek(&mut self) -> Option<u8> {
+        None
+    }
+
+    /**
+     * The total of each (count, sides) batch, rolled in the same order as
+     * individual `roll` calls would be, so transcripts and seeds replay the same.
+     */
+    fn roll_many(&mut self, specs: &[(u8, u8)]) -> Vec<u32> {
+        specs
+            .iter()
+            .map(|&(count, sides)| (0..count).map(|_| self.roll(sides) as u32).sum())
+            .collect()
+    }
+
+    /**
+     * An index picked with chance in proportion to its weight, for random tables.
+     * It only uses `roll`, so seeds and transcripts replay the same: one roll for
+     * totals up to 255, else one per base-255 digit (with a slight bias).
+     * Panics if every weight is 0.
+     */
+    fn weighted_index(&mut self, weights: &[u32]) -> usize {
+        let total: u64 = weights.iter().map(|&weight| weight as u64).sum();
+        assert!(total > 0, "there is nothing to choose from");
+        let mut value = 0;
+        if total <= 255 {
+            value = self.roll(total as u8).saturating_sub(1) as u64;
+        } else {
+            let mut span = 1;
+            while span < total {
+                value = value * 255 + self.roll(255).saturating_sub(1) as u64;
+                span *= 255;
+            }
+        }
+        let mut value = value % total;
+        for (index, &weight) in weights.iter().enumerate() {
+            match value.checked_sub(weight as u64) {
+                Some(rest) => value = rest,
+                None => return index,
+            }
+        }
+        unreachable!("the value is below the total weight")
+    }
+
+    // An independent stream for a forked engine; the same branch always gets the same one
+    fn fork(&self, branch: u64) -> Box<dyn Dice>;
+
+    // Panics if dice that expect a set number of rolls didn't get them all
+    fn assert_consumed(&self) {}
+
+    // How many rolls have been made, and a way back to any such point for replaying
+    fn position(&self) -> u64;
+    fn seek(&mut self, position: u64);
+}
+
+impl dyn Dice + '_ {
+    // One of the items, weighted as in `weighted_index`
+    pub(crate) fn choose_weighted<'a, T>(&mut self, items: &'a [(T, u32)]) -> &'a T {
+        let weights: Vec<u32> = items.iter().map(|(_, weight)| *weight).collect();
+        &items[self.weighted_index(&weights)].0
+    }
+}
+
+/** SeededDice
+ *  A splitmix64 stream; each roll hashes the seed with its position.
+ */
+pub(crate) struct SeededDice {
+    seed: u64,
+    position: u64,
+}
+
+impl SeededDice {
+    pub(crate) fn new(seed: u64) -> Self {
+        SeededDice { seed, position: 0 }
+    }
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl Dice for SeededDice {
+    fn roll(&mut self, sides: u8) -> u8 {
+        let value = splitmix64(self.seed ^ splitmix64(self.position));
+        self.position += 1;
+        (value % sides.max(1) as u64) as u8 + 1
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        Some((splitmix64(self.seed ^ splitmix64(self.position)) % 20) as u8 + 1)
+    }
+
+    // One tight loop over the positions, with no call per die
+    fn roll_many(&mut self, specs: &[(u8, u8)]) -> Vec<u32> {
+        let mut totals = Vec::with_capacity(specs.len());
+        for &(count, sides) in specs {
+            let sides = sides.max(1) as u64;
+            let mut total = 0;
+            for position in self.position..self.position + count as u64 {
+                total += (splitmix64(self.seed ^ splitmix64(position)) % sides) as u32 + 1;
+            }
+            self.position += count as u64;
+            totals.push(total);
+        }
+        totals
+    }
+
+    fn fork(&self, branch: u64) -> Box<dyn Dice> {
+        let seed = splitmix64(self.seed ^ splitmix64(self.position) ^ splitmix64(!branch));
+        Box::new(SeededDice::new(seed))
+    }
+
+    fn position(&self) -> u64 {
+        self.position
+    }
+
+    fn seek(&mut self, position: u64) {
+        self.position = position;
+    }
+}
+
+/** FixedDice
+ *  Replays a transcript of results in order, whatever dice are asked for.
+ *  It starts over once the transcript runs out, unless it is exact,
+ *  in which case every roll must be used and asking for more panics.
+ */
+pub(crate) struct FixedDice {
+    rolls: Vec<u8>,
+    next: usize,
+    exact: bool,
+}
+
+impl FixedDice {
+    pub(crate) fn new(rolls: Vec<u8>) -> Self {
+        FixedDice {
+            rolls,
+            next: 0,
+            exact: false,
+        }
+    }
+
+    // For golden tests: the scenario must consume exactly this transcript
+    pub(crate) fn exact(rolls: Vec<u8>) -> Self {
+        FixedDice {
+            exact: true,
+            ..FixedDice::new(rolls)
+        }
+    }
+}
+
+impl Dice for FixedDice {
+    fn roll(&mut self, _sides: u8) -> u8 {
+        if self.exact && self.next >= self.rolls.len() {
+            let (asked, available) = (self.next + 1, self.rolls.len());
+            panic!("roll {asked} was asked for, but the transcript only has {available}");
+        }
+        let value = self.rolls[self.next % self.rolls.len()];
+        self.next += 1;
+        value
+    }
+
+    // Whatever the transcript has next, as it would be for any die; None once an exact one is spent
+    fn peek(&mut self) -> Option<u8> {
+        if self.exact && self.next >= self.rolls.len() {
+            return None;
+        }
+        self.rolls.get(self.next % self.rolls.len().max(1)).copied()
+    }
+
+    // A transcript is already scripted, so every branch carries on from the same point
+    fn fork(&self, _branch: u64) -> Box<dyn Dice> {
+        Box::new(FixedDice {
+            rolls: self.rolls.clone(),
+            next: self.next,
+            exact: self.exact,
+        })
+    }
+
+    fn position(&self) -> u64 {
+        self.next as u64
+    }
+
+    fn seek(&mut self, position: u64) {
+        self.next = position as usize;
+    }
+
+    fn assert_consumed(&self) {
+        if self.exact && self.next < self.rolls.len() {
+            panic!("only {} of the transcript's {} rolls were used", self.next, self.rolls.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::WorldBuilder;
+
+    #[test]
+    fn seeded_dice_are_reproducible_and_in_range() {
+        let mut a = SeededDice::new(42);
+        let mut b = SeededDice::new(42);
+        for _ in 0..1000 {
+            let roll = a.d20();
+            assert_eq!(roll, b.d20());
+            assert!((1..=20).contains(&roll));
+        }
+    }
+
+    #[test]
+    fn forked_streams_differ_by_branch() {
+        let dice = SeededDice::new(42);
+        let rolls = |branch| {
+            let mut fork = dice.fork(branch);
+            (0..20).map(|_| fork.d20()).collect::<Vec<_>>()
+        };
+        assert_eq!(rolls(1), rolls(1));
+        assert_ne!(rolls(1), rolls(2));
+    }
+
+    #[test]
+    fn seeking_replays_from_that_roll() {
+        let mut dice = SeededDice::new(7);
+        let rolls: Vec<u8> = (0..5).map(|_| dice.d20()).collect();
+        assert_eq!(dice.position(), 5);
+        dice.seek(2);
+        assert_eq!((0..3).map(|_| dice.d20()).collect::<Vec<_>>(), rolls[2..]);
+    }
+
+    #[test]
+    fn batches_roll_what_single_dice_would() {
+        let specs = [(1, 20), (3, 6), (0, 8), (2, 10)];
+        let mut batched = SeededDice::new(9);
+        let mut single = SeededDice::new(9);
+        let totals: Vec<u32> = specs
+            .iter()
+            .map(|&(count, sides)| (0..count).map(|_| single.roll(sides) as u32).sum())
+            .collect();
+        assert_eq!(batched.roll_many(&specs), totals);
+        assert_eq!(batched.position(), single.position());
+        assert_eq!(batched.d20(), single.d20());
+
+        let mut fixed = FixedDice::new(vec![3, 17, 5]);
+        assert_eq!(fixed.roll_many(&[(1, 20), (2, 6)]), vec![3, 22]);
+    }
+
+    #[test]
+    fn weighted_choices_follow_their_weights() {
+        let table = [("copper", 1), ("silver", 3), ("gold", 0)];
+        let draw = |dice: &mut dyn Dice| -> Vec<&str> {
+            (0..4000).map(|_| *dice.choose_weighted(&table)).collect()
+        };
+        let draws = draw(&mut SeededDice::new(5));
+        assert_eq!(draws, draw(&mut SeededDice::new(5)));
+        let silver = draws.iter().filter(|item| **item == "silver").count();
+        assert!((2800..3200).contains(&silver), "{silver} silver in 4000 draws");
+        assert!(!draws.contains(&"gold"));
+
+        // A transcript picks by where the roll lands in the running total
+        let mut fixed = FixedDice::new(vec![1, 2, 4]);
+        assert_eq!(draw(&mut fixed)[..3], ["copper", "silver", "silver"]);
+    }
+
+    #[test]
+    fn peeking_shows_the_next_roll_without_taking_it() {
+        let mut fixed = FixedDice::exact(vec![14, 3]);
+        assert_eq!(fixed.peek(), Some(14));
+        assert_eq!(fixed.peek(), Some(14));
+        assert_eq!(fixed.d20(), 14);
+        assert_eq!(fixed.peek(), Some(3));
+        fixed.d20();
+        assert_eq!(fixed.peek(), None);
+
+        let mut seeded = SeededDice::new(11);
+        for _ in 0..20 {
+            let peeked = seeded.peek();
+            assert_eq!(peeked, Some(seeded.d20()));
+        }
+    }
+
+    #[test]
+    fn fixed_dice_replay_the_transcript() {
+        let mut dice = FixedDice::new(vec![3, 17]);
+        assert_eq!([dice.d20(), dice.roll(6), dice.d20()], [3, 17, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "only 2 of the transcript's 3 rolls were used")]
+    fn exact_transcripts_must_be_used_up() {
+        use crate::combat::{Weapon, WeaponGroup};
+        use crate::engine::{Action, Engine};
+        use crate::world::Creature;
+
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 30, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        // The strike hits with its first roll and its damage takes the second
+        engine.dice = Box::new(FixedDice::exact(vec![18, 4, 6]));
+        engine.act(Action::strike(0, 1, Weapon::new("Club", WeaponGroup::Club, 1, 6)));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        engine.dice.assert_consumed();
+    }
+
+    #[test]
+    #[should_panic(expected = "roll 2 was asked for")]
+    fn exact_transcripts_cannot_be_overrun() {
+        let mut dice = FixedDice::exact(vec![3]);
+        dice.d20();
+        dice.d20();
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+
+use crate::engine::{Base, Engine, State, Tag};
+use crate::world::{condition_name, CreatureId, World};
+
+// One thing a commit changed
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Change {
+    Hp { creature: CreatureId, from: i32, to: i32 },
+    ConditionAdded { creature: CreatureId, condition: Tag },
+    ConditionRemoved { creature: CreatureId, condition: Tag }, // Including a value it raised from
+    Appeared(CreatureId),
+    Vanished(CreatureId),
+}
+
+/** StateDiff
+ *  What a commit changed, for clients that would rather not compare
+ *  whole states themselves. Bases that don't describe their changes give an empty one.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct StateDiff {
+    pub(crate) changes: Vec<Change>,
+}
+
+pub(crate) type DiffSubscriber = Box<dyn Fn(&StateDiff) + Send + Sync>;
+
+impl StateDiff {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+// Like "creature 1 hp 30 -> 22" or "creature 1 gained frightened 1"
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Hp { creature, from, to } => write!(f, "creature {creature} hp {from} -> {to}"),
+            Change::ConditionAdded {
+                creature,
+                condition,
+            } => write!(f, "creature {creature} gained {}", condition_name(condition)),
+            Change::ConditionRemoved {
+                creature,
+                condition,
+            } => write!(f, "creature {creature} lost {}", condition_name(condition)),
+            Change::Appeared(creature) => write!(f, "creature {creature} appeared"),
+            Change::Vanished(creature) => write!(f, "creature {creature} vanished"),
+        }
+    }
+}
+
+/**
+ * Fails with what changed from `expected` to `actual`, change by change,
+ * rather than both states in full. States that differ only in what the
+ * base's diff doesn't describe are told apart by their fingerprints.
+ */
+#[cfg(test)]
+pub(crate) fn assert_state_eq<T: Base>(expected: &State<T>, actual: &State<T>) {
+    let diff = T::diff(expected, actual);
+    if !diff.is_empty() {
+        let changes: Vec<String> = diff.changes.iter().map(|change| change.to_string()).collect();
+        panic!("states differ, expected -> actual:\n  {}", changes.join("\n  "));
+    }
+    let (expected, actual) = (expected.fingerprint(), actual.fingerprint());
+    assert_eq!(expected, actual, "states differ outside their diff, by fingerprint");
+}
+
+// Creature by creature, in id order
+pub(crate) fn diff_world(before: &State<World>, after: &State<World>) -> StateDiff {
+    let mut changes = vec![];
+    let (old, new) = (&before.base.creatures, &after.base.creatures);
+    for (&id, was) in old.iter() {
+        let Some(now) = new.get(&id) else {
+            changes.push(Change::Vanished(id));
+            continue;
+        };
+        if was.hp != now.hp {
+            changes.push(Change::Hp {
+                creature: id,
+                from: was.hp,
+                to: now.hp,
+            });
+        }
+        for condition in was.conditions.iter().filter(|c| !now.conditions.contains(c)) {
+            changes.push(Change::ConditionRemoved {
+                creature: id,
+                condition: condition.clone(),
+            });
+        }
+        for condition in now.conditions.iter().filter(|c| !was.conditions.contains(c)) {
+            changes.push(Change::ConditionAdded {
+                creature: id,
+                condition: condition.clone(),
+            });
+        }
+    }
+    for &id in new.keys().filter(|id| !old.contains_key(id)) {
+        changes.push(Change::Appeared(id));
+    }
+    StateDiff { changes }
+}
+
+impl<T: Base> Engine<T> {
+    // Told what each commit changed, and only when it changed something
+    pub(crate) fn subscribe_diff(&mut self, subscriber: DiffSubscriber) {
+        self.diff_subscribers.push(subscriber);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::engine::{Action, ActionResponse, Meta, Resolved, UpdateBuilder};
+    use crate::world::{damage, Creature, WorldBuilder};
+
+    #[test]
+    fn diffs_list_only_what_changed() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 30, 16))
+            .add_creature(2, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        let seen = Arc::new(Mutex::new(vec![]));
+        let into = seen.clone();
+        engine.subscribe_diff(Box::new(move |diff| into.lock().unwrap().push(diff.clone())));
+        engine.act(Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(damage(1, 8))
+                .target(1)
+                .applies(Tag::Frightened(1))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        })));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(
+            seen[0].changes,
+            vec![
+                Change::Hp {
+                    creature: 1,
+                    from: 30,
+                    to: 22,
+                },
+                Change::ConditionAdded {
+                    creature: 1,
+                    condition: Tag::Frightened(1),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected -> actual:\n  creature 1 hp 30 -> 22\n")]
+    fn state_mismatches_name_what_differs() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 30, 16))
+            .build();
+        let expected = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+        assert_state_eq(&expected, &expected.clone());
+        let mut actual = expected.clone();
+        let ogre = actual.base.creature_mut(1).unwrap();
+        ogre.lose_hp(8);
+        ogre.add_condition(Tag::Frightened(1));
+        assert_state_eq(&expected, &actual);
+    }
+}
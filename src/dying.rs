@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use crate::engine::{
+    Action, ActionResponse, Filter, Resolved, Rule, RuleResponse, State, Tag, Update,
+    UpdateBuilder,
+};
+use crate::world::{Creature, CreatureId, World};
+
+impl Creature {
+    pub(crate) fn dying(&self) -> u8 {
+        self.conditions
+            .iter()
+            .find_map(|c| match c {
+                Tag::Dying(value) => Some(*value),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn wounded(&self) -> u8 {
+        self.conditions
+            .iter()
+            .find_map(|c| match c {
+                Tag::Wounded(value) => Some(*value),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+}
+
+// Losing Dying leaves the creature wounded, or more wounded than it was
+fn recover(target: CreatureId) -> Filter<World> {
+    Arc::new(move |mut state: State<World>| {
+        if let Some(creature) = state.base.creature_mut(target) {
+            creature.clear_condition(&Tag::Dying(0));
+            creature.increase_condition(&Tag::Wounded(0), 1);
+        }
+        state
+    })
+}
+
+// Whether the update's damage is all nonlethal; an update with no damage tags is lethal
+fn nonlethal(update: &Update<World>) -> bool {
+    let mut damage = update.tags.iter().filter_map(|tag| match tag {
+        Tag::Damage(breakdown) => Some(breakdown.nonlethal),
+        _ => None,
+    });
+    damage.next().is_some_and(|first| first && damage.all(|nonlethal| nonlethal))
+}
+
+/**
+ * Falling to 0 HP starts Dying at 1 plus the creature's Wounded value,
+ * unless the damage was nonlethal, which only knocks the creature unconscious.
+ * Getting back above 0 HP ends Dying and adds 1 to Wounded.
+ */
+pub(crate) fn dying() -> Rule<World> {
+    Arc::new(|state, update| {
+        let Some(before) = state.base.creature(update.target) else {
+            return RuleResponse::Skip;
+        };
+        // As with encumbrance, the rule has to look at what the update would leave behind
+        let Some(after) = update.outcome(state).base.creature(update.target) else {
+            return RuleResponse::Skip;
+        };
+        let target = update.target;
+        if before.hp > 0 && after.hp == 0 && before.dying() == 0 && nonlethal(update) {
+            RuleResponse::Apply(Tag::Unconscious)
+        } else if before.hp > 0 && after.hp == 0 && before.dying() == 0 {
+            RuleResponse::Apply(Tag::Dying(0).with_value(before.wounded().saturating_add(1)))
+        } else if before.hp == 0 && after.hp > 0 && before.dying() > 0 {
+            RuleResponse::Inject(Action::new(Arc::new(move |_, context| {
+                let recovery = UpdateBuilder::new()
+                    .filter(recover(target))
+                    .target(target)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![recovery])
+            })))
+        } else {
+            RuleResponse::Skip
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::{roll_damage, DamageCategory};
+    use crate::dice::FixedDice;
+    use crate::engine::Engine;
+    use crate::world::{damage, heal, revive, WorldBuilder};
+
+    fn apply(engine: &mut Engine<World>, filter: Filter<World>) {
+        let update = UpdateBuilder::new()
+            .filter(filter)
+            .resolved(Resolved::Resolved)
+            .build(engine.next_id());
+        engine.updates.push(update);
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    #[test]
+    fn wounded_makes_the_next_fall_worse() {
+        let world = WorldBuilder::new().add_creature(0, Creature::new("Fighter", 20, 18)).build();
+        let mut engine = Engine::for_base(world);
+        engine.rules.push(dying());
+        let conditions =
+            |engine: &Engine<World>| engine.state.base.creature(0).unwrap().conditions.clone();
+
+        apply(&mut engine, damage(0, 25));
+        assert_eq!(conditions(&engine), vec![Tag::Dying(1)]);
+
+        apply(&mut engine, heal(0, 5));
+        assert_eq!(conditions(&engine), vec![Tag::Wounded(1)]);
+
+        apply(&mut engine, damage(0, 5));
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert_eq!((fighter.dying(), fighter.wounded()), (2, 1));
+
+        apply(&mut engine, heal(0, 5));
+        assert_eq!(conditions(&engine), vec![Tag::Wounded(2)]);
+    }
+
+    #[test]
+    fn nonlethal_damage_knocks_out_instead() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Bandit", 20, 15))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.rules.push(dying());
+        let mut dice = FixedDice::new(vec![6]);
+        let fist = roll_damage(&mut dice, 1, 6, 20, None, DamageCategory::Direct);
+        for (target, breakdown) in [(0, fist.clone().nonlethal()), (1, fist)] {
+            let update = UpdateBuilder::new()
+                .filter(damage(target, breakdown.total))
+                .target(target)
+                .tag(Tag::Damage(breakdown))
+                .resolved(Resolved::Resolved)
+                .build(engine.next_id());
+            engine.updates.push(update);
+            while !engine.is_idle() {
+                engine.step();
+            }
+        }
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert_eq!((fighter.hp, fighter.dying()), (0, 0));
+        assert_eq!(fighter.conditions, vec![Tag::Unconscious]);
+        let bandit = engine.state.base.creature(1).unwrap();
+        assert_eq!((bandit.hp, bandit.dying(), bandit.dead), (0, 1, false));
+    }
+
+    #[test]
+    fn the_dead_ignore_everything_but_revival() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.conditions = vec![Tag::Wounded(3)];
+        let world = WorldBuilder::new().add_creature(0, fighter).build();
+        let mut engine = Engine::for_base(world);
+        engine.rules.push(dying());
+
+        apply(&mut engine, damage(0, 25)); // Dying 4
+        assert!(engine.state.base.creature(0).unwrap().dead);
+        apply(&mut engine, heal(0, 10));
+        assert_eq!(engine.state.base.creature(0).unwrap().hp, 0);
+        let skipped = engine.log().last().unwrap().skipped.clone();
+        assert_eq!(skipped.as_deref(), Some("no effect (dead)"));
+
+        apply(&mut engine, revive(0, 1));
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert_eq!((fighter.dead, fighter.hp, fighter.dying()), (false, 1, 0));
+        assert_eq!(engine.log().last().unwrap().skipped, None);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::combat::WeaponGroup;
+use crate::condition::ConditionRegistry;
+use crate::engine::{Context, State, Tag};
+use crate::field::Ability;
+use crate::fortune::Luck;
+use crate::modifier::{counted, Modifier, ModifierKind};
+use crate::stats::Statistic;
+use crate::world::{condition_name, Creature, CreatureId, World};
+
+// A number shown on a character sheet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StatKind {
+    ArmorClass,
+    Check(Statistic), // The check modifier, which its DC follows; a weapon's is a melee attack
+    RangedAttack(WeaponGroup), // An attack with a ranged weapon, which Dexterity aims
+}
+
+impl StatKind {
+    // The ability the stat is based on; spell and class DCs depend on the creature
+    fn ability(&self) -> Option<Ability> {
+        match self {
+            StatKind::ArmorClass | StatKind::Check(Statistic::Armor) => Some(Ability::Dexterity),
+            StatKind::RangedAttack(_) => Some(Ability::Dexterity),
+            StatKind::Check(Statistic::Save(save)) => Some(save.ability()),
+            StatKind::Check(Statistic::Skill(skill)) => Some(skill.ability()),
+            StatKind::Check(Statistic::Perception) => Some(Ability::Wisdom),
+            StatKind::Check(Statistic::Weapon(_)) => Some(Ability::Strength),
+            StatKind::Check(Statistic::Spell | Statistic::Class) => None,
+        }
+    }
+
+    pub(crate) fn is_attack(&self) -> bool {
+        matches!(self, StatKind::Check(Statistic::Weapon(_)) | StatKind::RangedAttack(_))
+    }
+
+    pub(crate) fn based_on(&self, abilities: &[Ability]) -> bool {
+        self.ability().is_some_and(|ability| abilities.contains(&ability))
+    }
+}
+
+/**
+ * The ability each status-penalty condition saps, and so the stats it lowers.
+ * Stupefied also takes from spellcasting, whatever its ability.
+ */
+fn saps(condition: &Tag, stat: StatKind) -> bool {
+    match condition {
+        Tag::Clumsy(_) => stat.based_on(&[Ability::Dexterity]),
+        Tag::Enfeebled(_) => stat.based_on(&[Ability::Strength]),
+        Tag::Drained(_) => stat.based_on(&[Ability::Constitution]),
+        Tag::Stupefied(_) => {
+            let mental = [Ability::Intelligence, Ability::Wisdom, Ability::Charisma];
+            stat == StatKind::Check(Statistic::Spell) || stat.based_on(&mental)
+        }
+        _ => false,
+    }
+}
+
+// What a condition does to the stat, named as it would be listed
+fn condition_modifier(
+    condition: &Tag,
+    stat: StatKind,
+    registry: &ConditionRegistry,
+) -> Option<(String, Modifier)> {
+    match condition {
+        Tag::Custom { .. } => {
+            let modifier = registry.modifier(condition, stat)?;
+            Some((condition_name(condition), modifier))
+        }
+        // Flat-footed only to a particular attacker is left to `flatfooted_to`
+        Tag::Flatfooted(None) if stat == StatKind::ArmorClass => Some((
+            String::from("flat-footed"),
+            Modifier::new(ModifierKind::Circumstance, -2),
+        )),
+        // Prone's flat-footed only counts against melee attacks, so only its penalty is listed
+        Tag::Prone if stat.is_attack() => Some((
+            String::from("prone"),
+            Modifier::new(ModifierKind::Circumstance, -2),
+        )),
+        Tag::ShieldRaised if stat == StatKind::ArmorClass => Some((
+            String::from("shield raised"),
+            Modifier::new(ModifierKind::Circumstance, 2),
+        )),
+        Tag::Cover(level) if stat == StatKind::ArmorClass => Some((
+            level.name(),
+            Modifier::new(ModifierKind::Circumstance, level.bonus()),
+        )),
+        Tag::Frightened(value) => Some((
+            format!("frightened {value}"),
+            Modifier::new(ModifierKind::Status, -(*value as i32)),
+        )),
+        sapping if saps(sapping, stat) => Some((
+            condition_name(sapping),
+            Modifier::new(ModifierKind::Status, -(sapping.value() as i32)),
+        )),
+        _ => None,
+    }
+}
+
+// Flat-footed to one attacker only counts against that attacker
+fn flatfooted_to(
+    condition: &Tag,
+    stat: StatKind,
+    attacker: Option<CreatureId>,
+) -> Option<(String, Modifier)> {
+    match condition {
+        Tag::Flatfooted(Some(source)) if stat == StatKind::ArmorClass => {
+            (attacker == Some(*source)).then(|| {
+                let penalty = Modifier::new(ModifierKind::Circumstance, -2);
+                (format!("flat-footed to {source}"), penalty)
+            })
+        }
+        _ => None,
+    }
+}
+
+impl Creature {
+    // Drained takes its value times the creature's level, at least 1, off its maximum HP
+    pub(crate) fn effective_max_hp(&self) -> i32 {
+        let drained = self.conditions.iter().find(|c| matches!(c, Tag::Drained(_)));
+        let drain = drained.map_or(0, |drained| drained.value() as i32 * self.level.max(1));
+        self.max_hp - drain
+    }
+
+    // The modifiers the creature's conditions give the stat, before stacking
+    pub(crate) fn condition_modifiers(
+        &self,
+        stat: StatKind,
+        registry: &ConditionRegistry,
+    ) -> Vec<Modifier> {
+        self.conditions
+            .iter()
+            .filter_map(|condition| condition_modifier(condition, stat, registry))
+            .map(|(_, modifier)| modifier)
+            .collect()
+    }
+}
+
+impl Context<'_> {
+    /**
+     * Runs `roll` with the creature's condition penalties to the stat stacked on,
+     * so they count against the action's own modifiers under the stacking rules.
+     */
+    pub(crate) fn with_conditions<R>(
+        &mut self,
+        creature: &Creature,
+        registry: &ConditionRegistry,
+        stat: StatKind,
+        roll: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let (outer, luck, level) = (self.modifiers.len(), self.luck, self.level);
+        self.modifiers.extend(creature.condition_modifiers(stat, registry));
+        self.luck = Luck::of(creature);
+        self.level = Some(creature.level);
+        let rolled = roll(self);
+        self.modifiers.truncate(outer);
+        self.luck = luck;
+        self.level = level;
+        rolled
+    }
+}
+
+impl State<World> {
+    /**
+     * The creature's stat after its conditions, with each contribution itemized:
+     * first the base value, then every condition modifier that counts under
+     * the stacking rules. Missing creatures have nothing to show.
+     * AC against a particular attacker also counts flat-footed to it alone.
+     */
+    pub(crate) fn effective(
+        &self,
+        creature: CreatureId,
+        stat: StatKind,
+        attacker: Option<CreatureId>,
+    ) -> (i32, Vec<(String, i32)>) {
+        let Some(creature) = self.base.creature(creature) else {
+            return (0, vec![]);
+        };
+        let custom = &self.base.custom_conditions;
+        let base = match stat {
+            StatKind::ArmorClass => creature.armor_class(),
+            StatKind::Check(statistic) => creature.statistic_modifier(statistic),
+            StatKind::RangedAttack(group) => {
+                creature.modifier(Ability::Dexterity)
+                    + creature.proficiency_bonus(Statistic::Weapon(group))
+            }
+        };
+        let (names, modifiers): (Vec<String>, Vec<Modifier>) = creature
+            .conditions
+            .iter()
+            .filter_map(|condition| {
+                condition_modifier(condition, stat, custom)
+                    .or_else(|| flatfooted_to(condition, stat, attacker))
+            })
+            .unzip();
+        let mut itemized = vec![(String::from("base"), base)];
+        for i in counted(&modifiers) {
+            itemized.push((names[i].clone(), modifiers[i].bonus));
+        }
+        (itemized.iter().map(|(_, value)| value).sum(), itemized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::check::Degree;
+    use crate::dice::FixedDice;
+    use crate::engine::{Action, Engine, Meta, UpdateBuilder};
+    use crate::stats::{Save, Skill};
+    use crate::world::{heal, Creature, WorldBuilder};
+
+    fn state(conditions: Vec<Tag>) -> State<World> {
+        let mut fighter = Creature::new("Fighter", 20, 20);
+        fighter.conditions = conditions;
+        let world = WorldBuilder::new().add_creature(0, fighter).build();
+        State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        }
+    }
+
+    #[test]
+    fn flatfooted_is_itemized_against_ac() {
+        let state = state(vec![Tag::Flatfooted(None)]);
+        let (ac, itemized) = state.effective(0, StatKind::ArmorClass, None);
+        assert_eq!(ac, 18);
+        assert_eq!(
+            itemized,
+            vec![(String::from("base"), 20), (String::from("flat-footed"), -2)]
+        );
+    }
+
+    #[test]
+    fn flatfooted_to_one_attacker_only_counts_against_it() {
+        // Feinted by 1, so only 1 gets past its guard
+        let feinted = state(vec![Tag::Flatfooted(Some(1))]);
+        let ac = |attacker| feinted.effective(0, StatKind::ArmorClass, attacker).0;
+        assert_eq!((ac(Some(1)), ac(Some(2)), ac(None)), (18, 20, 20));
+        let (_, itemized) = feinted.effective(0, StatKind::ArmorClass, Some(1));
+        assert_eq!(itemized[1], (String::from("flat-footed to 1"), -2));
+    }
+
+    #[test]
+    fn seeded_drain_lowers_hp_and_fortitude() {
+        let mut fighter = Creature::new("Fighter", 40, 18);
+        fighter.level = 3;
+        let fighter = fighter.with_condition(Tag::Drained(2));
+        assert_eq!((fighter.hp, fighter.effective_max_hp()), (34, 34));
+        let mut state = state(vec![]);
+        state.base.creatures.insert(0, fighter);
+        let fortitude = StatKind::Check(Statistic::Save(Save::Fortitude));
+        assert_eq!(state.effective(0, fortitude, None).0, -2);
+        let reflex = StatKind::Check(Statistic::Save(Save::Reflex));
+        assert_eq!(state.effective(0, reflex, None).0, 0);
+        let state = heal(0, 10)(state);
+        assert_eq!(state.base.creature(0).unwrap().hp, 34);
+    }
+
+    #[test]
+    fn only_the_worst_status_penalty_counts() {
+        let state = state(vec![Tag::Frightened(1), Tag::Clumsy(2), Tag::Flatfooted(None)]);
+        let stealth = StatKind::Check(Statistic::Skill(Skill::Stealth));
+        assert_eq!(state.effective(0, stealth, None).0, -2);
+        let will = StatKind::Check(Statistic::Save(Save::Will));
+        assert_eq!(
+            state.effective(0, will, None).1,
+            vec![(String::from("base"), 0), (String::from("frightened 1"), -1)]
+        );
+    }
+
+    #[test]
+    fn enfeebled_only_weakens_strength_checks() {
+        let mut engine = Engine::new(state(vec![Tag::Enfeebled(2)]));
+        engine.dice = Box::new(FixedDice::new(vec![10]));
+        // Both checks are +0, so a 10 makes DC 10 unless enfeebled applies
+        let mut degree = |skill| {
+            engine.act(Action::skill_check(0, skill, 10, |degree, context| {
+                vec![UpdateBuilder::new()
+                    .filter(Arc::new(|state| state))
+                    .tag(Tag::Degree(degree))
+                    .build(context.next_id())]
+            }));
+            engine.step();
+            engine.updates[0].tags.contains(&Tag::Degree(Degree::Success))
+        };
+        assert!(!degree(Skill::Athletics));
+        assert!(degree(Skill::Acrobatics));
+
+        let sheet = state(vec![Tag::Enfeebled(2), Tag::Stupefied(1)]);
+        let athletics = StatKind::Check(Statistic::Skill(Skill::Athletics));
+        let enfeebled = (String::from("enfeebled 2"), -2);
+        assert_eq!(sheet.effective(0, athletics, None).1[1], enfeebled);
+        let spell = StatKind::Check(Statistic::Spell);
+        assert_eq!(sheet.effective(0, spell, None).0, -1);
+    }
+}
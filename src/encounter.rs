@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use crate::engine::{Engine, Filter, Meta, State, Tag};
+use crate::initiative::Initiative;
+use crate::world::{CreatureId, World};
+
+// Conditions that only matter in the thick of a fight
+fn transient(condition: &Tag) -> bool {
+    matches!(
+        condition,
+        Tag::Flatfooted(_)
+            | Tag::Frightened(_)
+            | Tag::Clumsy(_)
+            | Tag::Enfeebled(_)
+            | Tag::Stupefied(_)
+            | Tag::Stunned(_)
+            | Tag::Paralyzed
+            | Tag::ShieldRaised
+            | Tag::Grabbed
+            | Tag::Prone
+            | Tag::Concealed
+            | Tag::Cover(_)
+    )
+}
+
+/**
+ * Strips every creature of transient conditions and temporary hit points.
+ * Lasting ones like Drained and Wounded stay until the creature rests.
+ */
+pub(crate) fn encounter_cleanup() -> Filter<World> {
+    Arc::new(|mut state: State<World>| {
+        for creature in state.base.creatures.values_mut() {
+            creature.conditions.retain(|condition| !transient(condition));
+            creature.temp_hp.clear();
+        }
+        state
+    })
+}
+
+impl Engine<World> {
+    // Starts an engine on the world with initiative rolled, fresh meta and the first turn up
+    pub(crate) fn new_encounter(mut world: World, rolls: Vec<(CreatureId, i32)>) -> Self {
+        world.initiative = Initiative::new(rolls);
+        Engine::new(State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        })
+    }
+
+    pub(crate) fn end_encounter(&mut self) {
+        self.apply_filter(encounter_cleanup());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{Creature, Position, WorldBuilder};
+
+    #[test]
+    fn only_lasting_conditions_survive_the_encounter() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.gain_temp_hp("False Life", 5);
+        fighter.conditions = vec![Tag::Frightened(2), Tag::Drained(1), Tag::Wounded(1)];
+        let world = WorldBuilder::new().add_creature(0, fighter).build();
+        let mut engine = Engine::for_base(world);
+        engine.end_encounter();
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert_eq!(fighter.conditions, vec![Tag::Drained(1), Tag::Wounded(1)]);
+        assert_eq!(fighter.temp_hp.value, 0);
+        assert_eq!(fighter.hp, 20);
+    }
+
+    #[test]
+    fn built_worlds_start_with_initiative_rolled() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .set_position(1, Position::new(3, 2))
+            .build();
+        let engine = Engine::new_encounter(world, vec![(0, 11), (1, 17)]);
+        let world = &engine.state.base;
+        assert_eq!(world.creature(0).unwrap().position, Position::default());
+        assert_eq!(world.creature(1).unwrap().position, Position::new(3, 2));
+        assert_eq!(world.creature(1).unwrap().name, "Goblin");
+        assert_eq!(world.initiative.order, vec![1, 0]);
+        assert_eq!(world.initiative.current(), Some(1));
+    }
+}
@@ -1,27 +1,296 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 
-#[derive(Clone)]
-pub(crate) struct Meta {}
+use crate::aid::PreparedAid;
+use crate::check::Degree;
+use crate::combat::WeaponGroup;
+use crate::cover::CoverLevel;
+use crate::crit::CritRules;
+use crate::damage::DamageBreakdown;
+use crate::degree::DegreeAdjustment;
+use crate::content::{undeclared_tags, ContentError};
+use crate::derived::follow;
+use crate::dice::{Dice, SeededDice};
+use crate::diff::{DiffSubscriber, StateDiff};
+use crate::exclusive::exclude;
+use crate::frequency::Frequency;
+use crate::fortune::Luck;
+use crate::hero::Generated;
+use crate::immunity::ExpiryRound;
+use crate::knowledge::Recollection;
+use crate::history::Replay;
+use crate::hook::{HookDecision, PostResolveHook};
+use crate::index::{retag, TagIndex};
+use crate::log::{summarize, ActionSummary, LogEntry};
+use crate::metadata::{Metadata, Value};
+use crate::metrics::Metrics;
+use crate::modifier::Modifier;
+use crate::movement::Movement;
+use crate::oneshot::OneShot;
+use crate::policy::InputPolicy;
+use crate::ongoing::Ongoing;
+use crate::reaction::{Reaction, ReactionOption};
+use crate::sandbox::{ask, RuleError};
+use crate::ready::Readied;
+use crate::sustain::SustainedEffect;
+use crate::tags::TagSet;
+use crate::targeting::{TargetValidator, Targeting};
+use crate::temporary::Expiry;
+use crate::world::{CreatureId, Position};
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Meta {
+    pub(crate) actions_remaining: u8, // Actions left to spend this turn
+    pub(crate) captures: HashMap<String, i32>, // Field values saved by `filter::capture`
+    pub(crate) sustained: Vec<SustainedEffect>, // Effects that end unless sustained each turn
+    pub(crate) hero_points: u8,
+    pub(crate) turn: u32, // Turns ended so far
+    pub(crate) uses: HashMap<String, (Frequency, u32)>, // Uses of limited abilities, by name
+    pub(crate) one_shots: Vec<OneShot>, // Modifiers waiting for the next matching roll
+    pub(crate) aids: Vec<PreparedAid>,  // Creatures ready to Aid an ally's check
+    pub(crate) temporary_immunities: Vec<(CreatureId, Tag, ExpiryRound)>, // See `grant_immunity`
+    pub(crate) recalled: Vec<Recollection>, // Everything learned by Recalling Knowledge
+    pub(crate) reactions_used: Vec<CreatureId>, // Reaction spent until their next turn
+    pub(crate) resources: HashMap<String, u32>, // Focus points, spell slots and the like, by name
+    pub(crate) attacks_this_turn: HashMap<CreatureId, usize>, // For the multiple attack penalty
+    pub(crate) version: u64, // Commits so far, for sending only what changed; see `WorldDelta`
+}
+
+impl Default for Meta {
+    fn default() -> Self {
+        Meta {
+            actions_remaining: 3,
+            captures: HashMap::new(),
+            sustained: vec![],
+            hero_points: 1, // Everyone starts a session with one
+            turn: 0,
+            uses: HashMap::new(),
+            one_shots: vec![],
+            aids: vec![],
+            temporary_immunities: vec![],
+            recalled: vec![],
+            reactions_used: vec![],
+            resources: HashMap::new(),
+            attacks_this_turn: HashMap::new(),
+            version: 0,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
 pub(crate) struct TestBase {
     pub(crate) magics: u8,
     pub(crate) woos: i32,
     pub(crate) name: String,
 }
 
+impl Base for TestBase {}
+
 #[derive(Clone)]
 pub(crate) struct State<T> {
     pub(crate) meta: Meta,
     pub(crate) base: T,
+    pub(crate) pre_chain: Option<Arc<State<T>>>, // The state as the current chain began
+}
+
+impl<T> State<T> {
+    /**
+     * The state from before the current chain's first update, for effects sized
+     * by how things stood when the action began. Outside a chain it is this state.
+     */
+    pub(crate) fn pre_state(&self) -> &State<T> {
+        self.pre_chain.as_deref().unwrap_or(self)
+    }
+
+    /**
+     * Converts the base, keeping the meta, for migrating saves to a new world type.
+     * The snapshot of a chain in progress is dropped rather than converted twice.
+     */
+    pub(crate) fn map_base<U>(self, f: impl FnOnce(T) -> U) -> State<U> {
+        State {
+            meta: self.meta,
+            base: f(self.base),
+            pre_chain: None,
+        }
+    }
+}
+
+/** Base
+ *  The world data an engine runs over.
+ *  Each update names a target; a base holding several creatures can
+ *  override `apply` to confine the filter to that target's slice.
+ */
+pub(crate) trait Base: Clone {
+    fn apply(state: State<Self>, update: &Update<Self>) -> State<Self> {
+        (update.filter)(state)
+    }
+
+    // Why the update, going from `before` to `after`, changes nothing; it is then skipped
+    fn no_effect(
+        _before: &State<Self>,
+        _after: &State<Self>,
+        _update: &Update<Self>,
+    ) -> Option<String> {
+        None
+    }
+
+    // A condition keeping the creature from acting, if it has one
+    fn incapacitated(_state: &State<Self>, _actor: CreatureId) -> Option<Tag> {
+        None
+    }
+
+    // What changed between two states, for diff subscribers
+    fn diff(_before: &State<Self>, _after: &State<Self>) -> StateDiff {
+        StateDiff::default()
+    }
+
+    // Whose turn it is, for durations anchored to a creature's turn
+    fn current_turn(_state: &State<Self>) -> Option<CreatureId> {
+        None
+    }
+
+    // A stable hash of the base, see `State::fingerprint`; bases that don't hash all match
+    fn fingerprint(&self) -> u64 {
+        0
+    }
+}
+
+pub(crate) type Filter<T> = Arc<dyn Fn(State<T>) -> State<T> + Send + Sync>;
+
+// Tags order by variant, then value; this is their canonical order
+// (Their serde representation lives in `tags`)
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub(crate) enum Tag {
+    Flatfooted(Option<CreatureId>), // Off-guard to every creature, or only to the source
+    Frightened(u8),
+    Clumsy(u8),
+    Enfeebled(u8),
+    Stupefied(u8),
+    Dying(u8),
+    Wounded(u8), // Raises the Dying value the next time the creature falls
+    Drained(u8), // Outlasts the encounter, until the creature rests
+    Stunned(u8), // Actions lost at the start of the creature's next turns
+    Unconscious,
+    Paralyzed,
+    ShieldRaised, // +2 circumstance to AC until the creature's next turn
+    Grabbed,      // Held in place until it Escapes; see `Action::escape`
+    Prone,        // Flat-footed to melee and -2 to attack until it Stands; see `Action::stand`
+    Fortune,      // Its checks roll twice and keep the higher, unless it also has Misfortune
+    Misfortune,   // Its checks roll twice and keep the lower
+    Concealed,    // Attackers have to pass a flat check to target it
+    Cover(CoverLevel),
+    Custom { key: String, value: u8 }, // Defined by content, see `ConditionRegistry`
+    Manipulate, // The update involves manipulating something (provokes reactions)
+    Casting { caster: CreatureId, rank: u8 }, // A spell begins; see `Action::cast`
+    Attack {
+        attacker: CreatureId,
+        target: CreatureId,
+        group: WeaponGroup,
+        dice: u8,       // Number of weapon damage dice
+        weapon: String, // Which weapon, for activities that attack with more than one
+    },
+    Hit,            // The attack behind the update hit, critically or not
+    Degree(Degree), // The degree of success of the check behind the update
+    FlatCheck { dc: u8, passed: bool }, // Rolled before the update's own check, if any
+    Damage(DamageBreakdown), // How the update's damage was rolled, one tag per item
+    Move {
+        mover: CreatureId,
+        from: Position,
+        to: Position,
+        kind: Movement,
+    },
+    TurnStart(CreatureId), // The creature's turn begins with this update
 }
 
-pub(crate) type Filter<T> = Box<dyn Fn(State<T>) -> State<T>>;
+impl Tag {
+    // Conditions persist on the creature an update targets; other tags describe the update
+    pub(crate) fn is_condition(&self) -> bool {
+        matches!(
+            self,
+            Tag::Flatfooted(_)
+                | Tag::Frightened(_)
+                | Tag::Clumsy(_)
+                | Tag::Enfeebled(_)
+                | Tag::Stupefied(_)
+                | Tag::Dying(_)
+                | Tag::Wounded(_)
+                | Tag::Drained(_)
+                | Tag::Stunned(_)
+                | Tag::Unconscious
+                | Tag::Paralyzed
+                | Tag::ShieldRaised
+                | Tag::Grabbed
+                | Tag::Prone
+                | Tag::Fortune
+                | Tag::Misfortune
+                | Tag::Concealed
+                | Tag::Cover(_)
+                | Tag::Custom { .. }
+        )
+    }
+
+    // Conditions that leave a creature unable to act at all
+    pub(crate) fn is_incapacitating(&self) -> bool {
+        matches!(self, Tag::Unconscious | Tag::Paralyzed)
+            || matches!(self, Tag::Stunned(value) if *value > 0)
+    }
+
+    // Whether both tags are the same condition, whatever their values
+    pub(crate) fn same_kind(&self, other: &Tag) -> bool {
+        match (self, other) {
+            (Tag::Custom { key, .. }, Tag::Custom { key: other, .. }) => key == other,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+
+    // The highest value a valued condition can reach; Dying 4 is death
+    pub(crate) fn cap(&self) -> u8 {
+        match self {
+            Tag::Stunned(_) => u8::MAX,
+            _ => 4,
+        }
+    }
+
+    // A valued condition's value; 0 for anything else
+    pub(crate) fn value(&self) -> u8 {
+        match self {
+            Tag::Frightened(value)
+            | Tag::Clumsy(value)
+            | Tag::Enfeebled(value)
+            | Tag::Stupefied(value)
+            | Tag::Dying(value)
+            | Tag::Wounded(value)
+            | Tag::Drained(value)
+            | Tag::Stunned(value)
+            | Tag::Custom { value, .. } => *value,
+            _ => 0,
+        }
+    }
 
-#[derive(Eq, PartialEq, Hash)]
-pub(crate) enum Tag {}
+    // The same valued condition with a new value, held to its cap; anything else as it is
+    pub(crate) fn with_value(&self, value: u8) -> Tag {
+        let value = value.min(self.cap());
+        match self {
+            Tag::Frightened(_) => Tag::Frightened(value),
+            Tag::Clumsy(_) => Tag::Clumsy(value),
+            Tag::Enfeebled(_) => Tag::Enfeebled(value),
+            Tag::Stupefied(_) => Tag::Stupefied(value),
+            Tag::Dying(_) => Tag::Dying(value),
+            Tag::Wounded(_) => Tag::Wounded(value),
+            Tag::Drained(_) => Tag::Drained(value),
+            Tag::Stunned(_) => Tag::Stunned(value),
+            Tag::Custom { key, .. } => Tag::Custom {
+                key: key.clone(),
+                value,
+            },
+            other => other.clone(),
+        }
+    }
+}
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum Resolved {
     // Should represent 3 states:
     // An update is resolved and we should resolve here
@@ -32,41 +301,325 @@ pub(crate) enum Resolved {
     Unresolved,
 }
 
+#[derive(Clone)]
 pub(crate) struct Update<T> {
     pub(crate) filter: Filter<T>,
     pub(crate) id: usize,
-    pub(crate) tags: HashSet<Tag>,
+    pub(crate) target: CreatureId, // The creature this update's filter is applied to
+    pub(crate) tags: TagSet,
+    pub(crate) applies: TagSet, // The conditions in `tags` its target keeps once it commits
     pub(crate) resolved: Resolved,
+    pub(crate) meta: Metadata, // Structured data for rules and logs, like a spell's rank
+    pub(crate) outcome: Outcome<T>, // What the filter leaves; see `Update::outcome`
+}
+
+/** Outcome
+ *  An update's filter run once on the state its rules are shown.
+ *  A copy starts out empty, since it may well be shown some other state.
+ */
+pub(crate) struct Outcome<T>(OnceLock<Box<State<T>>>);
+
+impl<T> Default for Outcome<T> {
+    fn default() -> Self {
+        Outcome(OnceLock::new())
+    }
+}
+
+impl<T> Clone for Outcome<T> {
+    fn clone(&self) -> Self {
+        Outcome::default()
+    }
+}
+
+// The builder's state before it has been given a filter
+pub(crate) struct NoFilter;
+
+/** UpdateBuilder
+ *  Builds an Update with tags defaulting to empty and resolved to Unresolved.
+ *  `build` only exists once a filter has been set, so forgetting it won't compile.
+ */
+pub(crate) struct UpdateBuilder<F> {
+    filter: F,
+    target: CreatureId,
+    tags: TagSet,
+    applies: TagSet,
+    resolved: Resolved,
+    meta: Metadata,
+}
+
+impl UpdateBuilder<NoFilter> {
+    pub(crate) fn new() -> Self {
+        UpdateBuilder {
+            filter: NoFilter,
+            target: 0,
+            tags: TagSet::new(),
+            applies: TagSet::new(),
+            resolved: Resolved::Unresolved,
+            meta: Metadata::new(),
+        }
+    }
+
+    pub(crate) fn filter<T>(self, filter: Filter<T>) -> UpdateBuilder<Filter<T>> {
+        UpdateBuilder {
+            filter,
+            target: self.target,
+            tags: self.tags,
+            applies: self.applies,
+            resolved: self.resolved,
+            meta: self.meta,
+        }
+    }
+}
+
+impl<F> UpdateBuilder<F> {
+    pub(crate) fn target(mut self, target: CreatureId) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub(crate) fn tag(mut self, tag: Tag) -> Self {
+        self.tags.insert(tag);
+        self
+    }
+
+    // Tags the update with a condition its target is left with, not just one for rules to read
+    pub(crate) fn applies(mut self, tag: Tag) -> Self {
+        self.applies.insert(tag.clone());
+        self.tag(tag)
+    }
+
+    pub(crate) fn resolved(mut self, resolved: Resolved) -> Self {
+        self.resolved = resolved;
+        self
+    }
+
+    pub(crate) fn meta(mut self, key: &str, value: Value) -> Self {
+        self.meta.insert(String::from(key), value);
+        self
+    }
+}
+
+impl<T> UpdateBuilder<Filter<T>> {
+    // Take the id from `Engine::next_id` or `Context::next_id`
+    pub(crate) fn build(self, id: usize) -> Update<T> {
+        Update {
+            filter: self.filter,
+            id,
+            target: self.target,
+            tags: self.tags,
+            applies: self.applies,
+            resolved: self.resolved,
+            meta: self.meta,
+            outcome: Outcome::default(),
+        }
+    }
+}
+
+impl<T> Update<T> {
+    /**
+     * What the filter leaves of the state the rules are shown, for rules that
+     * compare before and after, like dying's. It is run the first time a rule asks,
+     * and kept until the engine next runs rules on the update, so they share it.
+     */
+    pub(crate) fn outcome(&self, state: &State<T>) -> &State<T>
+    where
+        T: Clone,
+    {
+        self.outcome.0.get_or_init(|| Box::new((self.filter)(state.clone())))
+    }
+
+    // The tags in canonical order, for anything that must be reproducible
+    pub(crate) fn sorted_tags(&self) -> Vec<&Tag> {
+        let mut tags: Vec<&Tag> = self.tags.iter().collect();
+        tags.sort();
+        tags
+    }
+}
+
+// A prompt for the player, with everything a client needs to render it
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct UserInput {
+    pub(crate) label: String,
+    pub(crate) kind: InputKind,
 }
 
-pub(crate) enum UserInput {}
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum InputKind {
+    Target(Vec<CreatureId>),       // Pick one of these creatures
+    Number { min: i32, max: i32 }, // Pick a value in this inclusive range
+    Choice(Vec<String>),           // Pick one of these named options
+}
+
+// The player's answer to a UserInput
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Input {
+    Target(CreatureId),
+    Number(i32),
+    Choice(usize), // Index into the offered options
+}
 
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) enum InvalidAction {
     BadPredicate,
+    Incapacitated(Tag), // The actor has a condition that stops it acting
+    NoValidTarget,
+    OutOfRange { needed: i32, actual: i32 }, // In feet
+    NotEnoughActions, // The action costs more than is left this turn
+    Exhausted,        // A limited ability has been used as often as it can be for now
+    ChainInProgress,  // Strict engines won't abandon uncommitted updates for a new activity
 }
 
 pub(crate) enum ActionResponse<T> {
     Valid(Vec<Update<T>>),
     Invalid(InvalidAction),
     RequestInput(UserInput),
+    Partial(Vec<Update<T>>, UserInput), // Keep these updates, then re-enter with the answer
+}
+
+// What a generator can use besides the state
+pub(crate) struct Context<'a> {
+    pub(crate) dice: &'a mut dyn Dice,
+    pub(crate) input: Option<&'a Input>, // The answer to this action's last request
+    pub(crate) answers: &'a [Input],     // Every answer the action has had, oldest first
+    pub(crate) ids: &'a mut usize,       // The engine's update id counter
+    pub(crate) modifiers: Vec<Modifier>, // Added to every check the action rolls
+    pub(crate) crit_rules: CritRules,
+    pub(crate) luck: Luck, // Whether checks roll twice; see `Context::with_conditions`
+    pub(crate) adjustments: Vec<DegreeAdjustment>, // Applied to every degree the action rolls
+    pub(crate) level: Option<i32>, // The rolling creature's, within `Context::with_conditions`
+}
+
+impl Context<'_> {
+    pub(crate) fn next_id(&mut self) -> usize {
+        let id = *self.ids;
+        *self.ids += 1;
+        id
+    }
 }
 
+// Works out a modifier for the action's checks as they are rolled
+pub(crate) type Contributor<T> = Arc<dyn Fn(&State<T>) -> Modifier + Send + Sync>;
+
+// Works out what an action costs from the answers it was given
+pub(crate) type Costing = Arc<dyn Fn(&[Input]) -> u8 + Send + Sync>;
+
+pub(crate) type Generator<T> =
+    Arc<dyn Fn(&State<T>, &mut Context<'_>) -> ActionResponse<T> + Send + Sync>;
+
 /** Action
  *  An action generates a chain of updates based on the current state.
  *  An action can request user input.
  *  An action requires a predicate to be satisfied by the current state.
  */
+#[derive(Clone)]
 pub(crate) struct Action<T> {
-    pub(crate) generator: Box<dyn Fn(&State<T>) -> ActionResponse<T>>,
+    pub(crate) generator: Generator<T>,
+    pub(crate) cost: u8, // Actions spent once the generator accepts; the least, with `costing`
+    pub(crate) costing: Option<Costing>, // For activities whose cost depends on a choice
+    pub(crate) targeting: Option<Targeting>, // Checked by the engine's validator before generating
+    pub(crate) frequency: Option<(String, Frequency)>, // A limited ability's name and limit
+    pub(crate) modifiers: Vec<Contributor<T>>, // Such as the penalty for firing into melee
+    pub(crate) adjustments: Vec<DegreeAdjustment>, // Such as incapacitation
+    pub(crate) rules_needed: Vec<String>, // Named rules the action relies on being present
+    pub(crate) name: Option<String>,      // What reports call it, like "Strike"
+    pub(crate) actor: Option<CreatureId>, // Who takes it, when it isn't targeted
+    pub(crate) declared_tags: Option<TagSet>, // Every kind of tag it may generate, if declared
 }
 
 impl<T> Action<T> {
-    pub(crate) fn new(generator: Box<dyn Fn(&State<T>) -> ActionResponse<T>>) -> Self {
-        Self { generator }
+    pub(crate) fn new(generator: Generator<T>) -> Self {
+        Self {
+            generator,
+            cost: 0,
+            costing: None,
+            targeting: None,
+            frequency: None,
+            modifiers: vec![],
+            adjustments: vec![],
+            rules_needed: vec![],
+            name: None,
+            actor: None,
+            declared_tags: None,
+        }
+    }
+
+    pub(crate) fn named(mut self, name: &str) -> Self {
+        self.name = Some(String::from(name));
+        self
+    }
+
+    pub(crate) fn by(mut self, actor: CreatureId) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+
+    // The creature taking the action, if it is known
+    pub(crate) fn actor(&self) -> Option<CreatureId> {
+        self.actor.or(self.targeting.as_ref().map(|targeting| targeting.actor))
+    }
+
+    pub(crate) fn with_cost(mut self, cost: u8) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /**
+     * A cost that depends on the answers, like metamagic adding an action.
+     * Taking the action needs at least `min` actions left; the rest is only
+     * checked, and spent, once the answers are all in.
+     */
+    pub(crate) fn with_variable_cost(mut self, min: u8, costing: Costing) -> Self {
+        self.cost = min;
+        self.costing = Some(costing);
+        self
+    }
+
+    // What the action costs given these answers, never less than its least
+    pub(crate) fn final_cost(&self, answers: &[Input]) -> u8 {
+        let chosen = self.costing.as_ref().map_or(0, |costing| costing(answers));
+        chosen.max(self.cost)
+    }
+
+    pub(crate) fn with_target(mut self, actor: CreatureId, target: CreatureId, range: i32) -> Self {
+        self.targeting = Some(Targeting {
+            actor,
+            target,
+            range,
+            melee: false,
+        });
+        self
+    }
+
+    // As `with_target`, for a melee action reaching `reach` feet with its weapon
+    pub(crate) fn with_melee_target(
+        self,
+        actor: CreatureId,
+        target: CreatureId,
+        reach: i32,
+    ) -> Self {
+        let mut action = self.with_target(actor, target, reach);
+        if let Some(targeting) = &mut action.targeting {
+            targeting.melee = true;
+        }
+        action
+    }
+
+    pub(crate) fn with_modifier(mut self, contributor: Contributor<T>) -> Self {
+        self.modifiers.push(contributor);
+        self
+    }
+
+    // Moves the degree of every check the action rolls, after it's worked out
+    pub(crate) fn with_degree_adjustment(mut self, adjustment: DegreeAdjustment) -> Self {
+        self.adjustments.push(adjustment);
+        self
     }
 
-    pub(crate) fn apply(&self, state: &State<T>) -> ActionResponse<T> {
-        (self.generator)(state)
+    // Any actions the generator applies in turn roll with this action's modifiers too
+    pub(crate) fn apply(&self, state: &State<T>, context: &mut Context<'_>) -> ActionResponse<T> {
+        context.modifiers.extend(self.modifiers.iter().map(|contributor| contributor(state)));
+        context.adjustments.extend(self.adjustments.iter().copied());
+        (self.generator)(state, context)
     }
 }
 
@@ -77,29 +630,423 @@ pub(crate) enum RuleResponse<T> {
     Inject(Action<T>), // Inject a new action into the update chain
     // (This must satisfy the predicate of the next update)
     Attach(Tag), // Attach a tag to the current update (to be used by future rules)
+    Apply(Tag),  // As Attach, and the target keeps it as a condition once the update commits
+    Detach(Tag), // Remove every tag of this kind from the current update
+    Halt,        // Skip the remaining rules; the current update still goes ahead
+}
+
+impl<T> RuleResponse<T> {
+    // How strongly the response alters the chain, if it does at all
+    fn interrupt_rank(&self) -> Option<u8> {
+        match self {
+            RuleResponse::Revert(_) => Some(3),
+            RuleResponse::Inject(_) => Some(2),
+            RuleResponse::Divert(_) => Some(1),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> ResponseKind {
+        match self {
+            RuleResponse::Skip => ResponseKind::Skip,
+            RuleResponse::Divert(_) => ResponseKind::Divert,
+            RuleResponse::Revert(_) => ResponseKind::Revert,
+            RuleResponse::Inject(_) => ResponseKind::Inject,
+            RuleResponse::Attach(_) => ResponseKind::Attach,
+            RuleResponse::Apply(_) => ResponseKind::Apply,
+            RuleResponse::Detach(_) => ResponseKind::Detach,
+            RuleResponse::Halt => ResponseKind::Halt,
+        }
+    }
+}
+
+// The shape of a RuleResponse without its payload
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum ResponseKind {
+    Skip,
+    Divert,
+    Revert,
+    Inject,
+    Attach,
+    Apply,
+    Detach,
+    Halt,
+}
+
+// Why the engine stopped short of finishing an activity
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum EngineError {
+    Rejected(InvalidAction), // The last activity was invalid and was dropped
+}
+
+// Why `resolve` had nothing to commit
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ResolveError {
+    EmptyChain,     // There is no chain to resolve
+    PastEnd(usize), // The cursor is past the last of the chain's updates
+}
+
+// What a host loop should do next: step, ask the player, or show the result
+#[derive(Debug, PartialEq)]
+pub(crate) enum EngineStatus<'a> {
+    Idle,
+    ActionPending, // An action is waiting to generate its updates
+    AwaitingInput(&'a UserInput),
+    AwaitingReactions(&'a [ReactionOption]), // A reaction window must be answered
+    Resolving,                               // Updates are still being worked through
+    Error(EngineError),
+}
+
+// One rule's decision about one update
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct RuleTrace {
+    pub(crate) update_id: usize,
+    pub(crate) rule_index: usize,
+    pub(crate) response: ResponseKind,
+}
+
+pub(crate) type Rule<T> = Arc<dyn Fn(&State<T>, &Update<T>) -> RuleResponse<T> + Send + Sync>;
+
+// Where `Engine::merge_chain` puts the other chain's updates
+pub(crate) enum MergePolicy<T> {
+    Append,                            // After everything queued
+    Prepend,                           // Before everything queued
+    ByPriority(fn(&Update<T>) -> i32), // Highest first
+}
+
+pub(crate) fn priority_of<T>(priorities: &[(Rule<T>, i32)], rule: &Rule<T>) -> i32 {
+    priorities
+        .iter()
+        .find(|(r, _)| Arc::ptr_eq(r, rule))
+        .map_or(0, |(_, priority)| *priority)
 }
 
-pub(crate) type Rule<T> = Box<dyn Fn(&State<T>, &Update<T>) -> RuleResponse<T>>;
+// Only sees the state, so it can't reach back into the engine
+pub(crate) type Subscriber<T> = Box<dyn Fn(&State<T>) + Send + Sync>;
 
-pub(crate) struct Engine<T: Clone> {
+pub(crate) struct Engine<T: Base> {
     // T is the base type (world data)
     pub(crate) action: Option<Action<T>>,      // Only 1 action can be active at a time
     pub(crate) rules: Vec<Rule<T>>,            // Rules are applied to each update in the chain
     pub(crate) updates: Vec<Update<T>>,        // Updates are applied to the base type
+    pub(crate) tag_index: TagIndex,            // Which of the chain's updates carry each tag
     pub(crate) update: usize,                  // The current update in the chain
+    pub(crate) rule: usize,                    // The next rule to run on the current update
+    pub(crate) committed: usize,               // Updates before this have been applied to the state
+    pub(crate) began: usize,                   // How long the history was when the activity began
+    pub(crate) charged: u8,                    // Actions spent on the current activity
+    pub(crate) rejected: Option<InvalidAction>, // Why the last activity was rejected
+    pub(crate) tracing_enabled: bool,          // Record every rule decision into the trace
+    pub(crate) linting: bool,                  // Check generated tags against declared ones
+    pub(crate) strict: bool,                   // Reject new activities over unfinished chains
+    pub(crate) lints: Vec<ContentError>,       // Tags actions generated without declaring them
+    pub(crate) trace: Vec<RuleTrace>,          // Rule decisions, in the order they were made
+    pub(crate) rule_errors: Vec<RuleError>,    // Rules that panicked, and were skipped
+    pub(crate) audit: Option<Vec<RuleTrace>>,  // A recorded trace the live one should match
+    pub(crate) reactions: Vec<Reaction<T>>,    // Reactions creatures could take when triggered
+    pub(crate) window: Option<Vec<ReactionOption>>, // An open reaction window pauses the engine
+    pub(crate) confirmed: Option<usize>,       // The update `run` paused on, approved to commit
+    pub(crate) readied: Vec<Readied<T>>,       // Actions waiting on their trigger
+    pub(crate) round_start_rules: Vec<Action<T>>, // Resolved as each round begins, before any turn
+    pub(crate) round_end_rules: Vec<Action<T>>, // Resolved once the last turn of a round is over
+    pub(crate) ongoing: Vec<Ongoing<T>>,       // Effects reapplied every step while they last
+    pub(crate) dice: Box<dyn Dice>,            // Every roll an action makes comes from here
+    pub(crate) crit_rules: CritRules,          // How checks and hits turn critical
+    pub(crate) input: Option<UserInput>,       // The prompt the pending action is waiting on
+    pub(crate) answers: Vec<Input>,            // The replies the pending action has had so far
+    pub(crate) gathered: Vec<Update<T>>,       // Updates the pending action produced before asking
+    pub(crate) ids: usize,                     // The id the next built update receives
+    pub(crate) versions: u64,                  // The last version committed; undo keeps it
+    pub(crate) subscribers: Vec<Subscriber<T>>, // Told about every state change, in order
+    pub(crate) diff_subscribers: Vec<DiffSubscriber>, // Told what each state change changed
+    pub(crate) post_resolve_hook: Option<PostResolveHook<T>>, // House rules' say before each commit
+    pub(crate) generated: Option<Generated<T>>, // The last action generated, while rerollable
+    pub(crate) validator: Option<Arc<dyn TargetValidator<T>>>, // Range checks for targeted actions
+    pub(crate) input_policy: Option<Arc<dyn InputPolicy<T>>>, // Answers prompts during `run`
+    pub(crate) registry: Vec<(String, Action<T>)>, // Named actions a player or AI can choose from
+    pub(crate) expiring: Vec<(Rule<T>, Expiry)>, // Rules to remove once their duration is up
+    pub(crate) priorities: Vec<(Rule<T>, i32)>, // Rules without an entry have priority 0
+    pub(crate) rule_names: Vec<(Rule<T>, String)>, // Names content uses to refer to rules
+    pub(crate) rule_triggers: Vec<(Rule<T>, Tag)>, // Kinds of tag a labelled rule is limited to
+    pub(crate) derived: Vec<(Tag, Tag)>,       // Tags that come and go with a trigger tag
+    pub(crate) exclusive: Vec<(Tag, Tag)>,     // Tags that take away another when attached
+    pub(crate) metrics: Metrics,               // How the current activity's chain has grown
+    pub(crate) log: Vec<LogEntry>,             // Every committed update, oldest first
+    pub(crate) replays: Vec<Replay<T>>,        // What each log entry applied, for `state_at`
+    pub(crate) logged: usize,                  // How many log entries the current state has seen
+    pub(crate) summaries: Vec<ActionSummary>,  // Every action that generated, oldest first
+    pub(crate) history: Vec<(State<T>, usize)>, // The state before each commit, and its `logged`
     pub(crate) state: State<T>,                // The current state of the engine
 }
 
-impl<T: Clone> Engine<T> {
+impl<T: Base> Engine<T> {
+    // An engine on the base with fresh meta, as most tests start
+    #[cfg(test)]
+    pub(crate) fn for_base(base: T) -> Self {
+        Engine::new(State {
+            meta: Meta::default(),
+            base,
+            pre_chain: None,
+        })
+    }
+
+    pub(crate) fn new(state: State<T>) -> Self {
+        Self {
+            action: None,
+            rules: vec![],
+            updates: vec![],
+            tag_index: TagIndex::new(),
+            update: 0,
+            rule: 0,
+            committed: 0,
+            began: 0,
+            charged: 0,
+            rejected: None,
+            tracing_enabled: false,
+            linting: false,
+            strict: false,
+            lints: vec![],
+            trace: vec![],
+            rule_errors: vec![],
+            audit: None,
+            reactions: vec![],
+            window: None,
+            confirmed: None,
+            readied: vec![],
+            ongoing: vec![],
+            dice: Box::new(SeededDice::new(0)),
+            crit_rules: CritRules::default(),
+            input: None,
+            answers: vec![],
+            gathered: vec![],
+            ids: 1,
+            versions: state.meta.version,
+            subscribers: vec![],
+            diff_subscribers: vec![],
+            post_resolve_hook: None,
+            generated: None,
+            validator: None,
+            input_policy: None,
+            registry: vec![],
+            expiring: vec![],
+            priorities: vec![],
+            rule_names: vec![],
+            rule_triggers: vec![],
+            round_start_rules: vec![],
+            round_end_rules: vec![],
+            derived: vec![],
+            exclusive: vec![],
+            metrics: Metrics::default(),
+            log: vec![],
+            replays: vec![],
+            logged: 0,
+            summaries: vec![],
+            history: vec![],
+            state,
+        }
+    }
+
+    /**
+     * An independent copy for exploring a branch, such as an AI's lookahead.
+     * Rules and pending closures are shared, the state and chain are copied,
+     * and the dice continue as a stream of their own for each branch index.
+     * Subscribers and the post-resolve hook stay with the original.
+     */
+    pub(crate) fn fork(&self, branch: u64) -> Engine<T> {
+        Engine {
+            action: self.action.clone(),
+            rules: self.rules.clone(),
+            updates: self.updates.clone(),
+            tag_index: self.tag_index.clone(),
+            update: self.update,
+            rule: self.rule,
+            committed: self.committed,
+            began: self.began,
+            charged: self.charged,
+            rejected: self.rejected.clone(),
+            tracing_enabled: self.tracing_enabled,
+            linting: self.linting,
+            strict: self.strict,
+            lints: self.lints.clone(),
+            trace: self.trace.clone(),
+            rule_errors: self.rule_errors.clone(),
+            audit: self.audit.clone(),
+            reactions: self.reactions.clone(),
+            window: self.window.clone(),
+            confirmed: self.confirmed,
+            readied: self.readied.clone(),
+            ongoing: self.ongoing.clone(),
+            dice: self.dice.fork(branch),
+            crit_rules: self.crit_rules,
+            input: self.input.clone(),
+            answers: self.answers.clone(),
+            gathered: self.gathered.clone(),
+            ids: self.ids,
+            versions: self.versions,
+            subscribers: vec![],
+            diff_subscribers: vec![],
+            post_resolve_hook: None,
+            generated: self.generated.clone(),
+            validator: self.validator.clone(),
+            input_policy: self.input_policy.clone(),
+            registry: self.registry.clone(),
+            expiring: self.expiring.clone(),
+            priorities: self.priorities.clone(),
+            rule_names: self.rule_names.clone(),
+            rule_triggers: self.rule_triggers.clone(),
+            round_start_rules: self.round_start_rules.clone(),
+            round_end_rules: self.round_end_rules.clone(),
+            derived: self.derived.clone(),
+            exclusive: self.exclusive.clone(),
+            metrics: self.metrics.clone(),
+            log: self.log.clone(),
+            replays: self.replays.clone(),
+            logged: self.logged,
+            summaries: self.summaries.clone(),
+            history: self.history.clone(),
+            state: self.state.clone(),
+        }
+    }
+
+    pub(crate) fn next_id(&mut self) -> usize {
+        let id = self.ids;
+        self.ids += 1;
+        id
+    }
+
+    pub(crate) fn pending_input(&self) -> Option<&UserInput> {
+        self.input.as_ref()
+    }
+
+    /**
+     * Answers the pending prompt; the action is re-run with the answer on the next step,
+     * along with every answer it was given before.
+     * Returns false if nothing is waiting on input.
+     */
+    pub(crate) fn provide_input(&mut self, input: Input) -> bool {
+        if self.input.take().is_none() {
+            return false;
+        }
+        self.answers.push(input);
+        true
+    }
+
+    /**
+     * Cancels the action waiting on input, which costs nothing while it is incomplete:
+     * everything its activity committed is undone, back to the state it began in.
+     * Returns false if nothing is waiting on input.
+     */
+    pub(crate) fn cancel_action(&mut self) -> bool {
+        if self.input.take().is_none() {
+            return false;
+        }
+        // A finished chain has no start left; only what came after it is dropped
+        if let Some(start) = self.state.pre_chain.take() {
+            self.history.truncate(self.began);
+            self.state = (*start).clone();
+            self.logged = self.log.len();
+        }
+        self.clear_updates();
+        self.update = 0;
+        self.rule = 0;
+        self.committed = 0;
+        self.charged = 0;
+        self.action = None;
+        self.answers.clear();
+        self.gathered.clear();
+        self.generated = None;
+        true
+    }
+
+    pub(crate) fn subscribe(&mut self, subscriber: Subscriber<T>) {
+        self.subscribers.push(subscriber);
+    }
+
+    pub(crate) fn trace(&self) -> &[RuleTrace] {
+        &self.trace
+    }
+
+    /**
+     * Gives another engine this engine's rules, after any it already has.
+     * The handles are shared rather than rebuilt, and a rule the other engine
+     * already shares isn't added twice.
+     */
+    pub(crate) fn share_rules_with(&self, other: &mut Engine<T>) {
+        for rule in self.rules.iter() {
+            if !other.rules.iter().any(|r| Arc::ptr_eq(r, rule)) {
+                other.rules.push(rule.clone());
+                let priority = priority_of(&self.priorities, rule);
+                if priority != 0 {
+                    other.priorities.push((rule.clone(), priority));
+                }
+            }
+        }
+    }
+
+    /**
+     * Adds a rule whose Divert, Revert or Inject can beat those of lower priority
+     * on the same update, wherever the rules sit in the list.
+     */
+    pub(crate) fn add_rule_with_priority(&mut self, rule: Rule<T>, priority: i32) {
+        self.rules.push(rule.clone());
+        self.priorities.push((rule, priority));
+    }
+
+    /**
+     * Starts a new activity, abandoning whatever is left of the previous chain.
+     * A strict engine refuses instead while any of its updates are uncommitted,
+     * rejecting the new action with `ChainInProgress` and leaving the chain be.
+     */
+    pub(crate) fn act(&mut self, action: Action<T>) {
+        if self.strict && self.committed < self.updates.len() {
+            self.rejected = Some(InvalidAction::ChainInProgress);
+            return;
+        }
+        self.clear_updates();
+        self.update = 0;
+        self.rule = 0;
+        self.committed = 0;
+        self.charged = 0;
+        self.rejected = None;
+        self.input = None;
+        self.answers.clear();
+        self.gathered.clear();
+        self.generated = None;
+        self.metrics = Metrics::default();
+        self.action = Some(action);
+        self.began = self.history.len();
+        // Filters thread this state along, so the snapshot travels with them
+        let mut start = self.state.clone();
+        start.pre_chain = None;
+        self.state.pre_chain = Some(Arc::new(start));
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     pub(crate) fn step(&mut self) {
+        self.advance();
+        #[cfg(feature = "invariants")]
+        self.check_invariants();
+    }
+
+    fn advance(&mut self) {
+        // Wait for the reaction window or the prompt to be answered
+        if self.window.is_some() || self.input.is_some() {
+            return;
+        }
+        self.tick_ongoing();
+
         // Handle Action
         if self.process_action() {
             return; // We don't want to process rules if we have an action
         }
 
         // Handle Rules
-        if self.process_rule() {
-            return; // We don't want to process updates if we have a rule
+        // (Updates are processed as part of the rule pass)
+        if !self.process_rule() {
+            // Past the end of the chain, so a last Partial update won't get any further
+            self.resolve_partial();
         }
 
         // // Handle Updates
@@ -108,61 +1055,284 @@ impl<T: Clone> Engine<T> {
         // }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(update_id = self.updates.get(self.update).map(|u| u.id))
+        )
+    )]
     fn process_rule(&mut self) -> bool {
-        if let Some(update) = self.updates.last_mut() {
+        if let Some(update) = self.updates.get_mut(self.update) {
+            // What the state the rules see becomes may have changed while it was paused
+            update.outcome = Outcome::default();
             // Update is moved into the for loop
-            for rule in self.rules.iter() {
-                let response = 
-                    rule(&self.state, &update);
-    
+            // (An update paused by an injection picks up after the injecting rule)
+            let mut contender: Option<(usize, i32, RuleResponse<T>)> = None;
+            let before = update.tags.clone();
+            // Tags the update was built with bring their dependents too
+            if self.rule == 0 && !self.derived.is_empty() {
+                let present: Vec<Tag> = update.tags.iter().cloned().collect();
+                for tag in present {
+                    follow(&self.derived, &tag, true, &mut update.tags);
+                    if update.applies.contains(&tag) {
+                        follow(&self.derived, &tag, true, &mut update.applies);
+                    }
+                }
+            }
+            for (index, rule) in self.rules.iter().enumerate().skip(self.rule) {
+                // A panicking rule is treated as having nothing to say
+                let response = match ask(rule, &self.state, update) {
+                    Ok(response) => response,
+                    Err(message) => {
+                        self.rule_errors.push(RuleError {
+                            update_id: update.id,
+                            rule_index: index,
+                            message,
+                        });
+                        continue;
+                    }
+                };
+                #[cfg(feature = "tracing")]
+                tracing::trace!(
+                    update_id = update.id,
+                    rule_index = index,
+                    response = ?response.kind(),
+                    "rule fired"
+                );
+                if self.tracing_enabled {
+                    self.trace.push(RuleTrace {
+                        update_id: update.id,
+                        rule_index: index,
+                        response: response.kind(),
+                    });
+                }
+
+                // Once the chain is to be altered, later rules only compete to alter it
+                // instead, by priority and then Revert > Inject > Divert
+                if let Some(rank) = response.interrupt_rank() {
+                    let priority = priority_of(&self.priorities, rule);
+                    let beats = contender.as_ref().is_none_or(|(_, best, held)| {
+                        (priority, rank) > (*best, held.interrupt_rank().unwrap_or(0))
+                    });
+                    if beats {
+                        contender = Some((index, priority, response));
+                    }
+                    continue;
+                }
+                if contender.is_some() {
+                    continue;
+                }
+
                 match response {
                     RuleResponse::Skip => {
                         // Do nothing
                     }
+                    RuleResponse::Divert(_) | RuleResponse::Revert(_) | RuleResponse::Inject(_) => {
+                        // Held above until every rule has had its say
+                    }
+                    RuleResponse::Attach(t) | RuleResponse::Apply(t)
+                        if self.state.meta.temporarily_immune(update.target, &t) =>
+                    {
+                        // The target can't gain it again yet
+                    }
+                    RuleResponse::Attach(t) => {
+                        // Attach a tag to the current update (to be used by future rules)
+                        exclude(&self.exclusive, &t, &mut update.tags);
+                        follow(&self.derived, &t, true, &mut update.tags);
+                        update.tags.insert(t);
+                    }
+                    RuleResponse::Apply(t) => {
+                        // As Attach, with it and whatever follows from it kept by the target
+                        exclude(&self.exclusive, &t, &mut update.tags);
+                        follow(&self.derived, &t, true, &mut update.tags);
+                        follow(&self.derived, &t, true, &mut update.applies);
+                        update.applies.insert(t.clone());
+                        update.tags.insert(t);
+                    }
+                    RuleResponse::Detach(t) => {
+                        // Remove the condition regardless of its value
+                        follow(&self.derived, &t, false, &mut update.tags);
+                        follow(&self.derived, &t, false, &mut update.applies);
+                        update.tags.retain(|tag| !tag.same_kind(&t));
+                        update.applies.retain(|tag| !tag.same_kind(&t));
+                    }
+                    RuleResponse::Halt => {
+                        // No later rule sees this update
+                        break;
+                    }
+                }
+            }
+            retag(&mut self.tag_index, update.id, &before, &update.tags);
+            if let Some((index, _, response)) = contender {
+                match response {
                     RuleResponse::Divert(a) => {
                         // Kill all future updates in the chain, create a new action
-                        self.updates.truncate(self.update);
+                        self.metrics.diversions += 1;
+                        self.truncate_updates(self.update);
+                        self.rule = 0;
                         self.action = Some(a);
-                        return true;
                     }
                     RuleResponse::Revert(a) => {
                         // Kill all future and past updates in the chain
-                        self.updates.clear();
+                        self.metrics.reverts += 1;
+                        self.clear_updates();
                         self.update = 0;
+                        self.rule = 0;
+                        self.committed = 0;
                         self.action = Some(a);
-                        return true;
                     }
                     RuleResponse::Inject(a) => {
                         // Inject a new action into the update chain
+                        // Its updates follow this one, which resumes after the injecting rule
+                        self.metrics.injections += 1;
+                        self.rule = index + 1;
                         self.action = Some(a);
-                        return true;
-                    }
-                    RuleResponse::Attach(t) => {
-                        // Attach a tag to the current update (to be used by future rules)
-                        update.tags.insert(t);
                     }
+                    _ => {}
                 }
+                return true;
             }
             self.process_update();
+            // Anything the update set off gets its chance before the next one
+            let watched = !self.reactions.is_empty()
+                || !self.readied.is_empty()
+                || !self.expiring.is_empty();
+            let provoked = watched.then(|| self.updates[self.update].tags.clone());
             self.update += 1;
+            self.rule = 0;
+            self.settle();
+            if let Some(tags) = provoked {
+                self.expire_at_turn_start(&tags);
+                self.provoke(&tags);
+                self.trigger_readied(&tags);
+            }
             true
         } else {
             false
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     fn process_action(&mut self) -> bool {
-        if let Some(action) = &self.action {
-            let response = action.apply(&self.state);
+        if let Some(action) = self.action.take() {
+            // Free actions are still allowed
+            let incapacity = action
+                .actor()
+                .filter(|_| action.cost > 0)
+                .and_then(|actor| T::incapacitated(&self.state, actor));
+            let checked = match (&action.targeting, &self.validator, incapacity) {
+                (_, _, Some(condition)) => Err(InvalidAction::Incapacitated(condition)),
+                _ if action.cost > self.state.meta.actions_remaining => {
+                    Err(InvalidAction::NotEnoughActions)
+                }
+                _ if matches!(&action.frequency,
+                    Some((name, frequency)) if self.state.meta.exhausted(name, *frequency)) =>
+                {
+                    Err(InvalidAction::Exhausted)
+                }
+                (Some(targeting), Some(validator), _) => {
+                    validator.validate(&self.state, targeting)
+                }
+                _ => Ok(()),
+            };
+            let mut context = Context {
+                dice: self.dice.as_mut(),
+                input: self.answers.last(),
+                answers: &self.answers,
+                ids: &mut self.ids,
+                modifiers: vec![],
+                crit_rules: self.crit_rules,
+                luck: Luck::Single,
+                adjustments: vec![],
+                level: None,
+            };
+            let response = match checked {
+                Ok(()) => action.apply(&self.state, &mut context),
+                Err(reason) => ActionResponse::Invalid(reason),
+            };
+            #[cfg(feature = "tracing")]
+            match &response {
+                ActionResponse::Valid(updates) => {
+                    tracing::debug!(updates = updates.len(), "action applied")
+                }
+                ActionResponse::Invalid(reason) => tracing::debug!(?reason, "action rejected"),
+                ActionResponse::RequestInput(_) | ActionResponse::Partial(..) => {
+                    tracing::debug!("action needs input")
+                }
+            }
+            // A cost chosen with the answers can turn out to be more than is left
+            let cost = action.final_cost(&self.answers);
+            let response = match response {
+                ActionResponse::Valid(_)
+                    if action.costing.is_some() && cost > self.state.meta.actions_remaining =>
+                {
+                    ActionResponse::Invalid(InvalidAction::NotEnoughActions)
+                }
+                response => response,
+            };
             match response {
                 ActionResponse::Valid(updates) => {
-                    todo!();
+                    // The cost is spent now, but refunded if nothing commits
+                    let meta = &mut self.state.meta;
+                    let cost = cost.min(meta.actions_remaining);
+                    meta.actions_remaining -= cost;
+                    self.charged += cost;
+                    if let Some((name, frequency)) = &action.frequency {
+                        meta.record_use(name, *frequency);
+                    }
+                    // New updates happen before whatever is still queued,
+                    // but after an update that is paused mid-rules
+                    let at = if self.rule > 0 {
+                        self.update + 1
+                    } else {
+                        self.update
+                    };
+                    // Whatever was produced before the last prompt comes first
+                    let gathered = std::mem::take(&mut self.gathered);
+                    let (first, len) = (gathered.len(), updates.len());
+                    self.updates.splice(at..at, gathered.into_iter().chain(updates));
+                    let spliced = self.updates[at..at + first + len].to_vec();
+                    self.index_updates(&spliced);
+                    if let Some(summary) = summarize(&action, &spliced) {
+                        self.summaries.push(summary);
+                    }
+                    if self.linting {
+                        self.lints.extend(undeclared_tags(&action, &spliced[first..]));
+                    }
+                    self.metrics.depth = self.metrics.depth.max(self.updates.len());
+                    self.generated = Some(Generated {
+                        action,
+                        answers: std::mem::take(&mut self.answers),
+                        at: at + first,
+                        len,
+                    });
+                }
+                ActionResponse::Invalid(reason) => {
+                    // Uncommitted updates are dropped with the activity
+                    self.truncate_updates(self.committed);
+                    self.update = self.committed;
+                    self.rule = 0;
+                    if self.committed == 0 {
+                        self.state.meta.actions_remaining += self.charged;
+                    }
+                    self.charged = 0;
+                    self.answers.clear();
+                    self.gathered.clear();
+                    self.rejected = Some(reason);
+                    self.settle();
                 }
-                ActionResponse::Invalid(_) => {
-                    todo!();
+                ActionResponse::RequestInput(input) => {
+                    // Suspend until provide_input
+                    self.input = Some(input);
+                    self.action = Some(action);
                 }
-                ActionResponse::RequestInput(_) => {
-                    todo!();
+                ActionResponse::Partial(updates, input) => {
+                    // Held back until the action finishes
+                    self.gathered.extend(updates);
+                    self.input = Some(input);
+                    self.action = Some(action);
                 }
             }
             true
@@ -171,13 +1341,122 @@ impl<T: Clone> Engine<T> {
         }
     }
 
-    fn process_update(&mut self) -> bool {
-        if let Some(update) = self.updates.get_mut(self.update) {
-            if update.resolved == Resolved::Resolved {
-                // This update represents a FULLY resolved state
-                // We can apply it to the state
-                if let Some(state) = self.resolve() {
-                    self.state = state;
+    /**
+     * Once every update has been processed and committed the chain is finished,
+     * so it is cleared and the cursor returns to the start.
+     * A chain ending on an uncommitted update waits past its end instead.
+     */
+    fn settle(&mut self) {
+        if self.update >= self.updates.len() && self.committed == self.updates.len() {
+            self.clear_updates();
+            self.update = 0;
+            self.committed = 0;
+            self.charged = 0;
+            self.generated = None;
+            self.state.pre_chain = None;
+        }
+    }
+
+    /**
+     * Moves a queued update from one chain position to another.
+     * Only updates at or after the cursor are queued; processed ones stay put.
+     * The cursor keeps its position, so it points at whichever update is now next.
+     * If that is no longer an update paused mid-rules, it starts from the first rule.
+     */
+    pub(crate) fn reorder_update(&mut self, from: usize, to: usize) -> bool {
+        let queued = self.update..self.updates.len();
+        if !queued.contains(&from) || !queued.contains(&to) {
+            return false;
+        }
+        let paused = self.updates[self.update].id;
+        let update = self.updates.remove(from);
+        self.updates.insert(to, update);
+        if self.updates[self.update].id != paused {
+            self.rule = 0;
+        }
+        true
+    }
+
+    /**
+     * Drops a queued update by id, returning it.
+     * Removing the update under the cursor advances to the one after it,
+     * which starts from the first rule even if the removed one was paused.
+     */
+    pub(crate) fn remove_update(&mut self, id: usize) -> Option<Update<T>> {
+        let index = self.updates[self.update..].iter().position(|u| u.id == id)?;
+        if index == 0 {
+            self.rule = 0;
+        }
+        let update = self.updates.remove(self.update + index);
+        self.unindex_updates(std::slice::from_ref(&update));
+        self.settle();
+        Some(update)
+    }
+
+    /**
+     * Adds another chain's updates to the queued part of this one, with fresh ids.
+     * Processed updates stay put, and the cursor still points at the next update to run.
+     */
+    pub(crate) fn merge_chain(&mut self, other: Vec<Update<T>>, policy: MergePolicy<T>) {
+        let mut other = other;
+        for update in other.iter_mut() {
+            update.id = self.next_id();
+        }
+        self.index_updates(&other);
+        // Like spliced actions, merged updates go after one paused mid-rules
+        let queued = if self.rule > 0 {
+            self.update + 1
+        } else {
+            self.update
+        };
+        match policy {
+            MergePolicy::Append => self.updates.extend(other),
+            MergePolicy::Prepend => {
+                self.updates.splice(queued..queued, other);
+            }
+            MergePolicy::ByPriority(priority) => {
+                // Stable, so equal priorities keep this chain's updates first, each in order
+                let mut merged = self.updates.split_off(queued);
+                merged.extend(other);
+                merged.sort_by_key(|update| std::cmp::Reverse(priority(update)));
+                self.updates.extend(merged);
+            }
+        }
+    }
+
+    // Nothing left to do until a new action arrives
+    pub(crate) fn is_idle(&self) -> bool {
+        self.action.is_none() && self.window.is_none() && self.updates.is_empty()
+    }
+
+    /**
+     * Where the engine is, for hosts deciding what to do next.
+     * A prompt or window comes first since stepping can't get past it,
+     * and a rejection is reported once the engine has otherwise gone idle.
+     */
+    pub(crate) fn status(&self) -> EngineStatus<'_> {
+        if let Some(input) = &self.input {
+            EngineStatus::AwaitingInput(input)
+        } else if let Some(window) = &self.window {
+            EngineStatus::AwaitingReactions(window)
+        } else if self.action.is_some() {
+            EngineStatus::ActionPending
+        } else if !self.updates.is_empty() {
+            EngineStatus::Resolving
+        } else if let Some(reason) = &self.rejected {
+            EngineStatus::Error(EngineError::Rejected(reason.clone()))
+        } else {
+            EngineStatus::Idle
+        }
+    }
+
+    fn process_update(&mut self) -> bool {
+        if let Some(update) = self.updates.get_mut(self.update) {
+            if update.resolved == Resolved::Resolved {
+                // This update represents a FULLY resolved state
+                // We can apply it to the state
+                if let Ok(Some(state)) = self.resolve() {
+                    self.commit(state);
                 }
             }
             true
@@ -188,21 +1467,109 @@ impl<T: Clone> Engine<T> {
 
     /**
      * 1. Check if the current update is resolved
-     * 2. If it is, apply all updates in the chain to the state permenantly
-     * 3. If it is not, return None
-     * 4. If successful, clean up the update chain in case we resolved early
+     * 2. If it is, apply every uncommitted update up to it to the state permenantly
+     * 3. If it is not, return Ok(None) so the caller can try again later
+     * 4. If successful, mark those updates as committed
+     * 5. Let the post-resolve hook accept, replace or reject the result; a rejected one
+     *    returns Ok(None) too, with its updates dropped from the log but still marked committed
+     *
+     * An error means there is no current update to resolve at all.
+     * Only the uncommitted stretch is folded into the committed state, never the whole
+     * chain from the base, so each update is applied once: O(1) amortized per update.
      */
-    pub(crate) fn resolve(&mut self) -> Option<State<T>> {
-        let update = self.updates.last().unwrap();
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(update_id = self.updates.get(self.update).map(|u| u.id))
+        )
+    )]
+    pub(crate) fn resolve(&mut self) -> Result<Option<State<T>>, ResolveError> {
+        let Some(update) = self.updates.get(self.update) else {
+            return Err(match self.updates.len() {
+                0 => ResolveError::EmptyChain,
+                len => ResolveError::PastEnd(len),
+            });
+        };
         if update.resolved == Resolved::Unresolved {
+            return Ok(None);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(from = self.committed, to = self.update, "resolve committed");
+        let logged = self.log.len();
+        let state = self.fold(self.update);
+        self.committed = self.update + 1;
+        let decision = match &self.post_resolve_hook {
+            Some(hook) => hook(&self.state, &state),
+            None => HookDecision::Accept,
+        };
+        match decision {
+            HookDecision::Accept => Ok(Some(state)),
+            HookDecision::Replace(replaced) => Ok(Some(*replaced)),
+            HookDecision::Reject => {
+                self.log.truncate(logged);
+                self.replays.truncate(logged);
+                Ok(None)
+            }
+        }
+    }
+
+    /**
+     * Commits a chain that ran out on a Partial update, such as an optional
+     * follow-up the player declined. Returns None unless the cursor is past
+     * the end of such a chain with its last update still uncommitted.
+     */
+    pub(crate) fn resolve_partial(&mut self) -> Option<State<T>> {
+        let last = self.updates.len().checked_sub(1)?;
+        if self.update <= last
+            || self.committed > last
+            || self.updates[last].resolved != Resolved::Partial
+        {
             return None;
         }
+        let state = self.fold(last);
+        self.commit(state.clone());
+        self.committed = last + 1;
+        self.settle();
+        Some(state)
+    }
+
+    // Applies and logs every uncommitted update up to `to`, skipping any with no effect
+    fn fold(&mut self, to: usize) -> State<T> {
         let mut state = self.state.clone();
-        for update in self.updates.iter() {
-            let filter = &update.filter;
-            state = filter(state);
+        let mut outcomes = vec![];
+        for update in self.updates[self.committed..=to].iter() {
+            let after = T::apply(state.clone(), update);
+            let reason = T::no_effect(&state, &after, update);
+            if reason.is_none() {
+                state = after;
+                self.metrics.applied += 1;
+            }
+            outcomes.push((reason, state.fingerprint()));
         }
-        self.updates.clear();
+        self.record(self.committed, outcomes);
+        state
+    }
+
+    /**
+     * Stops the activity at the chosen update, whatever its resolved status.
+     * Every uncommitted update up to it is applied and the rest of the chain is dropped.
+     * Returns None if the update isn't in the chain or is already committed.
+     */
+    pub(crate) fn resolve_at(&mut self, update_id: usize) -> Option<State<T>> {
+        let at = self.updates.iter().position(|u| u.id == update_id)?;
+        if at < self.committed {
+            return None;
+        }
+        let state = self.fold(at);
+        self.commit(state.clone());
+        self.truncate_updates(at + 1);
+        self.update = at + 1;
+        self.rule = 0;
+        self.committed = at + 1;
+        self.action = None;
+        self.settle();
         Some(state)
     }
 }
@@ -210,55 +1577,805 @@ impl<T: Clone> Engine<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::world::Creature;
+
+    fn test_state() -> State<TestBase> {
+        State {
+            meta: Meta::default(),
+            base: TestBase {
+                magics: 0,
+                woos: 0,
+                name: String::from(""),
+            },
+            pre_chain: None,
+        }
+    }
+
+    fn magic_update(id: usize, resolved: Resolved) -> Update<TestBase> {
+        Update {
+            filter: Arc::new(|mut state| {
+                state.base.magics += 1;
+                state
+            }),
+            id,
+            target: 0,
+            tags: TagSet::new(),
+            applies: TagSet::new(),
+            resolved,
+            meta: Metadata::new(),
+            outcome: Outcome::default(),
+        }
+    }
+
+    // A two-action activity whose follow-up is rejected when `failing_id` is processed
+    fn failing_activity(engine: &mut Engine<TestBase>, failing_id: usize) {
+        engine.rules.push(Arc::new(move |_, update| {
+            if update.id == failing_id {
+                RuleResponse::Inject(Action::new(Arc::new(|_, _| {
+                    ActionResponse::Invalid(InvalidAction::BadPredicate)
+                })))
+            } else {
+                RuleResponse::Skip
+            }
+        }));
+        engine.act(
+            Action::new(Arc::new(|_, _| {
+                ActionResponse::Valid(vec![
+                    magic_update(1, Resolved::Resolved),
+                    magic_update(2, Resolved::Unresolved),
+                ])
+            }))
+            .with_cost(2),
+        );
+    }
 
     #[test]
     fn test() {
-        let mut engine = Engine {
-            action: None,
-            rules: vec![],
-            updates: vec![],
-            update: 0,
-            state: State {
-                meta: Meta {},
-                base: TestBase {
-                    magics: 0,
-                    woos: 0,
-                    name: String::from(""),
-                },
-            },
-        };
+        let mut engine = Engine::new(test_state());
         engine.step();
         assert_eq!(engine.update, 0);
     }
 
     #[test]
     fn test2() {
-        let mut engine = Engine {
-            action: None,
-            rules: vec![],
-            updates: vec![],
-            update: 0,
-            state: State {
-                meta: Meta {},
-                base: TestBase {
-                    magics: 0,
-                    woos: 0,
-                    name: String::from(""),
-                },
-            },
-        };
+        let mut engine = Engine::new(test_state());
         engine.updates.push(Update {
-            filter: Box::new(|state| {
+            filter: Arc::new(|state| {
                 let mut state = state.clone();
                 state.base.magics += 1;
                 state
             }),
             resolved: Resolved::Resolved,
-            tags: HashSet::new(),
+            meta: Metadata::new(),
+            tags: TagSet::new(),
+            applies: TagSet::new(),
             id: engine.update + 1,
+            target: 0,
+            outcome: Outcome::default(),
         });
         engine.step();
+        assert_eq!(engine.update, 0);
+        assert_eq!(engine.state.base.magics, 1);
+    }
+
+    #[test]
+    fn resolve_says_why_nothing_was_committed() {
+        let mut engine = Engine::new(test_state());
+        assert_eq!(engine.resolve().err(), Some(ResolveError::EmptyChain));
+        engine.updates.push(magic_update(0, Resolved::Unresolved));
+        assert!(matches!(engine.resolve(), Ok(None)));
+        engine.updates[0].resolved = Resolved::Resolved;
+        assert_eq!(engine.resolve().unwrap().unwrap().base.magics, 1);
+        engine.update = 1;
+        assert_eq!(engine.resolve().err(), Some(ResolveError::PastEnd(1)));
+    }
+
+    #[test]
+    fn rejection_before_resolution_refunds_cost() {
+        let mut engine = Engine::new(test_state());
+        failing_activity(&mut engine, 1);
+        engine.step(); // Activity accepted, 2 actions spent
+        assert_eq!(engine.state.meta.actions_remaining, 1);
+        engine.step(); // The first update injects the failing follow-up
+        engine.step(); // The follow-up is rejected
+        assert_eq!(engine.rejected, Some(InvalidAction::BadPredicate));
+        assert_eq!(engine.state.meta.actions_remaining, 3);
+        assert_eq!(engine.state.base.magics, 0);
+        assert!(engine.updates.is_empty());
+    }
+
+    #[test]
+    fn rejection_after_resolution_keeps_cost() {
+        let mut engine = Engine::new(test_state());
+        failing_activity(&mut engine, 2);
+        engine.step(); // Activity accepted, 2 actions spent
+        engine.step(); // The first update resolves
+        assert_eq!(engine.state.base.magics, 1);
+        engine.step(); // The second update injects the failing follow-up
+        engine.step(); // The follow-up is rejected
+        assert_eq!(engine.rejected, Some(InvalidAction::BadPredicate));
+        assert_eq!(engine.state.meta.actions_remaining, 1);
+        assert_eq!(engine.state.base.magics, 1);
+        assert!(engine.is_idle());
+    }
+
+    #[test]
+    fn trace_records_rule_decisions_in_order() {
+        let mut engine = Engine::new(test_state());
+        engine.tracing_enabled = true;
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Skip));
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Flatfooted(None))));
+        engine.updates.push(magic_update(7, Resolved::Resolved));
+        engine.step();
+        assert_eq!(
+            engine.trace(),
+            &[
+                RuleTrace {
+                    update_id: 7,
+                    rule_index: 0,
+                    response: ResponseKind::Skip,
+                },
+                RuleTrace {
+                    update_id: 7,
+                    rule_index: 1,
+                    response: ResponseKind::Attach,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn detach_removes_tags_of_the_same_kind() {
+        let mut engine = Engine::new(test_state());
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Frightened(2))));
+        engine.rules.push(Arc::new(|_, update| {
+            assert!(update.tags.contains(&Tag::Frightened(2)));
+            RuleResponse::Detach(Tag::Frightened(0))
+        }));
+        engine.rules.push(Arc::new(|_, update| {
+            assert!(update.tags.is_empty());
+            RuleResponse::Skip
+        }));
+        engine.updates.push(magic_update(1, Resolved::Unresolved));
+        engine.step();
+        assert!(engine.updates[0].tags.is_empty());
+    }
+
+    #[test]
+    fn sorted_tags_ignore_insertion_order() {
+        let tags = [Tag::Manipulate, Tag::Frightened(2), Tag::Flatfooted(None), Tag::Frightened(1)];
+        let run = |order: Vec<usize>| {
+            let mut engine = Engine::new(test_state());
+            for i in order {
+                let tag = tags[i].clone();
+                engine.rules.push(Arc::new(move |_, _| RuleResponse::Attach(tag.clone())));
+            }
+            engine.updates.push(magic_update(1, Resolved::Unresolved));
+            engine.step();
+            format!("{:?}", engine.updates[0].sorted_tags())
+        };
+        let forward = run(vec![0, 1, 2, 3]);
+        assert_eq!(forward, run(vec![3, 2, 1, 0]));
+        assert_eq!(forward, "[Flatfooted(None), Frightened(1), Frightened(2), Manipulate]");
+    }
+
+    #[test]
+    fn status_follows_the_activity() {
+        let mut engine = Engine::new(test_state());
+        assert_eq!(engine.status(), EngineStatus::Idle);
+        let prompt = UserInput {
+            label: String::from("How many magics?"),
+            kind: InputKind::Number { min: 1, max: 3 },
+        };
+        let asked = prompt.clone();
+        engine.act(Action::new(Arc::new(move |_, context| match context.input {
+            None => ActionResponse::RequestInput(asked.clone()),
+            Some(_) => ActionResponse::Valid(vec![magic_update(1, Resolved::Unresolved)]),
+        })));
+        assert_eq!(engine.status(), EngineStatus::ActionPending);
+        engine.step();
+        assert_eq!(engine.status(), EngineStatus::AwaitingInput(&prompt));
+        engine.provide_input(Input::Number(2));
+        engine.step();
+        assert_eq!(engine.status(), EngineStatus::Resolving);
+
+        failing_activity(&mut engine, 2);
+        for _ in 0..4 {
+            engine.step();
+        }
+        assert_eq!(
+            engine.status(),
+            EngineStatus::Error(EngineError::Rejected(InvalidAction::BadPredicate))
+        );
+    }
+
+    #[test]
+    fn mapping_the_base_keeps_the_meta() {
+        let mut state = test_state();
+        state.meta.actions_remaining = 1;
+        state.meta.hero_points = 2;
+        state.base.woos = 12;
+        state.base.name = String::from("Woodsy");
+        let meta = state.meta.clone();
+        let state = state.map_base(|base| Creature::new(&base.name, base.woos, 15));
+        assert_eq!(state.meta, meta);
+        assert_eq!((state.base.name.as_str(), state.base.hp, state.base.ac), ("Woodsy", 12, 15));
+    }
+
+    #[test]
+    fn strict_engines_keep_unfinished_chains() {
+        let unresolved = || {
+            Action::new(Arc::new(|_, _| {
+                ActionResponse::Valid(vec![magic_update(1, Resolved::Unresolved)])
+            }))
+        };
+        for strict in [true, false] {
+            let mut engine = Engine::new(test_state());
+            engine.strict = strict;
+            engine.act(unresolved());
+            engine.step();
+            engine.act(unresolved());
+            match strict {
+                true => {
+                    assert_eq!(engine.rejected, Some(InvalidAction::ChainInProgress));
+                    assert_eq!((engine.updates.len(), engine.action.is_some()), (1, false));
+                }
+                false => {
+                    assert_eq!(engine.rejected, None);
+                    assert_eq!((engine.updates.len(), engine.action.is_some()), (0, true));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cancelling_undoes_what_the_activity_committed() {
+        let mut engine = Engine::new(test_state());
+        let prompt = UserInput {
+            label: String::from("Which way?"),
+            kind: InputKind::Number { min: 1, max: 2 },
+        };
+        // The second update's rule asks for a choice once the first has committed
+        engine.rules.push(Arc::new(move |_, update| match update.id {
+            2 => {
+                let prompt = prompt.clone();
+                RuleResponse::Inject(Action::new(Arc::new(move |_, _| {
+                    ActionResponse::RequestInput(prompt.clone())
+                })))
+            }
+            _ => RuleResponse::Skip,
+        }));
+        engine.act(
+            Action::new(Arc::new(|_, _| {
+                ActionResponse::Valid(vec![
+                    magic_update(1, Resolved::Resolved),
+                    magic_update(2, Resolved::Resolved),
+                ])
+            }))
+            .with_cost(1),
+        );
+        for _ in 0..4 {
+            engine.step();
+        }
+        assert!(engine.pending_input().is_some());
+        assert_eq!(engine.state.base.magics, 1);
+        assert_eq!(engine.state.meta.actions_remaining, 2);
+
+        assert!(engine.cancel_action());
+        assert!(engine.is_idle());
+        assert_eq!(engine.state.base.magics, 0);
+        assert_eq!(engine.state.meta.actions_remaining, 3);
+        assert!(!engine.undo());
+        assert!(!engine.cancel_action());
+    }
+
+    #[test]
+    fn finishing_the_chain_leaves_the_engine_idle() {
+        let mut engine = Engine::new(test_state());
+        engine.updates.push(magic_update(1, Resolved::Resolved));
+        engine.step();
+        assert!(engine.is_idle());
+        assert_eq!((engine.update, engine.committed), (0, 0));
+        engine.step(); // Stepping an idle engine does nothing
+        assert_eq!(engine.state.base.magics, 1);
+    }
+
+    #[test]
+    fn unresolved_chain_waits_past_its_end() {
+        let mut engine = Engine::new(test_state());
+        engine.updates.push(magic_update(1, Resolved::Unresolved));
+        engine.step();
+        assert!(!engine.is_idle());
+        assert_eq!(engine.update, engine.updates.len());
+        engine.step();
+        assert_eq!(engine.update, 1);
+        assert_eq!(engine.state.base.magics, 0);
+    }
+
+    #[test]
+    fn builder_matches_the_hand_built_update() {
+        let mut engine = Engine::new(test_state());
+        let hand_built = magic_update(engine.update + 1, Resolved::Resolved);
+        let built = UpdateBuilder::new()
+            .filter(Arc::new(|mut state: State<TestBase>| {
+                state.base.magics += 1;
+                state
+            }))
+            .resolved(Resolved::Resolved)
+            .build(engine.next_id());
+        assert_eq!(built.id, hand_built.id);
+        assert_eq!(built.target, hand_built.target);
+        assert_eq!(built.tags, hand_built.tags);
+        assert_eq!(built.resolved, hand_built.resolved);
+        assert_eq!(engine.next_id(), 2);
+
+        engine.updates.push(built);
+        engine.step();
+        assert_eq!(engine.state.base.magics, 1);
+    }
+
+    #[test]
+    fn builder_defaults_to_unresolved() {
+        let update = UpdateBuilder::new()
+            .filter(Arc::new(|state: State<TestBase>| state))
+            .tag(Tag::Flatfooted(None))
+            .build(1);
+        assert_eq!(update.resolved, Resolved::Unresolved);
+        assert!(update.tags.contains(&Tag::Flatfooted(None)));
+    }
+
+    #[test]
+    fn removing_the_current_update_advances_the_cursor() {
+        let mut engine = Engine::new(test_state());
+        for id in 1..=3 {
+            engine.updates.push(magic_update(id, Resolved::Unresolved));
+        }
+        assert_eq!(engine.remove_update(1).map(|u| u.id), Some(1));
+        assert_eq!(engine.update, 0);
+        assert_eq!(engine.updates[engine.update].id, 2);
+    }
+
+    #[test]
+    fn only_queued_updates_can_be_moved_or_removed() {
+        let mut engine = Engine::new(test_state());
+        for id in 1..=3 {
+            engine.updates.push(magic_update(id, Resolved::Unresolved));
+        }
+        engine.step(); // Update 1 has been processed
+        assert!(engine.remove_update(1).is_none());
+        assert!(!engine.reorder_update(0, 2));
+
+        assert!(engine.reorder_update(2, 1));
+        let ids: Vec<usize> = engine.updates.iter().map(|u| u.id).collect();
+        assert_eq!(ids, vec![1, 3, 2]);
+        assert_eq!(engine.updates[engine.update].id, 3);
+    }
+
+    #[test]
+    fn injected_updates_follow_the_current_update() {
+        let mut engine = Engine::new(test_state());
+        engine.rules.push(Arc::new(|_, update| {
+            if update.id == 1 {
+                RuleResponse::Inject(Action::new(Arc::new(|_, _| {
+                    ActionResponse::Valid(vec![magic_update(10, Resolved::Unresolved)])
+                })))
+            } else {
+                RuleResponse::Skip
+            }
+        }));
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Flatfooted(None))));
+        engine.updates.push(magic_update(1, Resolved::Unresolved));
+        engine.updates.push(magic_update(2, Resolved::Unresolved));
+        for _ in 0..5 {
+            engine.step();
+        }
+        let ids: Vec<usize> = engine.updates.iter().map(|u| u.id).collect();
+        assert_eq!(ids, vec![1, 10, 2]);
+        // The injecting rule ran once; the rule after it still saw every update
+        assert!(engine.updates.iter().all(|u| u.tags.contains(&Tag::Flatfooted(None))));
+    }
+
+    #[test]
+    fn removing_a_paused_update_runs_every_rule_on_the_next() {
+        let mut engine = Engine::new(test_state());
+        engine.rules.push(Arc::new(|_, update| {
+            if update.id == 1 {
+                RuleResponse::Inject(Action::new(Arc::new(|_, _| {
+                    ActionResponse::Valid(vec![magic_update(10, Resolved::Unresolved)])
+                })))
+            } else {
+                RuleResponse::Attach(Tag::Clumsy(1))
+            }
+        }));
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Flatfooted(None))));
+        engine.updates.push(magic_update(1, Resolved::Unresolved));
+        engine.step(); // Update 1 pauses after the injecting rule
+        engine.step(); // The injected update is queued behind it
+        assert_eq!(engine.rule, 1);
+        assert_eq!(engine.remove_update(1).map(|u| u.id), Some(1));
+        assert_eq!(engine.rule, 0);
+        engine.step();
+        let tags = &engine.updates[0].tags;
+        assert!(tags.contains(&Tag::Clumsy(1)) && tags.contains(&Tag::Flatfooted(None)));
+    }
+
+    #[test]
+    fn reordering_a_paused_update_away_runs_every_rule_on_the_next() {
+        let mut engine = Engine::new(test_state());
+        engine.rules.push(Arc::new(|_, update| {
+            if update.id == 1 {
+                RuleResponse::Inject(Action::new(Arc::new(|_, _| {
+                    ActionResponse::Valid(vec![magic_update(10, Resolved::Unresolved)])
+                })))
+            } else {
+                RuleResponse::Attach(Tag::Clumsy(1))
+            }
+        }));
+        engine.updates.push(magic_update(1, Resolved::Unresolved));
+        engine.step();
+        engine.step();
+        assert!(engine.reorder_update(1, 0));
+        assert_eq!(engine.rule, 0);
+        engine.step();
+        assert!(engine.updates[0].tags.contains(&Tag::Clumsy(1)));
+    }
+
+    #[test]
+    fn higher_priority_interrupt_wins() {
+        // A chain-altering rule for update 1 that replaces the chain with update `id`
+        fn on_first(
+            id: usize,
+            response: fn(Action<TestBase>) -> RuleResponse<TestBase>,
+        ) -> Rule<TestBase> {
+            Arc::new(move |_, update| {
+                if update.id != 1 {
+                    return RuleResponse::Skip;
+                }
+                response(Action::new(Arc::new(move |_, _| {
+                    ActionResponse::Valid(vec![magic_update(id, Resolved::Unresolved)])
+                })))
+            })
+        }
+        let run = |priority: i32| {
+            let mut engine = Engine::new(test_state());
+            engine.add_rule_with_priority(on_first(20, RuleResponse::Divert), 0);
+            engine.add_rule_with_priority(on_first(30, RuleResponse::Revert), priority);
+            engine.add_rule_with_priority(on_first(40, RuleResponse::Divert), 1);
+            engine.updates.push(magic_update(1, Resolved::Unresolved));
+            engine.updates.push(magic_update(2, Resolved::Unresolved));
+            engine.step();
+            engine.step();
+            engine.updates.iter().map(|u| u.id).collect::<Vec<_>>()
+        };
+        assert_eq!(run(5), vec![30]);
+        assert_eq!(run(1), vec![30]); // Tied, so the Revert wins
+        // Outranked, the Revert loses to the later Divert
+        assert_eq!(run(-1), vec![40]);
+    }
+
+    #[test]
+    fn halting_rule_stops_later_rules() {
+        let mut engine = Engine::new(test_state());
+        engine.tracing_enabled = true;
+        engine.rules.push(Arc::new(|_, update| {
+            if update.id == 1 {
+                RuleResponse::Halt
+            } else {
+                RuleResponse::Skip
+            }
+        }));
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Flatfooted(None))));
+        engine.updates.push(magic_update(1, Resolved::Unresolved));
+        engine.updates.push(magic_update(2, Resolved::Unresolved));
+        engine.step();
+        engine.step();
+        assert!(!engine.updates[0].tags.contains(&Tag::Flatfooted(None)));
+        assert!(engine.updates[1].tags.contains(&Tag::Flatfooted(None)));
+        assert_eq!(engine.trace()[0].response, ResponseKind::Halt);
+        assert_eq!(engine.trace().len(), 3);
+    }
+
+    #[test]
+    fn resolving_at_an_update_stops_the_chain_there() {
+        let mut engine = Engine::new(test_state());
+        for id in 1..=3 {
+            engine.updates.push(magic_update(id, Resolved::Unresolved));
+        }
+        engine.step();
+        assert!(engine.resolve_at(7).is_none());
+        let state = engine.resolve_at(2).unwrap();
+        assert_eq!(state.base.magics, 2);
+        assert_eq!(engine.state.base.magics, 2);
+        assert!(engine.is_idle());
+        assert!(engine.resolve_at(1).is_none());
+    }
+
+    #[test]
+    fn merged_chains_take_fresh_ids_and_their_place() {
+        // Each update records its order of application in `name`
+        fn named(id: usize, name: &'static str, priority: i32) -> Update<TestBase> {
+            UpdateBuilder::new()
+                .filter(Arc::new(move |mut state: State<TestBase>| {
+                    state.base.name.push_str(name);
+                    state
+                }))
+                .tag(Tag::Frightened(priority as u8))
+                .resolved(Resolved::Resolved)
+                .build(id)
+        }
+        fn run(policy: MergePolicy<TestBase>) -> (String, Vec<usize>) {
+            let mut engine = Engine::new(test_state());
+            engine.ids = 3;
+            engine.updates = vec![named(1, "a", 1), named(2, "b", 2)];
+            engine.step();
+            engine.merge_chain(vec![named(1, "x", 3), named(2, "y", 1)], policy);
+            let ids = engine.updates.iter().map(|u| u.id).collect();
+            while !engine.is_idle() {
+                engine.step();
+            }
+            (engine.state.base.name, ids)
+        }
+
+        assert_eq!(run(MergePolicy::Append), (String::from("abxy"), vec![1, 2, 3, 4]));
+        assert_eq!(run(MergePolicy::Prepend), (String::from("axyb"), vec![1, 3, 4, 2]));
+        let frightened = |update: &Update<TestBase>| {
+            update
+                .tags
+                .iter()
+                .find_map(|tag| match tag {
+                    Tag::Frightened(value) => Some(*value as i32),
+                    _ => None,
+                })
+                .unwrap_or(0)
+        };
+        assert_eq!(
+            run(MergePolicy::ByPriority(frightened)),
+            (String::from("axby"), vec![1, 3, 2, 4])
+        );
+    }
+
+    #[test]
+    fn shared_rules_apply_to_each_engine() {
+        let mut first = Engine::new(test_state());
+        first.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Flatfooted(None))));
+        let mut second = Engine::new(test_state());
+        second.state.base.magics = 5;
+        first.share_rules_with(&mut second);
+        first.share_rules_with(&mut second);
+        assert_eq!(second.rules.len(), 1);
+        assert!(Arc::ptr_eq(&first.rules[0], &second.rules[0]));
+
+        for engine in [&mut first, &mut second] {
+            engine.updates.push(magic_update(1, Resolved::Resolved));
+            engine.rules.push(Arc::new(|_, update| {
+                assert!(update.tags.contains(&Tag::Flatfooted(None)));
+                RuleResponse::Skip
+            }));
+            engine.step();
+        }
+        assert_eq!(first.state.base.magics, 1);
+        assert_eq!(second.state.base.magics, 6);
+    }
+
+    #[test]
+    fn forks_are_independent() {
+        let mut engine = Engine::new(test_state());
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Flatfooted(None))));
+        engine.updates.push(magic_update(1, Resolved::Unresolved));
+        engine.updates.push(magic_update(2, Resolved::Resolved));
+        engine.step();
+
+        let mut left = engine.fork(0);
+        let mut right = engine.fork(1);
+        left.step();
+        right.act(Action::new(Arc::new(|_, context| {
+            let woo = UpdateBuilder::new()
+                .filter(Arc::new(|mut state: State<TestBase>| {
+                    state.base.woos += 1;
+                    state
+                }))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![woo])
+        })));
+        right.step();
+        right.step();
+
+        assert_eq!((left.state.base.magics, left.state.base.woos), (2, 0));
+        assert_eq!((right.state.base.magics, right.state.base.woos), (0, 1));
+        // The rule is shared, but ran on each fork's own updates
+        assert!(left.updates.is_empty() && right.updates.is_empty());
+        assert_eq!(engine.state.base.magics, 0);
         assert_eq!(engine.update, 1);
+        assert!(!engine.updates[1].tags.contains(&Tag::Flatfooted(None)));
+    }
+
+    #[test]
+    fn subscribers_see_each_resolved_state_in_order() {
+        use std::sync::{Arc, Mutex};
+
+        let mut engine = Engine::new(test_state());
+        let seen = Arc::new(Mutex::new(vec![]));
+        for name in ["first", "second"] {
+            let seen = seen.clone();
+            engine.subscribe(Box::new(move |state| {
+                seen.lock().unwrap().push((name, state.base.magics));
+            }));
+        }
+        engine.updates.push(magic_update(1, Resolved::Unresolved));
+        engine.updates.push(magic_update(2, Resolved::Resolved));
+        engine.step();
+        assert!(seen.lock().unwrap().is_empty()); // Nothing resolved yet
+        engine.step();
+        assert_eq!(*seen.lock().unwrap(), vec![("first", 2), ("second", 2)]);
+    }
+
+    #[test]
+    fn a_chosen_cost_is_checked_and_spent_once_answered() {
+        let cast = || {
+            let prompt = UserInput {
+                label: String::from("Reach Spell?"),
+                kind: InputKind::Number { min: 0, max: 1 },
+            };
+            let extra = |answers: &[Input]| match answers {
+                [Input::Number(1)] => 3,
+                _ => 2,
+            };
+            Action::new(Arc::new(move |_, context| match context.input {
+                None => ActionResponse::RequestInput(prompt.clone()),
+                Some(_) => ActionResponse::Valid(vec![magic_update(1, Resolved::Resolved)]),
+            }))
+            .with_variable_cost(2, Arc::new(extra))
+        };
+        let run = |remaining: u8, reach: i32| {
+            let mut engine = Engine::new(test_state());
+            engine.state.meta.actions_remaining = remaining;
+            engine.act(cast());
+            engine.step();
+            engine.provide_input(Input::Number(reach));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            (engine.rejected.clone(), engine.state.meta.actions_remaining)
+        };
+        // Too few for even the cheaper cast, so it never gets as far as asking
+        assert_eq!(run(1, 0), (Some(InvalidAction::NotEnoughActions), 1));
+        assert_eq!(run(2, 1), (Some(InvalidAction::NotEnoughActions), 2));
+        assert_eq!(run(2, 0), (None, 0));
+        assert_eq!(run(3, 1), (None, 0));
+    }
+
+    #[test]
+    fn generators_can_ask_again_before_finishing() {
+        let mut engine = Engine::new(test_state());
+        let prompt = |label: &str| UserInput {
+            label: String::from(label),
+            kind: InputKind::Number { min: 1, max: 3 },
+        };
+        let spell = Action::new(Arc::new(move |_, context| match context.answers {
+            [] => ActionResponse::Partial(
+                vec![magic_update(context.next_id(), Resolved::Unresolved)],
+                prompt("Targets"),
+            ),
+            [_] => ActionResponse::RequestInput(prompt("Saves")),
+            [Input::Number(targets), Input::Number(saves)] => {
+                let (targets, saves) = (*targets, *saves);
+                let update = UpdateBuilder::new()
+                    .filter(Arc::new(move |mut state: State<TestBase>| {
+                        state.base.woos = targets * 10 + saves;
+                        state
+                    }))
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![update])
+            }
+            _ => ActionResponse::Invalid(InvalidAction::BadPredicate),
+        }));
+        engine.act(spell);
+        engine.step();
+        assert_eq!(engine.pending_input().map(|p| p.label.as_str()), Some("Targets"));
+        assert!(engine.provide_input(Input::Number(2)));
+        engine.step();
+        assert_eq!(engine.pending_input().map(|p| p.label.as_str()), Some("Saves"));
+        assert!(engine.updates.is_empty()); // The first stage's update waits for the rest
+        assert!(engine.provide_input(Input::Number(1)));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!((engine.state.base.magics, engine.state.base.woos), (1, 21));
+    }
+
+    #[test]
+    fn chains_ending_on_partial_commit_once_run_out() {
+        let mut engine = Engine::new(test_state());
+        engine.updates.push(magic_update(1, Resolved::Partial));
+        for _ in 0..3 {
+            engine.step();
+        }
+        assert!(engine.is_idle());
+        assert_eq!(engine.state.base.magics, 1);
+    }
+
+    #[test]
+    fn long_chains_apply_each_update_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Each `every`th update resolves the stretch before it
+        let applied_by = |every: usize| {
+            let applied = Arc::new(AtomicUsize::new(0));
+            let mut engine = Engine::new(test_state());
+            for id in 0..1000 {
+                let applied = applied.clone();
+                let mut update = magic_update(id, Resolved::Unresolved);
+                update.filter = Arc::new(move |state| {
+                    applied.fetch_add(1, Ordering::Relaxed);
+                    state
+                });
+                if (id + 1) % every == 0 {
+                    update.resolved = Resolved::Resolved;
+                }
+                engine.updates.push(update);
+            }
+            while !engine.is_idle() {
+                engine.step();
+            }
+            assert_eq!(engine.log().len(), 1000);
+            applied.load(Ordering::Relaxed)
+        };
+        assert_eq!(applied_by(10), 1000);
+        // Even a stretch as long as the chain is folded in once, not re-resolved per update
+        assert_eq!(applied_by(1000), 1000);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn resolving_emits_a_span_with_the_update_id() {
+        use std::sync::Mutex;
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata};
+
+        // Keeps the name and update id of every span opened
+        #[derive(Default)]
+        struct Spans(Mutex<Vec<(&'static str, Option<u64>)>>);
+
+        struct UpdateId(Option<u64>);
+
+        impl Visit for UpdateId {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                if field.name() == "update_id" {
+                    self.0 = Some(value);
+                }
+            }
+
+            fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+        }
+
+        impl tracing::Subscriber for &'static Spans {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut visitor = UpdateId(None);
+                span.record(&mut visitor);
+                let mut spans = self.0.lock().unwrap();
+                spans.push((span.metadata().name(), visitor.0));
+                Id::from_u64(spans.len() as u64)
+            }
+
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event<'_>) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let spans: &'static Spans = Box::leak(Box::default());
+        let mut engine = Engine::new(test_state());
+        engine.updates.push(magic_update(7, Resolved::Resolved));
+        tracing::subscriber::with_default(spans, || engine.step());
         assert_eq!(engine.state.base.magics, 1);
+        assert!(spans.0.lock().unwrap().contains(&("resolve", Some(7))));
     }
-}
\ No newline at end of file
+}
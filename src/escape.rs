@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use crate::check::Degree;
+use crate::combat::WeaponGroup;
+use crate::effective::StatKind;
+use crate::engine::{Action, ActionResponse, InvalidAction, Resolved, State, Tag, UpdateBuilder};
+use crate::field::Ability;
+use crate::stats::{Skill, Statistic};
+use crate::world::{clear_condition, Creature, CreatureId, World};
+
+impl Creature {
+    // The best modifier Escape can use: Athletics, Acrobatics or an unarmed attack
+    pub(crate) fn escape_modifier(&self) -> (Statistic, i32) {
+        let unarmed = Statistic::Weapon(WeaponGroup::Brawling);
+        [
+            (Statistic::Skill(Skill::Athletics), self.skill_modifier(Skill::Athletics)),
+            (Statistic::Skill(Skill::Acrobatics), self.skill_modifier(Skill::Acrobatics)),
+            (unarmed, self.modifier(Ability::Strength) + self.proficiency_bonus(unarmed)),
+        ]
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, modifier)| *modifier)
+        .unwrap_or((unarmed, 0))
+    }
+}
+
+impl Action<World> {
+    /**
+     * Escape: one roll with the actor's best escape modifier against the grabber's
+     * Athletics DC. Success frees it from being grabbed. Only a grabbed creature can try.
+     */
+    pub(crate) fn escape(actor: CreatureId, grabber: CreatureId) -> Self {
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            let (Some(escaping), Some(grabbing)) =
+                (state.base.creature(actor), state.base.creature(grabber))
+            else {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            };
+            if !escaping.conditions.contains(&Tag::Grabbed) {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            }
+            let dc = 10 + grabbing.skill_modifier(Skill::Athletics);
+            let (statistic, modifier) = escaping.escape_modifier();
+            let (custom, stat) = (&state.base.custom_conditions, StatKind::Check(statistic));
+            let degree = context.with_conditions(escaping, custom, stat, |context| {
+                context.roll_check(modifier, dc)
+            });
+            let filter = match degree {
+                Degree::Success | Degree::CriticalSuccess => clear_condition(actor, Tag::Grabbed),
+                _ => Arc::new(|state| state),
+            };
+            let update = UpdateBuilder::new()
+                .filter(filter)
+                .target(actor)
+                .tag(Tag::Degree(degree))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+        .with_cost(1)
+        .named("Escape")
+        .by(actor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::FixedDice;
+    use crate::engine::Engine;
+    use crate::stats::Proficiency;
+    use crate::world::WorldBuilder;
+
+    #[test]
+    fn escaping_uses_the_best_modifier() {
+        let mut rogue = Creature::new("Rogue", 18, 18);
+        rogue.abilities[Ability::Dexterity as usize] = 16;
+        rogue.proficiencies.insert(Statistic::Skill(Skill::Acrobatics), Proficiency::Expert);
+        rogue.proficiencies.insert(Statistic::Weapon(WeaponGroup::Brawling), Proficiency::Trained);
+        let rogue = rogue.with_condition(Tag::Grabbed);
+        // Expert Acrobatics, +5 and +3 from Dexterity, beats a trained fist's +3
+        assert_eq!(rogue.escape_modifier(), (Statistic::Skill(Skill::Acrobatics), 8));
+
+        let mut ogre = Creature::new("Ogre", 50, 17);
+        ogre.abilities[Ability::Strength as usize] = 18;
+        ogre.proficiencies.insert(Statistic::Skill(Skill::Athletics), Proficiency::Trained);
+        let world = WorldBuilder::new().add_creature(0, rogue).add_creature(1, ogre).build();
+        let mut engine = Engine::new_encounter(world, vec![]);
+        // DC 17; an 8 falls short by one and a 9 just makes it
+        engine.dice = Box::new(FixedDice::new(vec![8, 9]));
+        for grabbed in [true, false] {
+            engine.act(Action::escape(0, 1));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            let conditions = &engine.state.base.creature(0).unwrap().conditions;
+            assert_eq!(conditions.contains(&Tag::Grabbed), grabbed);
+        }
+    }
+}
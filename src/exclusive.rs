@@ -0,0 +1,62 @@
+use crate::engine::{Base, Engine, Tag};
+use crate::tags::TagSet;
+
+impl<T: Base> Engine<T> {
+    /**
+     * Attaching a tag of `superseding`'s kind takes away any of `superseded`'s kind,
+     * whatever their values, like Unconscious putting an end to Frightened.
+     */
+    pub(crate) fn supersede(&mut self, superseding: Tag, superseded: Tag) {
+        self.exclusive.push((superseding, superseded));
+    }
+
+    // Neither tag can stay alongside the other; whichever is attached last wins
+    pub(crate) fn make_exclusive(&mut self, first: Tag, second: Tag) {
+        self.supersede(first.clone(), second.clone());
+        self.supersede(second, first);
+    }
+}
+
+// Takes away whatever the tag being attached supersedes
+pub(crate) fn exclude(exclusive: &[(Tag, Tag)], attached: &Tag, tags: &mut TagSet) {
+    let pairs = exclusive.iter().filter(|(superseding, _)| superseding.same_kind(attached));
+    for (_, superseded) in pairs {
+        tags.retain(|tag| !tag.same_kind(superseded));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{Resolved, RuleResponse, TestBase, Update};
+    use crate::metadata::Metadata;
+
+    #[test]
+    fn attaching_one_removes_the_other() {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.make_exclusive(Tag::Unconscious, Tag::Frightened(0));
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Unconscious)));
+        engine.rules.push(Arc::new(|_, update| {
+            assert_eq!(update.tags, TagSet::from([Tag::Unconscious, Tag::Manipulate]));
+            RuleResponse::Attach(Tag::Frightened(2))
+        }));
+        engine.rules.push(Arc::new(|_, update| {
+            assert_eq!(update.tags, TagSet::from([Tag::Frightened(2), Tag::Manipulate]));
+            RuleResponse::Skip
+        }));
+        engine.updates.push(Update {
+            filter: Arc::new(|state| state),
+            id: 1,
+            target: 0,
+            tags: TagSet::from([Tag::Frightened(1), Tag::Manipulate]),
+            applies: TagSet::new(),
+            resolved: Resolved::Unresolved,
+            meta: Metadata::new(),
+            outcome: Default::default(),
+        });
+        engine.step();
+        assert_eq!(engine.update, 1);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::check::Degree;
+use crate::combat::Weapon;
+use crate::crit::CritRules;
+use crate::engine::{Action, State};
+use crate::field::Ability;
+use crate::modifier::combine;
+use crate::world::{CreatureId, World};
+
+// The average of rolling `dice` dice with `die` sides each
+fn average(dice: u8, die: u8) -> f64 {
+    dice as f64 * (die as f64 + 1.0) / 2.0
+}
+
+impl Action<World> {
+    /**
+     * The average damage of a Strike with the weapon, without rolling: each natural
+     * roll's degree weighted by its 1 in 20 chance, critical hits doubled and with
+     * a deadly weapon's extra die. 0 if either creature is missing.
+     */
+    pub(crate) fn expected_damage(
+        state: &State<World>,
+        attacker: CreatureId,
+        target: CreatureId,
+        weapon: &Weapon,
+    ) -> f64 {
+        let (Some(attacking), Some(defending)) =
+            (state.base.creature(attacker), state.base.creature(target))
+        else {
+            return 0.0;
+        };
+        let stat = weapon.attack_stat();
+        let conditions = attacking.condition_modifiers(stat, &state.base.custom_conditions);
+        let modifier = attacking.attack_modifier(weapon) + combine(&conditions);
+        let ac = defending.ac_against_attack(attacker, weapon.range.is_none());
+        let hit = average(weapon.dice, weapon.die) + attacking.modifier(Ability::Strength) as f64;
+        let critical = 2.0 * hit + weapon.deadly.map_or(0.0, |die| average(1, die));
+        let rules = CritRules::default();
+        let total: f64 = (1..=20)
+            .map(|natural| match rules.degree(natural, natural as i32 + modifier, ac) {
+                Degree::CriticalSuccess => critical,
+                Degree::Success => hit,
+                _ => 0.0,
+            })
+            .sum();
+        total / 20.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::WeaponGroup;
+    use crate::engine::Meta;
+    use crate::stats::{Proficiency, Statistic};
+    use crate::world::{Creature, WorldBuilder};
+
+    #[test]
+    fn expected_damage_weighs_each_degree_by_its_chance() {
+        let mut fighter = Creature::new("Fighter", 40, 18);
+        fighter.level = 4;
+        fighter.abilities[Ability::Strength as usize] = 18;
+        let sword = Statistic::Weapon(WeaponGroup::Sword);
+        fighter.proficiencies.insert(sword, Proficiency::Trained);
+        let world = WorldBuilder::new()
+            .add_creature(0, fighter)
+            .add_creature(1, Creature::new("Ogre", 50, 18))
+            .build();
+        let state = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+        let longsword = Weapon::new("Longsword", WeaponGroup::Sword, 1, 8);
+        assert_eq!(state.base.creature(0).unwrap().attack_modifier(&longsword), 10);
+
+        // +10 against 18 hits on 8 to 17 for 8.5, and crits on 18 to 20 for 17
+        let expected = Action::expected_damage(&state, 0, 1, &longsword);
+        assert!((expected - (10.0 * 8.5 + 3.0 * 17.0) / 20.0).abs() < 1e-9);
+        let deadly = Action::expected_damage(&state, 0, 1, &longsword.with_deadly(10));
+        assert!((deadly - expected - 3.0 * 5.5 / 20.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::dice::Dice;
+use crate::field::Ability;
+use crate::world::Creature;
+
+// One term of a dice expression, before its sign
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Term {
+    Dice { count: u8, sides: u8 },
+    Flat(i32),
+    Ability(Ability), // The modifier of whoever the expression is rolled for
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ExprError {
+    Empty,
+    BadTerm(String), // The term as it was written
+}
+
+/** DiceExpr
+ *  Damage as content writes it, like "2d6+4" or "1d8+STR":
+ *  dice, flat numbers and ability modifiers, each added or subtracted.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DiceExpr {
+    pub(crate) terms: Vec<(i32, Term)>, // Each with its sign, 1 or -1
+}
+
+fn ability(name: &str) -> Option<Ability> {
+    let names = ["STR", "DEX", "CON", "INT", "WIS", "CHA"];
+    let at = names.iter().position(|n| n.eq_ignore_ascii_case(name))?;
+    Some(Ability::ALL[at])
+}
+
+fn term(written: &str) -> Result<Term, ExprError> {
+    let bad = || ExprError::BadTerm(String::from(written));
+    if let Some(ability) = ability(written) {
+        return Ok(Term::Ability(ability));
+    }
+    match written.split_once(['d', 'D']) {
+        Some((count, sides)) => {
+            // "d6" is one die
+            let count = if count.is_empty() { Ok(1) } else { count.parse() };
+            match (count, sides.parse()) {
+                (Ok(count), Ok(sides)) if sides > 0 => Ok(Term::Dice { count, sides }),
+                _ => Err(bad()),
+            }
+        }
+        None => written.parse().map(Term::Flat).map_err(|_| bad()),
+    }
+}
+
+impl DiceExpr {
+    pub(crate) fn parse(written: &str) -> Result<DiceExpr, ExprError> {
+        let written: String = written.chars().filter(|c| !c.is_whitespace()).collect();
+        if written.is_empty() {
+            return Err(ExprError::Empty);
+        }
+        let mut terms = vec![];
+        let mut sign = 1;
+        let mut start = 0;
+        // A sign ends the term before it, though a leading one starts the first
+        for (at, c) in written.char_indices().filter(|(_, c)| matches!(c, '+' | '-')) {
+            if at > 0 {
+                terms.push((sign, term(&written[start..at])?));
+            }
+            sign = if c == '-' { -1 } else { 1 };
+            start = at + 1;
+        }
+        terms.push((sign, term(&written[start..])?));
+        Ok(DiceExpr { terms })
+    }
+
+    // Rolls every dice term in one batch, in the order they were written
+    pub(crate) fn evaluate(&self, dice: &mut dyn Dice, creature: &Creature) -> i32 {
+        let specs: Vec<(u8, u8)> = self
+            .terms
+            .iter()
+            .filter_map(|(_, term)| match term {
+                Term::Dice { count, sides } => Some((*count, *sides)),
+                _ => None,
+            })
+            .collect();
+        let mut rolled = dice.roll_many(&specs).into_iter();
+        self.terms
+            .iter()
+            .map(|(sign, term)| {
+                sign * match term {
+                    Term::Dice { .. } => rolled.next().unwrap_or(0) as i32,
+                    Term::Flat(value) => *value,
+                    Term::Ability(ability) => creature.modifier(*ability),
+                }
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::FixedDice;
+
+    #[test]
+    fn expressions_parse_and_roll() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.abilities[Ability::Strength as usize] = 16;
+        let mut dice = FixedDice::new(vec![3, 5, 6]);
+
+        let greatsword = DiceExpr::parse("2d6+4").unwrap();
+        assert_eq!(
+            greatsword.terms,
+            vec![(1, Term::Dice { count: 2, sides: 6 }), (1, Term::Flat(4))]
+        );
+        assert_eq!(greatsword.evaluate(&mut dice, &fighter), 12);
+
+        let longsword = DiceExpr::parse("1d8 + STR").unwrap();
+        assert_eq!(longsword.evaluate(&mut dice, &fighter), 9);
+        assert_eq!(DiceExpr::parse("d4-1").unwrap().evaluate(&mut dice, &fighter), 2);
+    }
+
+    #[test]
+    fn malformed_expressions_are_rejected() {
+        assert_eq!(DiceExpr::parse(" "), Err(ExprError::Empty));
+        assert_eq!(DiceExpr::parse("2d"), Err(ExprError::BadTerm(String::from("2d"))));
+        assert_eq!(DiceExpr::parse("1d6+"), Err(ExprError::BadTerm(String::new())));
+        assert_eq!(DiceExpr::parse("1d6+LUCK"), Err(ExprError::BadTerm(String::from("LUCK"))));
+    }
+}
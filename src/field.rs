@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+pub(crate) type UpdateId = usize;
+
+pub(crate) type Derivation<C, T> = Arc<dyn Fn(&C, UpdateId) -> T + Send + Sync>;
+
+#[derive(Clone)]
+pub(crate) enum Layer<T> {
+    Pass,                                     // Preserves the lower layer
+    New(T),                                   // Overwrites the lower layer
+    Mod(Arc<dyn Fn(T) -> T + Send + Sync>),   // Modifies the lower layer
+}
+
+#[derive(Clone)]
+pub(crate) struct Lense<T> {
+    pub(crate) update_id: UpdateId,
+    pub(crate) layer: Layer<T>,
+}
+
+#[derive(Clone)]
+pub(crate) enum FieldType<C, T> {
+    Constant(T),
+    Derivative(Derivation<C, T>), // Computed from the rest of the context C
+}
+
+/** Field
+ *  A value with a stack of lenses, each applied from the update that added it.
+ *  Derivative fields read other fields out of their context, so they follow
+ *  any lens applied to what they depend on.
+ */
+#[derive(Clone)]
+pub(crate) struct Field<C, T: Copy> {
+    pub(crate) value: FieldType<C, T>,
+    pub(crate) lenses: Vec<Lense<T>>,
+}
+
+impl<C, T: Copy> Field<C, T> {
+    pub(crate) fn new_const(t: T) -> Self {
+        Field {
+            value: FieldType::Constant(t),
+            lenses: Vec::new(),
+        }
+    }
+
+    pub(crate) fn new_dyn(f: Derivation<C, T>) -> Self {
+        Field {
+            value: FieldType::Derivative(f),
+            lenses: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, update_id: UpdateId, layer: Layer<T>) {
+        self.lenses.push(Lense { update_id, layer });
+    }
+
+    // Returns the value of the field, applying all lenses up to the given update_id
+    pub(crate) fn at(&self, update_id: UpdateId, context: &C) -> T {
+        let mut value = self.base(context, update_id);
+        for lense in self.lenses.iter().filter(|l| l.update_id <= update_id) {
+            match &lense.layer {
+                Layer::Pass => (),
+                Layer::New(t) => value = *t,
+                Layer::Mod(f) => value = f(value),
+            }
+        }
+        value
+    }
+
+    // Returns the value before any lens
+    pub(crate) fn base(&self, context: &C, update_id: UpdateId) -> T {
+        match &self.value {
+            FieldType::Constant(t) => *t,
+            FieldType::Derivative(f) => f(context, update_id),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Ability {
+    Strength,
+    Dexterity,
+    Constitution,
+    Intelligence,
+    Wisdom,
+    Charisma,
+}
+
+impl Ability {
+    pub(crate) const ALL: [Ability; 6] = [
+        Ability::Strength,
+        Ability::Dexterity,
+        Ability::Constitution,
+        Ability::Intelligence,
+        Ability::Wisdom,
+        Ability::Charisma,
+    ];
+}
+
+pub(crate) fn modifier(score: u8) -> i32 {
+    (score as i32 - 10).div_euclid(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifier_rounds_down() {
+        assert_eq!(modifier(18), 4);
+        assert_eq!(modifier(10), 0);
+        assert_eq!(modifier(7), -2);
+    }
+
+    #[test]
+    fn derived_modifiers_follow_score_lenses() {
+        let mut score: Field<(), u8> = Field::new_const(10);
+        score.push(1, Layer::Mod(Arc::new(|s| s + 2)));
+        score.push(2, Layer::New(16));
+        score.push(3, Layer::Pass);
+        let derived: Field<Field<(), u8>, i32> =
+            Field::new_dyn(Arc::new(|score: &Field<(), u8>, id| modifier(score.at(id, &()))));
+        assert_eq!(derived.at(0, &score), 0);
+        assert_eq!(derived.at(1, &score), 1);
+        assert_eq!(derived.at(3, &score), 3);
+    }
+}
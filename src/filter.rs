@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use crate::engine::{Filter, State};
+
+/**
+ * Saves the value read by `get` into `Meta` under `key`,
+ * so a later `restore` can put it back.
+ */
+pub(crate) fn capture<T: 'static>(
+    key: &str,
+    get: impl Fn(&T) -> i32 + Send + Sync + 'static,
+) -> Filter<T> {
+    let key = String::from(key);
+    Arc::new(move |mut state: State<T>| {
+        let value = get(&state.base);
+        state.meta.captures.insert(key.clone(), value);
+        state
+    })
+}
+
+/**
+ * Writes the value captured under `key` back with `set`.
+ * Restoring a key that was never captured leaves the state untouched.
+ */
+pub(crate) fn restore<T: 'static>(
+    key: &str,
+    set: impl Fn(&mut T, i32) + Send + Sync + 'static,
+) -> Filter<T> {
+    let key = String::from(key);
+    Arc::new(move |mut state: State<T>| {
+        if let Some(value) = state.meta.captures.remove(&key) {
+            set(&mut state.base, value);
+        }
+        state
+    })
+}
+
+/**
+ * Takes `amount` of the resource in `Meta`, as an action pays for itself on resolving.
+ * Guard the action with `predicate::has_resource` so there is always enough;
+ * the count never drops below zero either way.
+ */
+pub(crate) fn spend_resource<T: 'static>(key: &str, amount: u32) -> Filter<T> {
+    let key = String::from(key);
+    Arc::new(move |mut state: State<T>| {
+        if let Some(left) = state.meta.resources.get_mut(&key) {
+            *left = left.saturating_sub(amount);
+        }
+        state
+    })
+}
+
+// Gives back what `spend_resource` took, as when a critical failure refunds the cost
+pub(crate) fn refund_resource<T: 'static>(key: &str, amount: u32) -> Filter<T> {
+    let key = String::from(key);
+    Arc::new(move |mut state: State<T>| {
+        *state.meta.resources.entry(key.clone()).or_insert(0) += amount;
+        state
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Meta;
+    use crate::world::{Creature, World, WorldBuilder};
+
+    #[test]
+    fn capture_then_restore_returns_the_captured_value() {
+        let world = WorldBuilder::new().add_creature(0, Creature::new("Fighter", 20, 18)).build();
+        let state = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+
+        let mut state = capture("ac", |world: &World| world.creature(0).unwrap().ac)(state);
+        state.base.creature_mut(0).unwrap().ac -= 2;
+        assert_eq!(state.base.creature(0).unwrap().ac, 16);
+
+        let state = restore("ac", |world: &mut World, ac| {
+            world.creature_mut(0).unwrap().ac = ac;
+        })(state);
+        assert_eq!(state.base.creature(0).unwrap().ac, 18);
+        assert!(state.meta.captures.is_empty());
+    }
+}
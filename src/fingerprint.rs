@@ -0,0 +1,116 @@
+use std::hash::{Hash, Hasher};
+
+use crate::engine::{Base, Engine, State};
+use crate::world::World;
+
+/** Fingerprinter
+ *  FNV-1a, which unlike the standard library's hasher is specified,
+ *  so fingerprints can be compared between builds and machines.
+ */
+pub(crate) struct Fingerprinter(u64);
+
+impl Default for Fingerprinter {
+    fn default() -> Self {
+        Fingerprinter(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fingerprinter {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+}
+
+impl<T: Base> State<T> {
+    // A stable hash of the base, cheaper to compare between runs than the states themselves
+    pub(crate) fn fingerprint(&self) -> u64 {
+        self.base.fingerprint()
+    }
+}
+
+// Conditions are hashed in canonical order, so the order they were gained in doesn't count
+pub(crate) fn fingerprint_world(world: &World) -> u64 {
+    let mut hasher = Fingerprinter::default();
+    for (id, creature) in world.creatures.iter() {
+        let mut creature = creature.clone();
+        creature.conditions.sort();
+        id.hash(&mut hasher);
+        creature.hash(&mut hasher);
+    }
+    world.difficult_terrain.hash(&mut hasher);
+    world.greater_difficult_terrain.hash(&mut hasher);
+    world.grid.hash(&mut hasher);
+    world.auras.hash(&mut hasher);
+    world.initiative.hash(&mut hasher);
+    world.custom_conditions.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<T: Base> Engine<T> {
+    /**
+     * The first log entry whose fingerprint doesn't match the recorded run's,
+     * in the same order as `log`. A log that runs short or long diverges where it ends.
+     */
+    pub(crate) fn fingerprint_divergence(&self, recorded: &[u64]) -> Option<usize> {
+        let fingerprints: Vec<u64> = self.log.iter().map(|entry| entry.fingerprint).collect();
+        (0..recorded.len().max(fingerprints.len()))
+            .find(|&i| recorded.get(i) != fingerprints.get(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::engine::{Action, Meta, Tag};
+    use crate::world::{Creature, WorldBuilder};
+
+    fn state(hp: i32, conditions: Vec<Tag>) -> State<World> {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.hp = hp;
+        fighter.conditions = conditions;
+        State {
+            meta: Meta::default(),
+            base: WorldBuilder::new().add_creature(0, fighter).build(),
+            pre_chain: None,
+        }
+    }
+
+    #[test]
+    fn only_a_real_difference_changes_the_fingerprint() {
+        let (frightened, clumsy) = (Tag::Frightened(1), Tag::Clumsy(2));
+        let state_a = state(20, vec![frightened.clone(), clumsy.clone()]);
+        let state_b = state(20, vec![clumsy.clone(), frightened.clone()]);
+        assert_eq!(state_a.fingerprint(), state_b.fingerprint());
+        assert_ne!(state_a.fingerprint(), state(19, vec![frightened, clumsy]).fingerprint());
+    }
+
+    #[test]
+    fn replays_are_checked_entry_by_entry() {
+        let run = |ac: i32| {
+            let world = WorldBuilder::new()
+                .add_creature(0, Creature::new("Fighter", 20, 18))
+                .add_creature(1, Creature::new("Ogre", 300, ac))
+                .build();
+            let mut engine = Engine::new_encounter(world, vec![]);
+            for _ in 0..3 {
+                let club = Weapon::new("Club", WeaponGroup::Club, 2, 6);
+                engine.act(Action::strike(0, 1, club));
+                while !engine.is_idle() {
+                    engine.step();
+                }
+            }
+            engine
+        };
+        let recorded: Vec<u64> = run(10).log().iter().map(|entry| entry.fingerprint).collect();
+        assert_eq!(run(10).fingerprint_divergence(&recorded), None);
+        assert_eq!(run(11).fingerprint_divergence(&recorded), Some(0));
+        assert_eq!(run(10).fingerprint_divergence(&recorded[..2]), Some(2));
+    }
+}
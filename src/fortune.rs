@@ -0,0 +1,73 @@
+use crate::engine::Tag;
+use crate::world::Creature;
+
+// How many d20s a check rolls, and which one it keeps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Luck {
+    #[default]
+    Single,
+    KeepHigher, // Fortune
+    KeepLower,  // Misfortune
+}
+
+impl Luck {
+    // Fortune and misfortune on the same creature cancel out, leaving a single roll
+    pub(crate) fn of(creature: &Creature) -> Luck {
+        let has = |tag| creature.conditions.contains(&tag);
+        match (has(Tag::Fortune), has(Tag::Misfortune)) {
+            (true, false) => Luck::KeepHigher,
+            (false, true) => Luck::KeepLower,
+            _ => Luck::Single,
+        }
+    }
+
+    // Which of the two rolls counts
+    pub(crate) fn keep(self, first: u8, second: u8) -> u8 {
+        match self {
+            Luck::Single => first,
+            Luck::KeepHigher => first.max(second),
+            Luck::KeepLower => first.min(second),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::check::Degree;
+    use crate::dice::FixedDice;
+    use crate::engine::{Action, Engine, Resolved, State, UpdateBuilder};
+    use crate::stats::Skill;
+    use crate::world::{World, WorldBuilder};
+
+    fn tumble(engine: &mut Engine<World>, rolls: Vec<u8>) -> bool {
+        engine.dice = Box::new(FixedDice::exact(rolls));
+        engine.act(Action::skill_check(0, Skill::Acrobatics, 15, |degree, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(|state: State<World>| state))
+                .tag(Tag::Degree(degree))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            vec![update]
+        }));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        engine.dice.assert_consumed();
+        engine.log().last().unwrap().tags.contains(&Tag::Degree(Degree::Success))
+    }
+
+    #[test]
+    fn misfortune_keeps_the_lower_roll_unless_fortune_cancels_it() {
+        let rogue = Creature::new("Rogue", 18, 18).with_condition(Tag::Misfortune);
+        let world = WorldBuilder::new().add_creature(0, rogue).build();
+        let mut engine = Engine::for_base(world);
+        assert!(!tumble(&mut engine, vec![17, 6]));
+        assert!(!tumble(&mut engine, vec![6, 17]));
+
+        engine.state.base.creature_mut(0).unwrap().add_condition(Tag::Fortune);
+        assert!(tumble(&mut engine, vec![17]));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::engine::{Action, Meta};
+
+// How often an ability can be used before it must reset
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::enum_variant_names)]
+pub(crate) enum Frequency {
+    PerRound, // A round ends as the user's next turn starts
+    PerTurn,
+    PerDay(u32), // Uses between long rests
+}
+
+impl Frequency {
+    fn allowed(&self) -> u32 {
+        match self {
+            Frequency::PerRound | Frequency::PerTurn => 1,
+            Frequency::PerDay(uses) => *uses,
+        }
+    }
+}
+
+impl<T> Action<T> {
+    // Uses are counted by name, so every action built for the ability shares the limit
+    pub(crate) fn with_frequency(mut self, name: &str, frequency: Frequency) -> Self {
+        self.frequency = Some((String::from(name), frequency));
+        self
+    }
+}
+
+impl Meta {
+    pub(crate) fn exhausted(&self, name: &str, frequency: Frequency) -> bool {
+        self.uses.get(name).map_or(0, |(_, used)| *used) >= frequency.allowed()
+    }
+
+    pub(crate) fn record_use(&mut self, name: &str, frequency: Frequency) {
+        self.uses.entry(String::from(name)).or_insert((frequency, 0)).1 += 1;
+    }
+
+    // Per-turn and per-round uses come back as a turn starts, and attacks stop counting
+    pub(crate) fn reset_turn(&mut self) {
+        self.uses.retain(|_, (frequency, _)| matches!(frequency, Frequency::PerDay(_)));
+        self.attacks_this_turn.clear();
+    }
+
+    // Everything comes back after a long rest
+    pub(crate) fn long_rest(&mut self) {
+        self.uses.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{
+        ActionResponse, Engine, InvalidAction, Resolved, State, TestBase, UpdateBuilder,
+    };
+
+    fn engine() -> Engine<TestBase> {
+        Engine::for_base(TestBase::default())
+    }
+
+    fn magic(frequency: Frequency) -> Action<TestBase> {
+        Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(|mut state: State<TestBase>| {
+                    state.base.magics += 1;
+                    state
+                }))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+        .with_frequency("Magic", frequency)
+    }
+
+    fn run(engine: &mut Engine<TestBase>, action: Action<TestBase>) {
+        engine.act(action);
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    #[test]
+    fn once_per_turn_until_the_turn_resets() {
+        for frequency in [Frequency::PerTurn, Frequency::PerRound] {
+            let mut engine = engine();
+            run(&mut engine, magic(frequency));
+            assert_eq!(engine.rejected, None);
+            run(&mut engine, magic(frequency));
+            assert_eq!(engine.rejected, Some(InvalidAction::Exhausted));
+            assert_eq!(engine.state.base.magics, 1);
+
+            engine.state.meta.reset_turn();
+            run(&mut engine, magic(frequency));
+            assert_eq!(engine.rejected, None);
+            assert_eq!(engine.state.base.magics, 2);
+        }
+    }
+
+    #[test]
+    fn daily_uses_survive_the_turn() {
+        let mut engine = engine();
+        for _ in 0..3 {
+            run(&mut engine, magic(Frequency::PerDay(2)));
+            engine.state.meta.reset_turn();
+        }
+        assert_eq!(engine.state.base.magics, 2);
+        engine.state.meta.long_rest();
+        run(&mut engine, magic(Frequency::PerDay(2)));
+        assert_eq!(engine.state.base.magics, 3);
+    }
+}
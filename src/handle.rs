@@ -0,0 +1,90 @@
+use std::sync::{Arc, RwLock};
+
+use crate::engine::{Action, Base, Engine, State};
+
+/** EngineHandle
+ *  Shares one engine between threads.
+ *  Any number of readers can look at the state while a single writer
+ *  queues actions and steps the engine.
+ */
+pub(crate) struct EngineHandle<T: Base> {
+    engine: Arc<RwLock<Engine<T>>>,
+}
+
+impl<T: Base> Clone for EngineHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            engine: Arc::clone(&self.engine),
+        }
+    }
+}
+
+impl<T: Base> EngineHandle<T> {
+    pub(crate) fn new(engine: Engine<T>) -> Self {
+        Self {
+            engine: Arc::new(RwLock::new(engine)),
+        }
+    }
+
+    // A copy of the committed state, so the lock is only held while cloning
+    pub(crate) fn read_state(&self) -> State<T> {
+        self.engine.read().unwrap().state.clone()
+    }
+
+    pub(crate) fn apply_action(&self, action: Action<T>) {
+        self.engine.write().unwrap().act(action);
+    }
+
+    pub(crate) fn step(&self) {
+        self.engine.write().unwrap().step();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::engine::{ActionResponse, Resolved, Update};
+    use crate::metadata::Metadata;
+    use crate::world::{Creature, World, WorldBuilder};
+
+    #[test]
+    fn reads_during_a_pending_action_do_not_deadlock() {
+        let world = WorldBuilder::new().add_creature(0, Creature::new("Goblin", 8, 16)).build();
+        let handle = EngineHandle::new(Engine::for_base(world));
+        handle.apply_action(Action::new(Arc::new(|_, _| {
+            ActionResponse::Valid(vec![Update {
+                filter: Arc::new(|mut state: State<World>| {
+                    state.base.creature_mut(0).unwrap().hp -= 3;
+                    state
+                }),
+                id: 1,
+                target: 0,
+                tags: Default::default(),
+                applies: Default::default(),
+                resolved: Resolved::Resolved,
+                meta: Metadata::new(),
+                outcome: Default::default(),
+            }])
+        })));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = handle.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        let hp = handle.read_state().base.creature(0).unwrap().hp;
+                        assert!(hp == 8 || hp == 5);
+                    }
+                })
+            })
+            .collect();
+        handle.step();
+        handle.step();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        assert_eq!(handle.read_state().base.creature(0).unwrap().hp, 5);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::check::Degree;
+use crate::engine::{Action, ActionResponse, Base, Context, Engine, Input, Tag, Update};
+use crate::fortune::Luck;
+
+/** Generated
+ *  The action behind the most recent updates, kept so it can be
+ *  generated again for a reroll, along with where its updates sit.
+ */
+#[derive(Clone)]
+pub(crate) struct Generated<T> {
+    pub(crate) action: Action<T>,
+    pub(crate) answers: Vec<Input>, // Replayed, so a reroll doesn't prompt again
+    pub(crate) at: usize,
+    pub(crate) len: usize,
+}
+
+// The degree of the first check among the updates
+fn degree<T>(updates: &[Update<T>]) -> Option<Degree> {
+    updates.iter().flat_map(|u| u.tags.iter()).find_map(|tag| match tag {
+        Tag::Degree(degree) => Some(*degree),
+        _ => None,
+    })
+}
+
+impl<T: Base> Engine<T> {
+    /**
+     * Spends a hero point to roll the last check again, keeping the better degree.
+     * Only possible while none of the check's updates have been processed,
+     * and only once per check. Returns whether a point was spent.
+     */
+    pub(crate) fn use_hero_point_reroll(&mut self) -> bool {
+        if self.state.meta.hero_points == 0 {
+            return false;
+        }
+        let Some(generated) = self.generated.take() else {
+            return false;
+        };
+        let (at, len) = (generated.at, generated.len);
+        if self.update > at || at + len > self.updates.len() {
+            return false;
+        }
+        let Some(rolled) = degree(&self.updates[at..at + len]) else {
+            // Not a check, so there is nothing to reroll
+            self.generated = Some(generated);
+            return false;
+        };
+
+        let mut context = Context {
+            dice: self.dice.as_mut(),
+            input: generated.answers.last(),
+            answers: &generated.answers,
+            ids: &mut self.ids,
+            modifiers: vec![],
+            crit_rules: self.crit_rules,
+            luck: Luck::Single,
+            adjustments: vec![],
+            level: None,
+        };
+        let response = generated.action.apply(&self.state, &mut context);
+        self.state.meta.hero_points -= 1;
+        if let ActionResponse::Valid(updates) = response {
+            if degree(&updates).is_some_and(|rerolled| rerolled > rolled) {
+                self.index_updates(&updates);
+                let replaced: Vec<Update<T>> = self.updates.splice(at..at + len, updates).collect();
+                self.unindex_updates(&replaced);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::dice::FixedDice;
+    
+    use crate::world::{Creature, WorldBuilder};
+
+    #[test]
+    fn reroll_keeps_the_better_result() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 30, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        // A 2 misses; the reroll's 18 hits for 5
+        engine.dice = Box::new(FixedDice::new(vec![2, 18, 5]));
+        engine.act(Action::strike(0, 1, Weapon::new("Club", WeaponGroup::Club, 1, 6)));
+        engine.step();
+
+        assert!(engine.use_hero_point_reroll());
+        assert_eq!(engine.state.meta.hero_points, 0);
+        engine.state.meta.hero_points = 1;
+        assert!(!engine.use_hero_point_reroll()); // Once per check
+
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!(engine.state.base.creature(1).unwrap().hp, 25);
+        assert_eq!(engine.state.meta.actions_remaining, 2);
+    }
+}
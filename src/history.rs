@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use crate::engine::{Base, Engine, Filter, State, Update};
+use crate::log::{LogEntry, LogSource};
+use crate::tags::TagSet;
+use crate::world::{CreatureId, World};
+
+// What a log entry did to the state, kept so it can be done again
+#[derive(Clone)]
+pub(crate) enum Replay<T> {
+    Update(Update<T>),
+    Filter(Filter<T>), // A manual edit
+}
+
+impl<T: Base> Engine<T> {
+    /**
+     * Makes `state` current, keeping the old one to undo back to.
+     * Versions only go up, so one that was undone is never handed out again.
+     */
+    pub(crate) fn commit(&mut self, mut state: State<T>) {
+        self.versions += 1;
+        state.meta.version = self.versions;
+        let previous = std::mem::replace(&mut self.state, state);
+        self.history.push((previous, self.logged));
+        self.logged = self.log.len();
+        for subscriber in self.subscribers.iter() {
+            subscriber(&self.state);
+        }
+        if !self.diff_subscribers.is_empty() {
+            let (before, _) = self.history.last().expect("the old state was just pushed");
+            let diff = T::diff(before, &self.state);
+            if !diff.is_empty() {
+                for subscriber in self.diff_subscribers.iter() {
+                    subscriber(&diff);
+                }
+            }
+        }
+    }
+
+    /**
+     * Runs the filter on the whole state and commits the result straight away,
+     * with no action, predicates or rules involved. For tools like a GM override.
+     */
+    pub(crate) fn apply_filter(&mut self, filter: Filter<T>) {
+        let state = filter(self.state.clone());
+        self.log.push(LogEntry {
+            source: LogSource::Manual,
+            tags: TagSet::new(),
+            rng_offset: self.dice.position(),
+            skipped: None,
+            fingerprint: state.fingerprint(),
+        });
+        self.replays.push(Replay::Filter(filter));
+        self.commit(state);
+    }
+
+    /**
+     * Goes back to the state before the last commit.
+     * Only while idle, since a chain in progress relies on what it has committed.
+     * Returns whether there was anything to undo.
+     */
+    pub(crate) fn undo(&mut self) -> bool {
+        if !self.is_idle() {
+            return false;
+        }
+        match self.history.pop() {
+            Some((state, _)) => {
+                self.state = state;
+                // Later commits replay from here, so they must skip what was undone
+                self.logged = self.log.len();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /**
+     * The state as it was just after the log entry was applied, for scrubbing
+     * back through a fight. It is rebuilt from the latest earlier snapshot by
+     * replaying the entries since, so undone entries can still be looked at.
+     * Returns None past the end of the log or if every snapshot before it is undone.
+     */
+    pub(crate) fn state_at(&self, log_index: usize) -> Option<State<T>> {
+        if log_index >= self.log.len() {
+            return None;
+        }
+        let (snapshot, from) =
+            self.history.iter().rev().find(|(_, logged)| *logged <= log_index)?;
+        let mut state = snapshot.clone();
+        for (entry, replay) in self.log[*from..=log_index].iter().zip(&self.replays[*from..]) {
+            state = match (replay, &entry.skipped) {
+                (_, Some(_)) => state,
+                (Replay::Update(update), None) => T::apply(state, update),
+                (Replay::Filter(filter), None) => filter(state),
+            };
+        }
+        Some(state)
+    }
+}
+
+impl Engine<World> {
+    /**
+     * Puts one creature back as it was before the last commit that changed it,
+     * leaving everything else alone, like undoing a misapplied save in an area.
+     * The restore is a manual edit, so it can be undone in turn.
+     * Only while idle; returns whether the creature had anything to undo.
+     */
+    pub(crate) fn undo_creature(&mut self, id: CreatureId) -> bool {
+        if !self.is_idle() {
+            return false;
+        }
+        // Each commit's result is the next one's starting point, or the current state
+        let after = |i: usize| self.history.get(i + 1).map_or(&self.state, |(state, _)| state);
+        let changed = (0..self.history.len()).rev().find(|&i| {
+            self.history[i].0.base.creature(id) != after(i).base.creature(id)
+        });
+        let Some(changed) = changed else {
+            return false;
+        };
+        let creature = self.history[changed].0.base.creature(id).cloned();
+        self.apply_filter(Arc::new(move |mut state: State<World>| {
+            match creature.clone() {
+                Some(creature) => state.base.creatures.insert(id, creature),
+                None => state.base.creatures.remove(&id),
+            };
+            state
+        }));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::dice::FixedDice;
+    use crate::engine::{Action, ActionResponse, Engine, Resolved, State, TestBase, UpdateBuilder};
+    use crate::log::LogSource;
+    use crate::world::{damage, Creature, World, WorldBuilder};
+
+    #[test]
+    fn manual_edits_commit_at_once_and_can_be_undone() {
+        let mut engine = Engine::for_base(TestBase::default());
+        // Rules never see a manual edit
+        engine.rules.push(Arc::new(|_, _| panic!("rules were consulted")));
+        engine.apply_filter(Arc::new(|mut state: State<TestBase>| {
+            state.base.woos = 10;
+            state
+        }));
+        assert_eq!(engine.state.base.woos, 10);
+        assert_eq!(engine.log()[0].source, LogSource::Manual);
+        assert!(engine.rule_errors().is_empty());
+
+        assert!(engine.undo());
+        assert_eq!(engine.state.base.woos, 0);
+        assert!(!engine.undo());
+    }
+
+    #[test]
+    fn earlier_log_entries_can_be_revisited() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 50, 10))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.dice = Box::new(FixedDice::new(vec![15, 4, 15, 6]));
+        let mut hp = vec![];
+        let club = || Weapon::new("Club", WeaponGroup::Club, 1, 6);
+        for _ in 0..2 {
+            engine.act(Action::strike(0, 1, club()));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            hp.push(engine.state.base.creature(1).unwrap().hp);
+        }
+        engine.apply_filter(Arc::new(|mut state: State<World>| {
+            state.base.creature_mut(1).unwrap().hp = 1;
+            state
+        }));
+        assert_eq!(hp, vec![46, 40]);
+        let ogre = |engine: &Engine<World>, at| engine.state_at(at).unwrap().base.creatures[&1].hp;
+        assert_eq!((ogre(&engine, 0), ogre(&engine, 1), ogre(&engine, 2)), (46, 40, 1));
+        assert!(engine.state_at(3).is_none());
+
+        // Undone entries are still history, and don't leak into later ones
+        engine.undo();
+        engine.undo();
+        engine.apply_filter(Arc::new(|mut state: State<World>| {
+            state.base.creature_mut(1).unwrap().hp -= 10;
+            state
+        }));
+        assert_eq!((ogre(&engine, 1), ogre(&engine, 2), ogre(&engine, 3)), (40, 1, 36));
+    }
+
+    #[test]
+    fn undoing_one_creature_leaves_the_rest_of_the_area_alone() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.act(Action::new(Arc::new(|_, context| {
+            let burst = [0, 1].map(|target| {
+                UpdateBuilder::new()
+                    .filter(damage(target, 5))
+                    .target(target)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id())
+            });
+            ActionResponse::Valid(burst.into())
+        })));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        let hp = |engine: &Engine<World>, id| engine.state.base.creature(id).unwrap().hp;
+        assert_eq!((hp(&engine, 0), hp(&engine, 1)), (15, 3));
+
+        // The fighter was out of the burst after all
+        assert!(engine.undo_creature(0));
+        assert_eq!((hp(&engine, 0), hp(&engine, 1)), (20, 3));
+        assert_eq!(engine.log().last().unwrap().source, LogSource::Manual);
+        assert!(engine.undo());
+        assert_eq!((hp(&engine, 0), hp(&engine, 1)), (15, 3));
+        assert!(!engine.undo_creature(7));
+    }
+}
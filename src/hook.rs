@@ -0,0 +1,62 @@
+use crate::engine::State;
+
+// What a house rule makes of a resolution, given the state before it and the one it proposes
+pub(crate) enum HookDecision<T> {
+    Accept,
+    Reject,                 // Nothing the resolution did is committed or logged
+    Replace(Box<State<T>>), // Commit this instead
+}
+
+pub(crate) type PostResolveHook<T> =
+    Box<dyn Fn(&State<T>, &State<T>) -> HookDecision<T> + Send + Sync>;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{Action, ActionResponse, Engine, Resolved, TestBase, UpdateBuilder};
+
+    fn magic() -> Action<TestBase> {
+        Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(|mut state: State<TestBase>| {
+                    state.base.magics += 1;
+                    state
+                }))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+    }
+
+    fn run(engine: &mut Engine<TestBase>) {
+        engine.act(magic());
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    #[test]
+    fn house_rules_can_veto_or_rewrite_a_resolution() {
+        let mut engine = Engine::for_base(TestBase::default());
+        // No more than two magics at this table, and every one comes with a woo
+        engine.post_resolve_hook = Some(Box::new(|_, proposed| match proposed.base.magics {
+            0..=2 => {
+                let mut replaced = proposed.clone();
+                replaced.base.woos += 1;
+                HookDecision::Replace(Box::new(replaced))
+            }
+            _ => HookDecision::Reject,
+        }));
+        for _ in 0..3 {
+            run(&mut engine);
+        }
+        assert_eq!((engine.state.base.magics, engine.state.base.woos), (2, 2));
+        assert_eq!(engine.log().len(), 2);
+
+        engine.post_resolve_hook = Some(Box::new(|_, _| HookDecision::Accept));
+        run(&mut engine);
+        assert_eq!((engine.state.base.magics, engine.state.base.woos), (3, 2));
+    }
+}
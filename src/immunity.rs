@@ -0,0 +1,64 @@
+use crate::engine::{Meta, Tag};
+use crate::world::CreatureId;
+
+// The value of `Meta::turn` at which something lapses
+pub(crate) type ExpiryRound = u32;
+
+impl Meta {
+    /**
+     * Keeps rules from attaching the condition to the creature for the next `turns` turns,
+     * like the immunity that follows recovering from some effects. Any value of it counts.
+     */
+    pub(crate) fn grant_immunity(&mut self, creature: CreatureId, tag: Tag, turns: u32) {
+        let expiry = self.turn + turns;
+        self.temporary_immunities.push((creature, tag, expiry));
+    }
+
+    pub(crate) fn temporarily_immune(&self, creature: CreatureId, tag: &Tag) -> bool {
+        self.temporary_immunities
+            .iter()
+            .any(|(immune, held, expiry)| {
+                *immune == creature && held.same_kind(tag) && self.turn < *expiry
+            })
+    }
+
+    // Forgets the immunities that have run out
+    pub(crate) fn expire_immunities(&mut self) {
+        let turn = self.turn;
+        self.temporary_immunities.retain(|(_, _, expiry)| turn < *expiry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::engine::{
+        Action, ActionResponse, Engine, Resolved, RuleResponse, Tag, TestBase,
+        UpdateBuilder,
+    };
+
+    #[test]
+    fn immunity_blocks_the_condition_until_it_expires() {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Stunned(1))));
+        engine.state.meta.grant_immunity(0, Tag::Stunned(0), 1);
+        let stuns = |engine: &mut Engine<TestBase>| {
+            engine.act(Action::new(Arc::new(|_, context| {
+                let update = UpdateBuilder::new()
+                    .filter(Arc::new(|state| state))
+                    .target(0)
+                    .resolved(Resolved::Unresolved)
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![update])
+            })));
+            engine.step();
+            engine.step();
+            engine.updates[0].tags.contains(&Tag::Stunned(1))
+        };
+        assert!(!stuns(&mut engine));
+        engine.end_turn();
+        assert!(stuns(&mut engine));
+        assert!(engine.state.meta.temporary_immunities.is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::derived::follow;
+use crate::engine::{Base, Engine, Tag, Update};
+use crate::exclusive::exclude;
+use crate::tags::TagSet;
+
+// The ids of the chain's updates carrying each tag, in the order they gained it
+pub(crate) type TagIndex = HashMap<Tag, Vec<usize>>;
+
+fn add(index: &mut TagIndex, id: usize, tag: &Tag) {
+    index.entry(tag.clone()).or_default().push(id);
+}
+
+fn remove(index: &mut TagIndex, id: usize, tag: &Tag) {
+    if let Some(ids) = index.get_mut(tag) {
+        ids.retain(|i| *i != id);
+        if ids.is_empty() {
+            index.remove(tag);
+        }
+    }
+}
+
+// Brings the index up to date after rules changed an update's tags
+pub(crate) fn retag(index: &mut TagIndex, id: usize, before: &TagSet, after: &TagSet) {
+    for tag in before.iter().filter(|tag| !after.contains(tag)) {
+        remove(index, id, tag);
+    }
+    for tag in after.iter().filter(|tag| !before.contains(tag)) {
+        add(index, id, tag);
+    }
+}
+
+impl<T: Base> Engine<T> {
+    pub(crate) fn updates_with_tag(&self, tag: &Tag) -> &[usize] {
+        self.tag_index.get(tag).map_or(&[], Vec::as_slice)
+    }
+
+    // Indexes updates as they join the chain
+    pub(crate) fn index_updates(&mut self, updates: &[Update<T>]) {
+        for update in updates {
+            for tag in update.tags.iter() {
+                add(&mut self.tag_index, update.id, tag);
+            }
+        }
+    }
+
+    // Forgets updates as they leave the chain
+    pub(crate) fn unindex_updates(&mut self, updates: &[Update<T>]) {
+        for update in updates {
+            for tag in update.tags.iter() {
+                remove(&mut self.tag_index, update.id, tag);
+            }
+        }
+    }
+
+    // Drops the chain from `len` on, keeping the index in step
+    pub(crate) fn truncate_updates(&mut self, len: usize) {
+        let dropped = self.updates.split_off(len.min(self.updates.len()));
+        self.unindex_updates(&dropped);
+    }
+
+    pub(crate) fn clear_updates(&mut self) {
+        self.updates.clear();
+        self.tag_index.clear();
+    }
+
+    /**
+     * Attaches the tag to every update still pending in the chain, as if a rule
+     * had attached it to each: immunities, derived and exclusive tags all apply.
+     * Updates the cursor has passed already keep the tags they had.
+     */
+    pub(crate) fn attach_to_chain(&mut self, tag: Tag) {
+        let pending = self.update.min(self.updates.len())..;
+        for update in self.updates[pending].iter_mut() {
+            if self.state.meta.temporarily_immune(update.target, &tag) {
+                continue;
+            }
+            let before = update.tags.clone();
+            exclude(&self.exclusive, &tag, &mut update.tags);
+            follow(&self.derived, &tag, true, &mut update.tags);
+            update.tags.insert(tag.clone());
+            retag(&mut self.tag_index, update.id, &before, &update.tags);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{
+        Action, ActionResponse, Resolved, RuleResponse, State, TestBase, UpdateBuilder,
+    };
+
+    #[test]
+    fn attached_tags_are_indexed_until_the_chain_clears() {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Frightened(1))));
+        engine.act(Action::new(Arc::new(|_, context| {
+            let updates = (0..2)
+                .map(|_| {
+                    UpdateBuilder::new()
+                        .filter(Arc::new(|state: State<TestBase>| state))
+                        .build(context.next_id())
+                })
+                .collect();
+            ActionResponse::Valid(updates)
+        })));
+        assert!(engine.updates_with_tag(&Tag::Frightened(1)).is_empty());
+        for _ in 0..3 {
+            engine.step();
+        }
+        let ids: Vec<usize> = engine.updates.iter().map(|update| update.id).collect();
+        assert_eq!(engine.updates_with_tag(&Tag::Frightened(1)), ids.as_slice());
+        // A last update that resolves finishes the chain
+        let last = engine.updates.last_mut().unwrap();
+        last.resolved = Resolved::Resolved;
+        engine.update = 1;
+        engine.step();
+        assert!(engine.updates.is_empty());
+        assert!(engine.updates_with_tag(&Tag::Frightened(1)).is_empty());
+    }
+
+    #[test]
+    fn a_chain_wide_tag_marks_every_pending_update() {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.act(Action::new(Arc::new(|_, context| {
+            let updates = (0..3)
+                .map(|_| {
+                    UpdateBuilder::new()
+                        .filter(Arc::new(|state: State<TestBase>| state))
+                        .tag(Tag::Frightened(1))
+                        .build(context.next_id())
+                })
+                .collect();
+            ActionResponse::Valid(updates)
+        })));
+        engine.step();
+        engine.attach_to_chain(Tag::Manipulate);
+        let ids: Vec<usize> = engine.updates.iter().map(|update| update.id).collect();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(engine.updates_with_tag(&Tag::Manipulate), ids.as_slice());
+        assert!(engine.updates.iter().all(|update| update.tags.contains(&Tag::Manipulate)));
+        assert_eq!(engine.updates_with_tag(&Tag::Frightened(1)), ids.as_slice());
+    }
+}
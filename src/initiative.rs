@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::engine::{Engine, State};
+use crate::world::{CreatureId, World};
+
+/** Initiative
+ *  The order creatures take their turns in, highest roll first,
+ *  and whose turn it is. `current` indexes into `order`.
+ *  Delayed creatures are out of the order until they choose to come back.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Initiative {
+    pub(crate) order: Vec<CreatureId>,
+    pub(crate) current: usize,
+    pub(crate) delayed: Vec<CreatureId>,
+}
+
+// How creatures that rolled the same initiative are ordered among themselves
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum TieBreak {
+    #[default]
+    Given, // In the order the rolls were listed
+    Teams(Vec<u8>), // By team, in this order, like players before enemies; the rest after
+    Key(BTreeMap<CreatureId, i32>), // Higher first, like the Perception modifier; missing is 0
+}
+
+impl TieBreak {
+    // Lower goes first; equal ranks keep the order given
+    fn rank(&self, creature: CreatureId, team: u8) -> i32 {
+        match self {
+            TieBreak::Given => 0,
+            TieBreak::Teams(teams) => {
+                teams.iter().position(|t| *t == team).unwrap_or(teams.len()) as i32
+            }
+            TieBreak::Key(keys) => -keys.get(&creature).copied().unwrap_or(0),
+        }
+    }
+}
+
+impl Initiative {
+    // Orders the creatures by their rolls, highest first; ties keep the given order
+    pub(crate) fn new(rolls: Vec<(CreatureId, i32)>) -> Self {
+        Initiative::with_tie_break(rolls, &TieBreak::Given, |_| 0)
+    }
+
+    // As `new`, with ties broken by the policy; `team` says which side each creature is on
+    pub(crate) fn with_tie_break(
+        mut rolls: Vec<(CreatureId, i32)>,
+        tie_break: &TieBreak,
+        team: impl Fn(CreatureId) -> u8,
+    ) -> Self {
+        rolls.sort_by_key(|&(creature, roll)| (-roll, tie_break.rank(creature, team(creature))));
+        Initiative {
+            order: rolls.into_iter().map(|(creature, _)| creature).collect(),
+            current: 0,
+            delayed: vec![],
+        }
+    }
+
+    pub(crate) fn current(&self) -> Option<CreatureId> {
+        self.order.get(self.current).copied()
+    }
+
+    // Moves on to the next creature, wrapping around at the end of the round
+    pub(crate) fn advance(&mut self) -> Option<CreatureId> {
+        if self.order.is_empty() {
+            return None;
+        }
+        self.current = (self.current + 1) % self.order.len();
+        self.current()
+    }
+
+    /**
+     * Splices a creature in just before or after the anchor, as for a summon
+     * joining next to its summoner, without re-rolling anyone.
+     * The cursor moves with the current creature if the summon lands ahead of it.
+     * Does nothing if the anchor isn't in the order.
+     */
+    pub(crate) fn insert_relative(&mut self, new: CreatureId, anchor: CreatureId, before: bool) {
+        let Some(at) = self.order.iter().position(|creature| *creature == anchor) else {
+            return;
+        };
+        let at = if before { at } else { at + 1 };
+        self.order.insert(at, new);
+        if at <= self.current {
+            self.current += 1;
+        }
+    }
+
+    // Takes the current creature out of the order to wait, making the next one current
+    pub(crate) fn delay(&mut self) -> Option<CreatureId> {
+        let creature = self.current()?;
+        self.order.remove(self.current);
+        if self.current >= self.order.len() {
+            self.current = 0;
+        }
+        self.delayed.push(creature);
+        Some(creature)
+    }
+
+    /**
+     * Brings a delayed creature back in just before `at`. If it is `at`'s turn,
+     * the returning creature goes first and so takes its turn now.
+     * Returns false unless the creature is delayed and `at` is in the order.
+     */
+    pub(crate) fn resume(&mut self, creature: CreatureId, at: CreatureId) -> bool {
+        let Some(held) = self.delayed.iter().position(|c| *c == creature) else {
+            return false;
+        };
+        if !self.order.contains(&at) {
+            return false;
+        }
+        self.delayed.remove(held);
+        let now = self.current() == Some(at);
+        self.insert_relative(creature, at, true);
+        if now {
+            self.current -= 1;
+        }
+        true
+    }
+}
+
+impl World {
+    // Rolls the creatures into a fresh initiative order, breaking ties by the policy
+    pub(crate) fn roll_initiative(&mut self, rolls: Vec<(CreatureId, i32)>, tie_break: &TieBreak) {
+        let team = |id| self.creature(id).map_or(0, |creature| creature.team);
+        self.initiative = Initiative::with_tie_break(rolls, tie_break, team);
+    }
+}
+
+impl Engine<World> {
+    /**
+     * Delays the current creature's turn: it leaves the initiative order,
+     * and its turn ends as usual, so whatever wears off then does now.
+     */
+    pub(crate) fn delay_turn(&mut self) -> Option<CreatureId> {
+        let creature = self.state.base.initiative.current()?;
+        self.apply_filter(Arc::new(move |mut state: State<World>| {
+            state.base.initiative.delay();
+            let registry = state.base.custom_conditions.clone();
+            if let Some(delaying) = state.base.creature_mut(creature) {
+                delaying.end_turn(&registry);
+            }
+            state
+        }));
+        self.end_turn();
+        Some(creature)
+    }
+
+    // Returns the delayed creature to the order just before `at`, as `Initiative::resume`
+    pub(crate) fn resume_delayed(&mut self, creature: CreatureId, at: CreatureId) -> bool {
+        let mut initiative = self.state.base.initiative.clone();
+        if !initiative.resume(creature, at) {
+            return false;
+        }
+        self.apply_filter(Arc::new(move |mut state: State<World>| {
+            state.base.initiative = initiative.clone();
+            state
+        }));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Tag;
+    use crate::world::{Creature, WorldBuilder};
+
+    #[test]
+    fn summons_join_without_losing_the_current_turn() {
+        let mut initiative = Initiative::new(vec![(0, 12), (1, 20), (2, 15)]);
+        assert_eq!(initiative.order, vec![1, 2, 0]);
+        initiative.advance();
+        initiative.advance();
+        assert_eq!(initiative.current(), Some(0));
+
+        // The summoner 1 acts first, so its summon goes ahead of the current creature
+        initiative.insert_relative(3, 1, false);
+        assert_eq!(initiative.order, vec![1, 3, 2, 0]);
+        assert_eq!(initiative.current(), Some(0));
+        initiative.insert_relative(4, 0, false);
+        assert_eq!(initiative.order, vec![1, 3, 2, 0, 4]);
+        assert_eq!(initiative.current(), Some(0));
+        assert_eq!(initiative.advance(), Some(4));
+        assert_eq!(initiative.advance(), Some(1));
+    }
+
+    #[test]
+    fn delayed_creatures_come_back_where_they_choose() {
+        let mut world = World::default();
+        for (id, name) in [(0, "Fighter"), (1, "Rogue"), (2, "Goblin")] {
+            world.creatures.insert(id, Creature::new(name, 20, 16));
+        }
+        world.creature_mut(1).unwrap().conditions = vec![Tag::Frightened(2)];
+        world.initiative = Initiative::new(vec![(0, 12), (1, 20), (2, 15)]);
+        let mut engine = Engine::for_base(world);
+        assert_eq!(engine.delay_turn(), Some(1));
+        let initiative = &engine.state.base.initiative;
+        assert_eq!((initiative.order.clone(), initiative.current()), (vec![2, 0], Some(2)));
+        // Its turn ended, so its fear has started to fade
+        assert_eq!(engine.state.base.creature(1).unwrap().conditions, vec![Tag::Frightened(1)]);
+
+        engine.state.base.initiative.advance();
+        assert!(!engine.resume_delayed(2, 0));
+        assert!(engine.resume_delayed(1, 0));
+        let initiative = &engine.state.base.initiative;
+        assert_eq!(initiative.order, vec![2, 1, 0]);
+        assert_eq!(initiative.current(), Some(1));
+        assert!(initiative.delayed.is_empty());
+    }
+
+    #[test]
+    fn tied_creatures_go_in_the_order_the_policy_gives() {
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Goblin", 8, 16))
+            .add_creature(1, Creature::new("Fighter", 20, 18))
+            .add_creature(2, Creature::new("Rogue", 18, 18))
+            .build();
+        world.creature_mut(0).unwrap().team = 1;
+        let turns = |world: &World, tie_break| {
+            let mut world = world.clone();
+            world.roll_initiative(vec![(0, 15), (1, 15), (2, 9)], &tie_break);
+            let mut engine = Engine::for_base(world);
+            let first = engine.state.base.initiative.current();
+            vec![first, engine.next_turn(), engine.next_turn()]
+        };
+        assert_eq!(turns(&world, TieBreak::Teams(vec![0, 1])), vec![Some(1), Some(0), Some(2)]);
+        assert_eq!(turns(&world, TieBreak::Teams(vec![1, 0])), vec![Some(0), Some(1), Some(2)]);
+        let perception = BTreeMap::from([(0, 3), (1, 5)]);
+        assert_eq!(turns(&world, TieBreak::Key(perception)), vec![Some(1), Some(0), Some(2)]);
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+
+use crate::engine::{Base, Engine};
+
+impl<T: Base> Engine<T> {
+    /**
+     * Panics, saying what went wrong, if the engine's bookkeeping doesn't add up.
+     * Only built with the `invariants` feature, which runs it after every step.
+     */
+    pub(crate) fn check_invariants(&self) {
+        let len = self.updates.len();
+        assert!(
+            self.update <= len,
+            "update cursor {} is past the end of a {len} update chain",
+            self.update
+        );
+        assert!(
+            self.committed <= self.update,
+            "{} updates are committed but the cursor is only at {}",
+            self.committed,
+            self.update
+        );
+        assert!(
+            self.rule == 0 || self.update < len,
+            "rule {} is paused on an update that doesn't exist",
+            self.rule
+        );
+        assert!(
+            self.rule <= self.rules.len(),
+            "rule cursor {} is past the last of {} rules",
+            self.rule,
+            self.rules.len()
+        );
+        let mut ids = HashSet::new();
+        for update in self.updates.iter() {
+            assert!(ids.insert(update.id), "update id {} appears twice in the chain", update.id);
+        }
+        assert!(
+            self.updates.is_empty() || self.committed < len || self.update < len,
+            "a fully committed chain was left unsettled"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::engine::{Engine, Resolved, TestBase, UpdateBuilder};
+
+    fn engine() -> Engine<TestBase> {
+        Engine::for_base(TestBase::default())
+    }
+
+    #[test]
+    fn a_healthy_chain_passes() {
+        let mut engine = engine();
+        for resolved in [Resolved::Unresolved, Resolved::Resolved] {
+            let id = engine.next_id();
+            let update = UpdateBuilder::new().filter(Arc::new(|s| s)).resolved(resolved);
+            engine.updates.push(update.build(id));
+        }
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "update cursor 5 is past the end")]
+    fn an_out_of_range_cursor_is_caught() {
+        let mut engine = engine();
+        let id = engine.next_id();
+        engine.updates.push(UpdateBuilder::new().filter(Arc::new(|s| s)).build(id));
+        engine.update = 5;
+        engine.step();
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use crate::check::Degree;
+use crate::engine::{Action, Resolved, State, UpdateBuilder};
+use crate::resistance::Resistance;
+use crate::stats::{Save, Skill};
+use crate::world::{Creature, CreatureId, DamageType, World};
+
+const SAVES: [Save; 3] = [Save::Fortitude, Save::Reflex, Save::Will];
+
+// Something worth knowing about a creature, most useful first
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Fact {
+    WeakestSave(Save),
+    Immunity(DamageType),
+    Resistance(Resistance),
+    Ac(i32),
+    Level(i32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum KnowledgeResult {
+    Facts(Vec<Fact>),
+    Nothing,
+    Misinformation(Fact), // Believed to be true, but isn't
+}
+
+// What a creature learned about another by Recalling Knowledge
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Recollection {
+    pub(crate) actor: CreatureId,
+    pub(crate) target: CreatureId,
+    pub(crate) result: KnowledgeResult,
+}
+
+impl Creature {
+    pub(crate) fn facts(&self) -> Vec<Fact> {
+        let weakest = SAVES.into_iter().min_by_key(|save| self.save_modifier(*save));
+        weakest
+            .map(Fact::WeakestSave)
+            .into_iter()
+            .chain(self.immunities.iter().copied().map(Fact::Immunity))
+            .chain(self.resistances.iter().cloned().map(Fact::Resistance))
+            .chain([Fact::Ac(self.armor_class()), Fact::Level(self.level)])
+            .collect()
+    }
+
+    // Passes its strongest save off as its weakest, or overstates its AC if they're all alike
+    fn misinformation(&self) -> Fact {
+        let by_modifier = |save: &Save| self.save_modifier(*save);
+        let weakest = SAVES.into_iter().min_by_key(by_modifier);
+        match (weakest, SAVES.into_iter().max_by_key(by_modifier)) {
+            (Some(weakest), Some(strongest)) if by_modifier(&strongest) > by_modifier(&weakest) => {
+                Fact::WeakestSave(strongest)
+            }
+            _ => Fact::Ac(self.armor_class() + 2),
+        }
+    }
+
+    pub(crate) fn recall(&self, degree: Degree) -> KnowledgeResult {
+        let facts = |count| KnowledgeResult::Facts(self.facts().into_iter().take(count).collect());
+        match degree {
+            Degree::CriticalSuccess => facts(2),
+            Degree::Success => facts(1),
+            Degree::Failure => KnowledgeResult::Nothing,
+            Degree::CriticalFailure => KnowledgeResult::Misinformation(self.misinformation()),
+        }
+    }
+}
+
+impl Action<World> {
+    /**
+     * A skill check to remember something about the target, kept in `Meta::recalled`.
+     * A critical success recalls two facts and a success one; a critical failure
+     * recalls something false, which the actor can't tell from the truth.
+     */
+    pub(crate) fn recall_knowledge(
+        actor: CreatureId,
+        skill: Skill,
+        target: CreatureId,
+        dc: i32,
+    ) -> Self {
+        Action::skill_check(actor, skill, dc, move |degree, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(move |mut state: State<World>| {
+                    if let Some(result) = state.base.creature(target).map(|c| c.recall(degree)) {
+                        state.meta.recalled.push(Recollection { actor, target, result });
+                    }
+                    state
+                }))
+                .target(actor)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            vec![update]
+        })
+        .with_cost(1)
+        .named("Recall Knowledge")
+        .by(actor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::FixedDice;
+    use crate::engine::Engine;
+    use crate::field::Ability;
+    use crate::world::WorldBuilder;
+
+    fn recall(roll: u8) -> KnowledgeResult {
+        let mut troll = Creature::new("Troll", 115, 20);
+        troll.abilities[Ability::Constitution as usize] = 18;
+        troll.abilities[Ability::Wisdom as usize] = 8;
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Wizard", 16, 15))
+            .add_creature(1, troll)
+            .build();
+        let mut engine = Engine::new_encounter(world, vec![]);
+        engine.dice = Box::new(FixedDice::new(vec![roll]));
+        engine.act(Action::recall_knowledge(0, Skill::Nature, 1, 15));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        engine.state.meta.recalled.pop().unwrap().result
+    }
+
+    #[test]
+    fn the_degree_decides_what_is_remembered() {
+        assert_eq!(recall(16), KnowledgeResult::Facts(vec![Fact::WeakestSave(Save::Will)]));
+        assert_eq!(
+            recall(20),
+            KnowledgeResult::Facts(vec![Fact::WeakestSave(Save::Will), Fact::Ac(20)])
+        );
+        assert_eq!(recall(10), KnowledgeResult::Nothing);
+        // Fortitude is its best save by far
+        assert_eq!(
+            recall(1),
+            KnowledgeResult::Misinformation(Fact::WeakestSave(Save::Fortitude))
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::check::Degree;
+use crate::engine::{Action, Base, Engine, Tag, Update};
+use crate::history::Replay;
+use crate::tags::TagSet;
+use crate::world::CreatureId;
+
+// Where a logged change came from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum LogSource {
+    Update { id: usize, target: CreatureId },
+    Manual, // Applied straight to the state with `apply_filter`
+}
+
+/** LogEntry
+ *  One committed change, as it was when it was applied.
+ *  `rng_offset` is how many rolls the dice had made by then, so seeking
+ *  a fresh copy of the dice there replays everything that came after.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct LogEntry {
+    pub(crate) source: LogSource,
+    pub(crate) tags: TagSet,
+    pub(crate) rng_offset: u64,
+    pub(crate) skipped: Option<String>, // Why the update had no effect, if it was skipped
+    pub(crate) fingerprint: u64,        // Of the state just after it was applied
+}
+
+/** ActionSummary
+ *  One action a creature took, for an after-action report like
+ *  "Strike on 1 (Success, 6 damage)". The damage is as rolled, before any resistance.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ActionSummary {
+    pub(crate) actor: CreatureId,
+    pub(crate) name: String,
+    pub(crate) target: Option<CreatureId>,
+    pub(crate) degree: Option<Degree>,
+    pub(crate) damage: i32,
+}
+
+// Sums up an action from the updates it generated, if it is named and has a known actor
+pub(crate) fn summarize<T>(action: &Action<T>, updates: &[Update<T>]) -> Option<ActionSummary> {
+    let (actor, name) = (action.actor()?, action.name.clone()?);
+    let tags = || updates.iter().flat_map(|update| update.tags.iter());
+    let target = match &action.targeting {
+        Some(targeting) => Some(targeting.target),
+        None => updates.iter().map(|update| update.target).find(|target| *target != actor),
+    };
+    Some(ActionSummary {
+        actor,
+        name,
+        target,
+        degree: tags().find_map(|tag| match tag {
+            Tag::Degree(degree) => Some(*degree),
+            _ => None,
+        }),
+        damage: tags()
+            .map(|tag| match tag {
+                Tag::Damage(breakdown) => breakdown.total,
+                _ => 0,
+            })
+            .sum(),
+    })
+}
+
+impl<T: Base> Engine<T> {
+    pub(crate) fn log(&self) -> &[LogEntry] {
+        &self.log
+    }
+
+    // Everything the creature did, in the order it did it
+    pub(crate) fn creature_log(&self, id: CreatureId) -> Vec<ActionSummary> {
+        self.summaries.iter().filter(|summary| summary.actor == id).cloned().collect()
+    }
+
+    // Logs the chain's updates from `from` on as they commit, one per skip reason and fingerprint
+    pub(crate) fn record(&mut self, from: usize, outcomes: Vec<(Option<String>, u64)>) {
+        let rng_offset = self.dice.position();
+        for (update, (skipped, fingerprint)) in self.updates[from..].iter().zip(outcomes) {
+            self.log.push(LogEntry {
+                source: LogSource::Update {
+                    id: update.id,
+                    target: update.target,
+                },
+                tags: update.tags.clone(),
+                rng_offset,
+                skipped,
+                fingerprint,
+            });
+            self.replays.push(Replay::Update(update.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::dice::FixedDice;
+    
+    use crate::world::{Creature, Position, WorldBuilder};
+
+    #[test]
+    fn entries_record_the_rolls_made_so_far() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 300, 10))
+            .build();
+        let mut engine = Engine::for_base(world);
+        let mut rolls = vec![];
+        for _ in 0..2 {
+            engine.act(Action::strike(0, 1, Weapon::new("Club", WeaponGroup::Club, 2, 6)));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            rolls.push(engine.dice.position());
+        }
+        let offsets: Vec<u64> = engine.log().iter().map(|entry| entry.rng_offset).collect();
+        assert_eq!(offsets, rolls);
+        assert!(offsets[0] > 0 && offsets[1] > offsets[0]);
+    }
+
+    #[test]
+    fn each_creature_gets_its_own_log() {
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .build();
+        let mut goblin = Creature::new("Goblin", 8, 16);
+        goblin.position = Position::new(1, 0);
+        world.creatures.insert(1, goblin);
+        let mut engine = Engine::for_base(world);
+        // The fighter's 18 hits AC 16, the goblin's 2 misses AC 18 badly
+        engine.dice = Box::new(FixedDice::new(vec![18, 4, 2]));
+        let club = || Weapon::new("Club", WeaponGroup::Club, 1, 6);
+        for action in [
+            Action::strike(0, 1, club()),
+            Action::strike(1, 0, club()),
+            Action::step(1, Position::new(2, 0)),
+        ] {
+            engine.act(action);
+            while !engine.is_idle() {
+                engine.step();
+            }
+        }
+        let fighter = engine.creature_log(0);
+        assert_eq!(fighter.len(), 1);
+        assert_eq!(fighter[0].name, "Strike");
+        assert_eq!(fighter[0].target, Some(1));
+        assert_eq!(fighter[0].degree, Some(Degree::Success));
+        assert!(fighter[0].damage > 0);
+        let goblin: Vec<(String, Option<Degree>)> = engine
+            .creature_log(1)
+            .into_iter()
+            .map(|summary| (summary.name, summary.degree))
+            .collect();
+        assert_eq!(
+            goblin,
+            vec![
+                (String::from("Strike"), Some(Degree::CriticalFailure)),
+                (String::from("Step"), None),
+            ]
+        );
+    }
+}
@@ -1,3 +1,124 @@
+// The demo in `main` drives only part of the engine; the tests drive the rest
+#![cfg_attr(not(test), allow(dead_code))]
+
+mod affliction;
+mod aid;
+mod audit;
+mod aura;
+mod bomb;
+mod bulk;
+mod casting;
+mod check;
+mod combat;
+mod condition;
+mod content;
+mod counteract;
+mod crit;
+mod cover;
+mod damage;
+mod defense;
+mod degree;
+mod delta;
+mod derived;
+mod dice;
+mod diff;
+mod dying;
+mod effective;
+mod encounter;
+mod exclusive;
+mod expected;
+mod expression;
 mod engine;
+mod escape;
+mod field;
+mod fortune;
+mod filter;
+mod fingerprint;
+mod frequency;
+mod handle;
+mod hero;
+mod immunity;
+mod index;
+mod initiative;
+mod history;
+mod hook;
+#[cfg(feature = "invariants")]
+mod invariants;
+mod knowledge;
+mod log;
+mod metadata;
+mod metrics;
+mod modifier;
+mod movement;
+mod ongoing;
+mod oneshot;
+mod persistent;
+mod policy;
+mod pool;
+mod predicate;
+mod prone;
+mod reaction;
+mod ready;
+mod resistance;
+mod registry;
+mod repeat;
+mod round;
+mod regen;
+mod sandbox;
+mod scenario;
+mod shield;
+mod size;
+#[cfg(feature = "binary")]
+mod snapshot;
+#[cfg(feature = "json")]
+mod statblock;
+mod stats;
+mod status;
+mod sustain;
+mod tags;
+mod temporary;
+mod targeting;
+mod world;
+
+use combat::{Weapon, WeaponGroup};
+use engine::{Action, Engine, Meta, State};
+use world::{Creature, Position, World};
+
+// A fighter swings at a goblin; the same engine the tests drive, start to finish
+fn example() -> Engine<World> {
+    let mut world = World::default();
+    world.creatures.insert(0, Creature::new("Fighter", 20, 18));
+    let mut goblin = Creature::new("Goblin", 8, 16);
+    goblin.position = Position::new(1, 0);
+    world.creatures.insert(1, goblin);
+    let mut engine = Engine::new(State {
+        meta: Meta::default(),
+        base: world,
+        pre_chain: None,
+    });
+    engine.act(Action::strike(0, 1, Weapon::new("Longsword", WeaponGroup::Sword, 1, 8)));
+    while !engine.is_idle() {
+        engine.step();
+    }
+    engine
+}
+
+fn main() {
+    let engine = example();
+    for creature in engine.state.base.creatures.values() {
+        println!("{creature}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn main() {}
+    #[test]
+    fn the_example_runs_the_strike_through_the_engine() {
+        let engine = example();
+        assert!(engine.is_idle());
+        assert_eq!(engine.creature_log(0).len(), 1);
+        assert_eq!(engine.state.meta.actions_remaining, 2);
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::engine::Update;
+
+// A piece of structured data carried on an update
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Int(i32),
+    Text(String),
+    Flag(bool),
+}
+
+// Empty by default, which doesn't allocate
+pub(crate) type Metadata = HashMap<String, Value>;
+
+impl<T> Update<T> {
+    pub(crate) fn meta_value(&self, key: &str) -> Option<&Value> {
+        self.meta.get(key)
+    }
+
+    // The value under the key, if it is a number
+    pub(crate) fn meta_int(&self, key: &str) -> Option<i32> {
+        match self.meta.get(key) {
+            Some(Value::Int(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set_meta(&mut self, key: &str, value: Value) {
+        self.meta.insert(String::from(key), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{
+        Action, ActionResponse, Engine, Resolved, RuleResponse, State, Tag, TestBase,
+        UpdateBuilder,
+    };
+
+    fn spell(rank: i32) -> Action<TestBase> {
+        Action::new(Arc::new(move |_, context| {
+            let mut update = UpdateBuilder::new()
+                .filter(Arc::new(|state: State<TestBase>| state))
+                .meta("rank", Value::Int(rank))
+                .meta("name", Value::Text(String::from("Fireball")))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            update.set_meta("heightened", Value::Flag(rank > 3));
+            ActionResponse::Valid(vec![update])
+        }))
+    }
+
+    #[test]
+    fn rules_can_branch_on_metadata() {
+        let mut engine = Engine::for_base(TestBase::default());
+        // Only spells of rank 3 or higher are worth countering
+        engine.rules.push(Arc::new(|_, update| match update.meta_int("rank") {
+            Some(rank) if rank >= 3 => RuleResponse::Attach(Tag::Manipulate),
+            _ => RuleResponse::Skip,
+        }));
+        let countered = |engine: &mut Engine<TestBase>, rank| {
+            engine.act(spell(rank));
+            engine.step();
+            engine.step();
+            engine.log().last().unwrap().tags.contains(&Tag::Manipulate)
+        };
+        assert!(!countered(&mut engine, 2));
+        assert!(countered(&mut engine, 3));
+        assert_eq!(engine.log().len(), 2);
+        engine.act(spell(4));
+        engine.step();
+        assert_eq!(engine.updates[0].meta_value("heightened"), Some(&Value::Flag(true)));
+    }
+}
@@ -0,0 +1,78 @@
+use crate::engine::{Base, Engine};
+
+/** Metrics
+ *  How the current activity's chain grew while it resolved, for spotting
+ *  combos that blow up. Reset by every `act`.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Metrics {
+    pub(crate) depth: usize, // The longest the chain got, injected updates included
+    pub(crate) applied: usize, // Updates applied to the state, leaving out skipped ones
+    pub(crate) injections: usize,
+    pub(crate) diversions: usize,
+    pub(crate) reverts: usize,
+}
+
+impl<T: Base> Engine<T> {
+    pub(crate) fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{
+        Action, ActionResponse, Resolved, Rule, RuleResponse, State, Tag, TestBase,
+        UpdateBuilder,
+    };
+
+    fn magic(tag: Option<Tag>) -> Action<TestBase> {
+        Action::new(Arc::new(move |_, context| {
+            let mut builder = UpdateBuilder::new();
+            if let Some(tag) = tag.clone() {
+                builder = builder.tag(tag);
+            }
+            let update = builder
+                .filter(Arc::new(|mut state: State<TestBase>| {
+                    state.base.magics += 1;
+                    state
+                }))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+    }
+
+    // Injects an untagged magic after any manipulating update
+    fn echo() -> Rule<TestBase> {
+        Arc::new(|_, update| match update.tags.contains(&Tag::Manipulate) {
+            true => RuleResponse::Inject(magic(None)),
+            false => RuleResponse::Skip,
+        })
+    }
+
+    #[test]
+    fn injections_deepen_the_chain() {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.rules.push(echo());
+        engine.rules.push(echo());
+        engine.act(magic(Some(Tag::Manipulate)));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!(engine.state.base.magics, 3);
+        assert_eq!(
+            engine.metrics(),
+            &Metrics {
+                depth: 3,
+                applied: 3,
+                injections: 2,
+                diversions: 0,
+                reverts: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,82 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum ModifierKind {
+    Status,
+    Circumstance,
+    Item,
+    Untyped,
+}
+
+// A bonus (positive) or penalty (negative) to a roll or DC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Modifier {
+    pub(crate) kind: ModifierKind,
+    pub(crate) bonus: i32,
+}
+
+impl Modifier {
+    pub(crate) fn new(kind: ModifierKind, bonus: i32) -> Self {
+        Modifier { kind, bonus }
+    }
+}
+
+/**
+ * The modifiers that count under the stacking rules, by index:
+ * for each typed kind only the best bonus and the worst penalty count,
+ * while untyped modifiers all add together.
+ */
+pub(crate) fn counted(modifiers: &[Modifier]) -> Vec<usize> {
+    let mut counted = vec![];
+    for kind in [
+        ModifierKind::Status,
+        ModifierKind::Circumstance,
+        ModifierKind::Item,
+    ] {
+        let of_kind = || modifiers.iter().enumerate().filter(move |(_, m)| m.kind == kind);
+        let best = of_kind().filter(|(_, m)| m.bonus > 0).max_by_key(|(_, m)| m.bonus);
+        let worst = of_kind().filter(|(_, m)| m.bonus < 0).min_by_key(|(_, m)| m.bonus);
+        counted.extend(best.into_iter().chain(worst).map(|(i, _)| i));
+    }
+    counted.extend(
+        modifiers
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.kind == ModifierKind::Untyped)
+            .map(|(i, _)| i),
+    );
+    counted.sort();
+    counted
+}
+
+// Totals modifiers per the stacking rules
+pub(crate) fn combine(modifiers: &[Modifier]) -> i32 {
+    counted(modifiers).into_iter().map(|i| modifiers[i].bonus).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ModifierKind::*;
+
+    #[test]
+    fn same_type_bonuses_do_not_stack() {
+        let two_status = [Modifier::new(Status, 2), Modifier::new(Status, 2)];
+        assert_eq!(combine(&two_status), 2);
+        let mixed = [Modifier::new(Status, 2), Modifier::new(Circumstance, 1)];
+        assert_eq!(combine(&mixed), 3);
+    }
+
+    #[test]
+    fn penalties_take_the_worst_per_type_and_untyped_add_up() {
+        let modifiers = [
+            Modifier::new(Status, 1),
+            Modifier::new(Status, -1),
+            Modifier::new(Status, -2),
+            Modifier::new(Circumstance, -2),
+            Modifier::new(Untyped, -1),
+            Modifier::new(Untyped, -1),
+        ];
+        assert_eq!(combine(&modifiers), 1 - 2 - 2 - 2);
+    }
+}
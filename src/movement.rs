@@ -0,0 +1,344 @@
+use std::sync::Arc;
+
+use crate::combat::Weapon;
+use crate::engine::{
+    Action, ActionResponse, Filter, InvalidAction, Resolved, State, Tag, Update, UpdateBuilder,
+};
+use crate::reaction::{Reaction, ReactionBuilder};
+use crate::world::{CreatureId, Position, World};
+
+// How a creature came to change squares; only some kinds provoke reactions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Movement {
+    Stride,
+    Step,
+    Forced, // Shoved, pulled or otherwise moved by something else
+}
+
+// Moves the creature onto the square, spending `cost` feet of its movement
+fn place(mover: CreatureId, to: Position, cost: i32) -> Filter<World> {
+    Arc::new(move |mut state: State<World>| {
+        if let Some(creature) = state.base.creature_mut(mover) {
+            creature.position = to;
+            creature.movement_left -= cost;
+        }
+        state
+    })
+}
+
+/**
+ * One update per square of the path, each tagged with the move it makes.
+ * Every square must be adjacent to the one before it, starting from the mover's own.
+ * Only Strides spend the mover's movement: every second diagonal costs 10 feet
+ * instead of 5, and difficult terrain doubles the cost of entering a square,
+ * greater difficult terrain triples it. The whole path must fit the movement left.
+ */
+fn movement(mover: CreatureId, path: Vec<Position>, kind: Movement) -> Action<World> {
+    Action::new(Arc::new(move |state: &State<World>, context| {
+        let Some(creature) = state.base.creature(mover) else {
+            return ActionResponse::Invalid(InvalidAction::BadPredicate);
+        };
+        let mut from = creature.position;
+        let mut diagonals = 0;
+        let mut spent = 0;
+        let mut updates: Vec<Update<World>> = vec![];
+        for &to in path.iter() {
+            if !from.adjacent(&to) {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            }
+            let mut cost = 5;
+            if from.x != to.x && from.y != to.y {
+                diagonals += 1;
+                if diagonals % 2 == 0 {
+                    cost = 10;
+                }
+            }
+            cost *= state.base.terrain_multiplier(&to);
+            if kind != Movement::Stride {
+                cost = 0;
+            }
+            spent += cost;
+            updates.push(
+                UpdateBuilder::new()
+                    .filter(place(mover, to, cost))
+                    .target(mover)
+                    .tag(Tag::Move {
+                        mover,
+                        from,
+                        to,
+                        kind,
+                    })
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id()),
+            );
+            from = to;
+        }
+        if updates.is_empty() {
+            return ActionResponse::Invalid(InvalidAction::BadPredicate);
+        }
+        if spent > creature.movement_left {
+            return ActionResponse::Invalid(InvalidAction::OutOfRange {
+                needed: creature.movement_left,
+                actual: spent,
+            });
+        }
+        ActionResponse::Valid(updates)
+    }))
+}
+
+impl Action<World> {
+    pub(crate) fn stride(mover: CreatureId, path: Vec<Position>) -> Self {
+        movement(mover, path, Movement::Stride).with_cost(1).named("Stride").by(mover)
+    }
+
+    // A single 5-foot step, which doesn't provoke
+    pub(crate) fn step(mover: CreatureId, to: Position) -> Self {
+        movement(mover, vec![to], Movement::Step).with_cost(1).named("Step").by(mover)
+    }
+
+    // Costs the mover nothing; whatever forces the move pays for it
+    pub(crate) fn forced_move(mover: CreatureId, path: Vec<Position>) -> Self {
+        movement(mover, path, Movement::Forced)
+    }
+}
+
+/**
+ * A reaction for an enemy moving out of the owner's reach, `reach` feet, in any way.
+ * Moves that start and end within reach don't trigger it.
+ */
+pub(crate) fn leaving_reach(
+    owner: CreatureId,
+    reach: i32,
+    name: &str,
+    build: ReactionBuilder<World>,
+) -> Reaction<World> {
+    Reaction {
+        owner,
+        name: String::from(name),
+        trigger: Tag::Move {
+            mover: owner,
+            from: Position::default(),
+            to: Position::default(),
+            kind: Movement::Stride,
+        },
+        eligible: Arc::new(move |state, trigger| {
+            let Tag::Move { mover, from, to, .. } = trigger else {
+                return false;
+            };
+            let (Some(reacting), Some(moving)) =
+                (state.base.creature(owner), state.base.creature(*mover))
+            else {
+                return false;
+            };
+            let within = |square: &Position| reacting.position.distance(square) <= reach;
+            *mover != owner && reacting.is_enemy(moving) && within(from) && !within(to)
+        }),
+        build,
+    }
+}
+
+/**
+ * Attack of Opportunity: a free Strike against an enemy that Strides out of
+ * a square within the owner's reach with the weapon. Steps and forced movement don't trigger it.
+ */
+pub(crate) fn attack_of_opportunity(owner: CreatureId, weapon: Weapon) -> Reaction<World> {
+    let reach = weapon.reach;
+    Reaction {
+        owner,
+        name: String::from("Attack of Opportunity"),
+        trigger: Tag::Move {
+            mover: owner,
+            from: Position::default(),
+            to: Position::default(),
+            kind: Movement::Stride,
+        },
+        eligible: Arc::new(move |state, trigger| {
+            let Tag::Move {
+                mover,
+                from,
+                kind: Movement::Stride,
+                ..
+            } = trigger
+            else {
+                return false;
+            };
+            let (Some(reacting), Some(moving)) =
+                (state.base.creature(owner), state.base.creature(*mover))
+            else {
+                return false;
+            };
+            let threatened = reacting.position.distance(from) <= reacting.melee_reach(reach);
+            *mover != owner && reacting.is_enemy(moving) && threatened
+        }),
+        build: Arc::new(move |_, trigger| {
+            let Tag::Move { mover, .. } = trigger else {
+                unreachable!("Attack of Opportunity only triggers on movement");
+            };
+            // The reaction is spent, not one of the owner's actions
+            Action::strike(owner, *mover, weapon.clone()).with_cost(0)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::WeaponGroup;
+    use crate::engine::Engine;
+    use crate::world::{Creature, WorldBuilder};
+
+    // A fighter at `at` and a goblin, ready to make Attacks of Opportunity, at (2, 1)
+    fn engine(at: Position) -> Engine<World> {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.position = at;
+        let mut goblin = Creature::new("Goblin", 8, 16);
+        goblin.position = Position::new(2, 1);
+        goblin.team = 1;
+        let world = WorldBuilder::new()
+            .add_creature(0, fighter)
+            .add_creature(1, goblin)
+            .build();
+        let mut engine = Engine::for_base(world);
+        let dogslicer = Weapon::new("Dogslicer", WeaponGroup::Sword, 1, 6);
+        engine.reactions.push(attack_of_opportunity(1, dogslicer));
+        engine
+    }
+
+    #[test]
+    fn striding_out_of_reach_opens_a_window() {
+        let mut engine = engine(Position::new(0, 0));
+        engine.act(Action::stride(
+            0,
+            vec![Position::new(1, 0), Position::new(2, 0), Position::new(3, 0)],
+        ));
+        engine.step(); // Generates the path
+        engine.step(); // (0, 0) to (1, 0), entering reach doesn't provoke
+        assert!(engine.window.is_none());
+        engine.step(); // (1, 0) to (2, 0) leaves a square next to the goblin
+        let window = engine.window.clone().unwrap();
+        assert_eq!(window.len(), 1);
+        assert_eq!(window[0].owner, 1);
+        assert_eq!(engine.state.base.creature(0).unwrap().position, Position::new(2, 0));
+
+        engine.decline_reactions();
+        engine.step(); // Leaving (2, 0) provokes again
+        assert!(engine.window.is_some());
+        engine.decline_reactions();
+        assert!(engine.is_idle());
+        assert_eq!(engine.state.base.creature(0).unwrap().position, Position::new(3, 0));
+    }
+
+    #[test]
+    fn only_leaving_a_reach_weapons_threat_triggers() {
+        let mut engine = engine(Position::new(1, 1));
+        engine.reactions.clear();
+        let glaive = Weapon::new("Glaive", WeaponGroup::Polearm, 1, 8).with_reach(10);
+        engine.reactions.push(leaving_reach(
+            1,
+            glaive.reach,
+            "Attack of Opportunity",
+            Arc::new(move |_, _| Action::strike(1, 0, glaive.clone()).with_cost(0)),
+        ));
+        engine.act(Action::stride(0, vec![Position::new(0, 1), Position::new(-1, 1)]));
+        engine.step();
+        engine.step(); // (1, 1) to (0, 1) stays 10 feet from the goblin
+        assert!(engine.window.is_none());
+        engine.step(); // (0, 1) to (-1, 1) is 15 feet away
+        assert_eq!(engine.window.clone().unwrap()[0].owner, 1);
+    }
+
+    #[test]
+    fn stepping_does_not_provoke() {
+        let mut engine = engine(Position::new(1, 0));
+        engine.act(Action::step(0, Position::new(1, 1)));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!(engine.state.base.creature(0).unwrap().position, Position::new(1, 1));
+        assert_eq!(engine.state.meta.actions_remaining, 2);
+    }
+
+    #[test]
+    fn strides_spend_the_turns_movement() {
+        let east = |squares: i32| (1..=squares).map(|x| Position::new(x, 0)).collect::<Vec<_>>();
+        let mut engine = engine(Position::new(0, 0));
+        engine.reactions.clear();
+        engine.act(Action::stride(0, east(6)));
+        engine.step();
+        assert_eq!(
+            engine.rejected,
+            Some(InvalidAction::OutOfRange {
+                needed: 25,
+                actual: 30
+            })
+        );
+
+        engine.act(Action::stride(0, east(5)));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert_eq!(fighter.position, Position::new(5, 0));
+        assert_eq!(fighter.movement_left, 0);
+
+        // Back to where it was, but every square is now difficult terrain
+        let fighter = engine.state.base.creature_mut(0).unwrap();
+        fighter.position = Position::new(0, 0);
+        fighter.reset_turn();
+        engine.state.base.difficult_terrain.extend(east(6));
+        engine.act(Action::stride(0, east(3)));
+        engine.step();
+        assert!(engine.rejected.is_some());
+        engine.act(Action::stride(0, east(2)));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!(engine.state.base.creature(0).unwrap().movement_left, 5);
+    }
+
+    #[test]
+    fn terrain_shortens_a_stride() {
+        let east = |squares: i32| (1..=squares).map(|x| Position::new(x, 0)).collect::<Vec<_>>();
+        let mut engine = engine(Position::new(0, 0));
+        engine.reactions.clear();
+        let stride = |engine: &mut Engine<World>, squares| {
+            let fighter = engine.state.base.creature_mut(0).unwrap();
+            fighter.position = Position::new(0, 0);
+            fighter.speed = 30;
+            fighter.reset_turn();
+            engine.act(Action::stride(0, east(squares)));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            engine.state.base.creature(0).unwrap().position.x
+        };
+        assert_eq!(stride(&mut engine, 6), 6);
+
+        // 15 feet through difficult terrain spends all 30; a fourth square is a Stride too far
+        engine.state.base.difficult_terrain.extend(east(6));
+        assert_eq!(stride(&mut engine, 3), 3);
+        assert_eq!(stride(&mut engine, 4), 0);
+        assert_eq!(
+            engine.rejected,
+            Some(InvalidAction::OutOfRange {
+                needed: 30,
+                actual: 40
+            })
+        );
+
+        // Greater difficult terrain takes over, at three times the cost
+        engine.state.base.greater_difficult_terrain.insert(Position::new(2, 0));
+        assert_eq!(stride(&mut engine, 2), 2);
+        assert_eq!(engine.state.base.creature(0).unwrap().movement_left, 5);
+    }
+
+    #[test]
+    fn paths_must_be_contiguous() {
+        let mut engine = engine(Position::new(0, 0));
+        engine.act(Action::stride(0, vec![Position::new(2, 0)]));
+        engine.step();
+        assert!(engine.is_idle());
+        assert_eq!(engine.state.base.creature(0).unwrap().position, Position::new(0, 0));
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use crate::check::Degree;
+use crate::engine::{Context, Filter, Meta, Resolved, State, Update, UpdateBuilder};
+use crate::modifier::Modifier;
+use crate::stats::Statistic;
+use crate::world::CreatureId;
+
+// Which of a creature's rolls a one-shot modifier waits for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum NextRoll {
+    Attack, // Any attack roll
+    Check(Statistic),
+}
+
+/** OneShot
+ *  A modifier to the creature's next roll of a kind, like "+1 to your next attack roll".
+ *  The roll uses it up whatever its result.
+ */
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct OneShot {
+    pub(crate) creature: CreatureId,
+    pub(crate) roll: NextRoll,
+    pub(crate) modifier: Modifier,
+}
+
+impl Meta {
+    pub(crate) fn add_one_shot(
+        &mut self,
+        creature: CreatureId,
+        roll: NextRoll,
+        modifier: Modifier,
+    ) {
+        self.one_shots.push(OneShot {
+            creature,
+            roll,
+            modifier,
+        });
+    }
+}
+
+// Clears every one-shot modifier waiting for the roll
+fn consume<T: 'static>(creature: CreatureId, roll: NextRoll) -> Filter<T> {
+    Arc::new(move |mut state: State<T>| {
+        state.meta.one_shots.retain(|o| (o.creature, o.roll) != (creature, roll));
+        state
+    })
+}
+
+impl Context<'_> {
+    /**
+     * As `roll_check`, with every one-shot modifier waiting for this roll stacked on.
+     * They stay in place until the update returned alongside the degree is applied.
+     */
+    pub(crate) fn roll_consuming<T: 'static>(
+        &mut self,
+        meta: &Meta,
+        creature: CreatureId,
+        roll: NextRoll,
+        modifier: i32,
+        dc: i32,
+    ) -> (Degree, Option<Update<T>>) {
+        let waiting = meta.one_shots.iter().filter(|o| (o.creature, o.roll) == (creature, roll));
+        let outer = self.modifiers.len();
+        self.modifiers.extend(waiting.map(|o| o.modifier));
+        let used = self.modifiers.len() > outer;
+        let degree = self.roll_check(modifier, dc);
+        self.modifiers.truncate(outer);
+        let update = used.then(|| {
+            UpdateBuilder::new()
+                .filter(consume(creature, roll))
+                .target(creature)
+                .resolved(Resolved::Resolved)
+                .build(self.next_id())
+        });
+        (degree, update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::dice::FixedDice;
+    use crate::engine::{Action, Engine, Tag};
+    use crate::modifier::ModifierKind;
+    use crate::world::{Creature, World, WorldBuilder};
+
+    #[test]
+    fn the_next_attack_uses_up_the_bonus() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 60, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        let status = Modifier::new(ModifierKind::Status, 1);
+        engine.state.meta.add_one_shot(0, NextRoll::Attack, status);
+        engine.state.meta.add_one_shot(0, NextRoll::Attack, Modifier::new(ModifierKind::Item, 1));
+        engine.state.meta.add_one_shot(1, NextRoll::Attack, status);
+        // 14 misses AC 16 unless both bonuses apply
+        engine.dice = Box::new(FixedDice::new(vec![14, 4]));
+        let strike = |engine: &mut Engine<World>| {
+            let club = Weapon::new("Club", WeaponGroup::Club, 1, 6);
+            engine.act(Action::strike(0, 1, club));
+            engine.step();
+            let hit = engine.updates[0].tags.contains(&Tag::Degree(Degree::Success));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            hit
+        };
+        assert!(strike(&mut engine));
+        assert_eq!(engine.state.meta.one_shots.len(), 1); // The ogre's is still waiting
+        engine.state.meta.actions_remaining = 3;
+        assert!(!strike(&mut engine));
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use crate::engine::{Base, Engine, Filter, State};
+use crate::predicate::Predicate;
+
+// A `Predicate` that can be shared, so forks keep their ongoing effects
+pub(crate) type Guard<T> = Arc<dyn Fn(&State<T>) -> bool + Send + Sync>;
+
+/** Ongoing
+ *  An effect that applies its filter again on every step, outside of any chain,
+ *  for as long as its guard holds, like a channelled spell.
+ *  The filter runs once per step, so it should add to the state a step's worth
+ *  (or set it outright) rather than assume it runs only once.
+ */
+#[derive(Clone)]
+pub(crate) struct Ongoing<T> {
+    pub(crate) filter: Filter<T>,
+    pub(crate) guard: Guard<T>,
+}
+
+impl<T: Base> Engine<T> {
+    pub(crate) fn add_ongoing(&mut self, filter: Filter<T>, guard: Predicate<T>) {
+        self.ongoing.push(Ongoing {
+            filter,
+            guard: Arc::from(guard),
+        });
+    }
+
+    /**
+     * Applies every ongoing effect whose guard still holds and commits the result.
+     * An effect is dropped the first time its guard fails, so it won't come back.
+     */
+    pub(crate) fn tick_ongoing(&mut self) {
+        let state = &self.state;
+        self.ongoing.retain(|ongoing| (ongoing.guard)(state));
+        if self.ongoing.is_empty() {
+            return;
+        }
+        let mut state = self.state.clone();
+        for ongoing in self.ongoing.iter() {
+            state = (ongoing.filter)(state);
+        }
+        self.commit(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::TestBase;
+
+    #[test]
+    fn ongoing_effects_stop_once_their_guard_fails() {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.add_ongoing(
+            Arc::new(|mut state: State<TestBase>| {
+                state.base.magics += 1;
+                state
+            }),
+            Box::new(|state| state.base.magics < 3),
+        );
+        for _ in 0..5 {
+            engine.step();
+        }
+        assert_eq!(engine.state.base.magics, 3);
+        assert!(engine.ongoing.is_empty());
+        // Even once the guard would hold again
+        engine.state.base.magics = 0;
+        engine.step();
+        assert_eq!(engine.state.base.magics, 0);
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use crate::check::roll_flat_check;
+use crate::engine::{Action, ActionResponse, Engine, Resolved, State, Tag, UpdateBuilder};
+use crate::world::{target_filter, typed_damage, Creature, CreatureId, DamageType, World};
+
+// The flat check that ends persistent damage, made after taking it
+pub(crate) const RECOVERY_DC: u8 = 15;
+// The flat check instead, once someone has helped, like patting out the flames
+pub(crate) const ASSISTED_DC: u8 = 10;
+
+// Damage a creature takes again at the end of each of its turns, like burning
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PersistentDamage {
+    pub(crate) kind: DamageType,
+    pub(crate) amount: i32,
+    pub(crate) assisted: bool, // Helped since the last flat check, so the next is `ASSISTED_DC`
+}
+
+impl Creature {
+    // Persistent damage of a type it already has doesn't stack; the higher amount counts
+    pub(crate) fn add_persistent_damage(&mut self, kind: DamageType, amount: i32) {
+        match self.persistent_damage.iter_mut().find(|held| held.kind == kind) {
+            Some(held) => held.amount = held.amount.max(amount),
+            None => self.persistent_damage.push(PersistentDamage {
+                kind,
+                amount,
+                assisted: false,
+            }),
+        }
+    }
+}
+
+impl Action<World> {
+    /**
+     * The creature's persistent damage, as its turn ends: each type deals its damage,
+     * then a flat check against `RECOVERY_DC`, or `ASSISTED_DC` if it was helped,
+     * ends it on a success. Help only lasts for the one check.
+     */
+    pub(crate) fn persistent_damage(creature: CreatureId) -> Self {
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            let Some(burning) = state.base.creature(creature) else {
+                return ActionResponse::Valid(vec![]);
+            };
+            let mut updates = vec![];
+            for held in burning.persistent_damage.clone() {
+                let PersistentDamage { kind, amount, .. } = held;
+                updates.push(
+                    UpdateBuilder::new()
+                        .filter(typed_damage(creature, amount, kind))
+                        .target(creature)
+                        .resolved(Resolved::Resolved)
+                        .build(context.next_id()),
+                );
+                let dc = if held.assisted { ASSISTED_DC } else { RECOVERY_DC };
+                let passed = roll_flat_check(context.dice, dc);
+                updates.push(
+                    UpdateBuilder::new()
+                        .filter(target_filter(creature, move |creature| {
+                            let effects = &mut creature.persistent_damage;
+                            if passed {
+                                effects.retain(|held| held.kind != kind);
+                            }
+                            for held in effects.iter_mut().filter(|held| held.kind == kind) {
+                                held.assisted = false;
+                            }
+                        }))
+                        .target(creature)
+                        .tag(Tag::FlatCheck { dc, passed })
+                        .resolved(Resolved::Resolved)
+                        .build(context.next_id()),
+                );
+            }
+            ActionResponse::Valid(updates)
+        }))
+        .named("Persistent Damage")
+    }
+}
+
+impl Engine<World> {
+    /**
+     * The helper works to end the target's persistent damage of that type,
+     * so its next flat check against it is at `ASSISTED_DC`.
+     * Returns false unless the helper is up and the target has that damage.
+     */
+    pub(crate) fn assist_persistent(
+        &mut self,
+        helper: CreatureId,
+        target: CreatureId,
+        kind: DamageType,
+    ) -> bool {
+        let world = &self.state.base;
+        let able = world.creature(helper).is_some_and(|helper| {
+            !helper.dead && !helper.conditions.iter().any(|c| c.is_incapacitating())
+        });
+        let burning = world
+            .creature(target)
+            .is_some_and(|target| target.persistent_damage.iter().any(|held| held.kind == kind));
+        if !able || !burning {
+            return false;
+        }
+        self.apply_filter(target_filter(target, move |creature| {
+            for held in creature.persistent_damage.iter_mut().filter(|held| held.kind == kind) {
+                held.assisted = true;
+            }
+        }));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dice::FixedDice;
+    use crate::engine::Engine;
+    use crate::world::WorldBuilder;
+
+    #[test]
+    fn a_successful_flat_check_puts_the_fire_out() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.add_persistent_damage(DamageType::Fire, 2);
+        fighter.add_persistent_damage(DamageType::Fire, 3);
+        let world = WorldBuilder::new().add_creature(0, fighter).build();
+        let mut engine = Engine::for_base(world);
+        engine.dice = Box::new(FixedDice::new(vec![14, 15]));
+        let mut burning = vec![];
+        for _ in 0..2 {
+            engine.act(Action::persistent_damage(0));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            let fighter = engine.state.base.creature(0).unwrap();
+            burning.push((fighter.hp, fighter.persistent_damage.len()));
+        }
+        // Only the higher of the two fires burns, until a 15 puts it out
+        assert_eq!(burning, vec![(17, 1), (14, 0)]);
+    }
+
+    #[test]
+    fn help_lowers_the_next_flat_check() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.add_persistent_damage(DamageType::Fire, 2);
+        let world = WorldBuilder::new()
+            .add_creature(0, fighter)
+            .add_creature(1, Creature::new("Cleric", 18, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.dice = Box::new(FixedDice::new(vec![12]));
+        let burning = |engine: &mut Engine<World>| {
+            engine.act(Action::persistent_damage(0));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            engine.state.base.creature(0).unwrap().persistent_damage.len()
+        };
+        // A 12 doesn't make DC 15, but does once the cleric has helped
+        assert_eq!(burning(&mut engine), 1);
+        assert!(!engine.assist_persistent(1, 0, DamageType::Acid));
+        assert!(engine.assist_persistent(1, 0, DamageType::Fire));
+        assert_eq!(burning(&mut engine), 0);
+        assert!(!engine.assist_persistent(1, 0, DamageType::Fire));
+    }
+}
@@ -0,0 +1,366 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::check::Degree;
+use crate::damage::DamageBreakdown;
+use crate::diff::Change;
+use crate::engine::{Base, Engine, Input, InputKind, Resolved, State, Tag, Update, UserInput};
+use crate::log::{LogEntry, LogSource};
+use crate::world::CreatureId;
+
+// What a policy sees when `run` is about to commit an update or is stopped on a prompt
+pub(crate) struct ResolveContext<'a, T> {
+    pub(crate) state: &'a State<T>,
+    pub(crate) update: Option<&'a Update<T>>, // The update about to commit, if any
+    pub(crate) input: Option<&'a UserInput>,  // The prompt waiting on an answer, if any
+}
+
+/** ResolutionPolicy
+ *  How much `run` automates: whether it hands control back to the host
+ *  before a commit or at a prompt.
+ */
+pub(crate) trait ResolutionPolicy<T> {
+    fn should_pause(&self, context: &ResolveContext<T>) -> bool;
+}
+
+/** InputPolicy
+ *  Answers prompts for a host that isn't there, as in batch simulations.
+ *  With one on the engine, `run` answers every prompt it can instead of stopping;
+ *  a prompt the policy has no answer for is left for the host as usual.
+ */
+pub(crate) trait InputPolicy<T>: Send + Sync {
+    fn answer(&self, state: &State<T>, input: &UserInput) -> Option<Input>;
+}
+
+// Picks the first target or option offered, and the lowest number allowed
+pub(crate) struct FirstLegal;
+
+impl<T> InputPolicy<T> for FirstLegal {
+    fn answer(&self, _: &State<T>, input: &UserInput) -> Option<Input> {
+        match &input.kind {
+            InputKind::Target(targets) => targets.first().copied().map(Input::Target),
+            InputKind::Number { min, max } => (min <= max).then_some(Input::Number(*min)),
+            InputKind::Choice(options) => (!options.is_empty()).then_some(Input::Choice(0)),
+        }
+    }
+}
+
+// Resolves everything it can, only stopping where stepping can't get any further
+pub(crate) struct Auto;
+
+// Stops before every commit, and at every prompt
+pub(crate) struct ConfirmEach;
+
+// Commits freely, stopping only to ask for input
+pub(crate) struct InputOnly;
+
+// Why `run` handed control back
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RunStatus {
+    Stuck,       // Idle, or waiting on a prompt or window, so stepping can't get further
+    Paused,      // The policy paused it
+    Interrupted, // The cancellation token was set
+}
+
+// One thing that happened during a run, for a narrative like "critical hit, 14 slashing"
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum OutcomeEvent {
+    Degree {
+        target: CreatureId,
+        degree: Degree,
+    },
+    Damage {
+        target: CreatureId,
+        breakdown: DamageBreakdown, // As rolled, before any resistance
+    },
+    Changed(Change), // Hit points and conditions, from the base's diff
+}
+
+/** RunOutcome
+ *  What `run` did: why it stopped, the state it left, and what happened on the way.
+ *  Degrees and damage come from the log in the order they committed, followed by
+ *  everything the run changed overall, so an HP change is its net result.
+ */
+#[derive(Clone)]
+pub(crate) struct RunOutcome<T> {
+    pub(crate) status: RunStatus,
+    pub(crate) final_state: State<T>,
+    pub(crate) events: Vec<OutcomeEvent>,
+}
+
+// The degrees and damage on each logged update; changes applied by hand don't say whose they are
+fn logged_events(log: &[LogEntry]) -> Vec<OutcomeEvent> {
+    let updates = log.iter().filter_map(|entry| match entry.source {
+        LogSource::Update { target, .. } if entry.skipped.is_none() => Some((target, entry)),
+        _ => None,
+    });
+    updates
+        .flat_map(|(target, entry)| {
+            entry.tags.iter().filter_map(move |tag| match tag {
+                Tag::Degree(degree) => Some(OutcomeEvent::Degree {
+                    target,
+                    degree: *degree,
+                }),
+                Tag::Damage(breakdown) => Some(OutcomeEvent::Damage {
+                    target,
+                    breakdown: breakdown.clone(),
+                }),
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+impl<T> ResolutionPolicy<T> for Auto {
+    fn should_pause(&self, _: &ResolveContext<T>) -> bool {
+        false
+    }
+}
+
+impl<T> ResolutionPolicy<T> for ConfirmEach {
+    fn should_pause(&self, _: &ResolveContext<T>) -> bool {
+        true
+    }
+}
+
+impl<T> ResolutionPolicy<T> for InputOnly {
+    fn should_pause(&self, context: &ResolveContext<T>) -> bool {
+        context.input.is_some()
+    }
+}
+
+impl<T: Base> Engine<T> {
+    /**
+     * Steps until the engine is idle, stuck on a prompt or window, or the policy pauses it.
+     * The engine's input policy, if it has one, gets the first say on each prompt.
+     * Running again after a pause before a commit goes ahead with that commit.
+     * `cancel` is checked between steps, so another thread can stop a long run;
+     * the engine is left between steps, and running again carries on from there.
+     * What happened along the way comes back with the status; see `RunOutcome`.
+     */
+    pub(crate) fn run(
+        &mut self,
+        policy: &dyn ResolutionPolicy<T>,
+        cancel: &AtomicBool,
+    ) -> RunOutcome<T> {
+        let (start, logged) = (self.state.clone(), self.log.len());
+        let status = self.resume(policy, cancel);
+        let mut events = logged_events(&self.log[logged..]);
+        let changes = T::diff(&start, &self.state).changes;
+        events.extend(changes.into_iter().map(OutcomeEvent::Changed));
+        RunOutcome {
+            status,
+            final_state: self.state.clone(),
+            events,
+        }
+    }
+
+    fn resume(&mut self, policy: &dyn ResolutionPolicy<T>, cancel: &AtomicBool) -> RunStatus {
+        loop {
+            if let Some(input) = &self.input {
+                let answering = self.input_policy.as_ref();
+                if let Some(answer) = answering.and_then(|p| p.answer(&self.state, input)) {
+                    self.provide_input(answer);
+                    continue;
+                }
+                let context = ResolveContext {
+                    state: &self.state,
+                    update: None,
+                    input: Some(input),
+                };
+                return match policy.should_pause(&context) {
+                    true => RunStatus::Paused,
+                    false => RunStatus::Stuck,
+                };
+            }
+            if self.is_idle() || self.window.is_some() {
+                return RunStatus::Stuck;
+            }
+            let next = self.updates.get(self.update);
+            let committing = next.filter(|update| {
+                self.action.is_none()
+                    && self.rule == 0
+                    && update.resolved == Resolved::Resolved
+                    && self.confirmed != Some(update.id)
+            });
+            if let Some(update) = committing {
+                let context = ResolveContext {
+                    state: &self.state,
+                    update: Some(update),
+                    input: None,
+                };
+                if policy.should_pause(&context) {
+                    self.confirmed = Some(update.id);
+                    return RunStatus::Paused;
+                }
+            }
+            if cancel.load(Ordering::Acquire) {
+                return RunStatus::Interrupted;
+            }
+            self.step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::dice::FixedDice;
+    use crate::engine::{Action, ActionResponse, RuleResponse, TestBase, UpdateBuilder};
+    use crate::world::{damage, Creature, World, WorldBuilder};
+
+    fn magics(count: usize) -> Action<TestBase> {
+        Action::new(Arc::new(move |_, context| {
+            let updates = (0..count)
+                .map(|_| {
+                    UpdateBuilder::new()
+                        .filter(Arc::new(|mut state: State<TestBase>| {
+                            state.base.magics += 1;
+                            state
+                        }))
+                        .resolved(Resolved::Resolved)
+                        .build(context.next_id())
+                })
+                .collect();
+            ActionResponse::Valid(updates)
+        }))
+    }
+
+    fn engine() -> Engine<TestBase> {
+        Engine::for_base(TestBase::default())
+    }
+
+    #[test]
+    fn confirm_each_pauses_before_every_commit() {
+        let mut engine = engine();
+        let never = AtomicBool::new(false);
+        engine.act(magics(2));
+        assert_eq!(engine.run(&ConfirmEach, &never).status, RunStatus::Paused);
+        assert_eq!(engine.updates.len(), 2);
+        assert_eq!(engine.state.base.magics, 0);
+        assert_eq!(engine.run(&ConfirmEach, &never).status, RunStatus::Paused);
+        assert_eq!(engine.state.base.magics, 1);
+        assert_eq!(engine.run(&ConfirmEach, &never).status, RunStatus::Stuck);
+        assert_eq!(engine.state.base.magics, 2);
+        assert!(engine.is_idle());
+
+        engine.act(magics(2));
+        assert_eq!(engine.run(&InputOnly, &never).status, RunStatus::Stuck);
+        assert_eq!(engine.state.base.magics, 4);
+
+        // A policy of its own can look at the state before the update commits
+        struct AtFive;
+        impl ResolutionPolicy<TestBase> for AtFive {
+            fn should_pause(&self, context: &ResolveContext<TestBase>) -> bool {
+                context.update.is_some() && context.state.base.magics == 5
+            }
+        }
+        engine.act(magics(2));
+        assert_eq!(engine.run(&AtFive, &never).status, RunStatus::Paused);
+        assert_eq!(engine.state.base.magics, 5);
+        assert_eq!(engine.run(&AtFive, &never).status, RunStatus::Stuck);
+        assert_eq!(engine.state.base.magics, 6);
+    }
+
+    #[test]
+    fn a_cancelled_run_stops_between_steps_and_can_resume() {
+        let mut engine = engine();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let token = cancel.clone();
+        // Cancels as if from elsewhere, while the rule looks at the third update
+        let seen = AtomicUsize::new(0);
+        engine.rules.push(Arc::new(move |_, _| {
+            if seen.fetch_add(1, Ordering::Relaxed) == 2 {
+                token.store(true, Ordering::Release);
+            }
+            RuleResponse::Skip
+        }));
+        engine.act(magics(5));
+        assert_eq!(engine.run(&Auto, &cancel).status, RunStatus::Interrupted);
+        assert!(engine.state.base.magics < 5);
+        assert!(!engine.is_idle());
+
+        cancel.store(false, Ordering::Release);
+        assert_eq!(engine.run(&Auto, &cancel).status, RunStatus::Stuck);
+        assert_eq!(engine.state.base.magics, 5);
+        assert!(engine.is_idle());
+    }
+
+    #[test]
+    fn headless_runs_answer_their_own_prompts() {
+        let mut world = World::default();
+        for (id, name) in [(1, "Goblin"), (2, "Orc")] {
+            world.creatures.insert(id, Creature::new(name, 10, 15));
+        }
+        let mut engine = Engine::for_base(world);
+        let zap = || {
+            Action::new(Arc::new(|_, context| match context.input {
+                Some(Input::Target(target)) => {
+                    let update = UpdateBuilder::new()
+                        .filter(damage(*target, 3))
+                        .target(*target)
+                        .resolved(Resolved::Resolved)
+                        .build(context.next_id());
+                    ActionResponse::Valid(vec![update])
+                }
+                _ => ActionResponse::RequestInput(UserInput {
+                    label: String::from("Zap whom?"),
+                    kind: InputKind::Target(vec![2, 1]),
+                }),
+            }))
+        };
+        let never = AtomicBool::new(false);
+        engine.act(zap());
+        assert_eq!(engine.run(&Auto, &never).status, RunStatus::Stuck);
+        assert!(engine.pending_input().is_some());
+        engine.cancel_action();
+
+        engine.input_policy = Some(Arc::new(FirstLegal));
+        engine.act(zap());
+        assert_eq!(engine.run(&Auto, &never).status, RunStatus::Stuck);
+        assert!(engine.is_idle());
+        assert_eq!(engine.state.base.creature(2).unwrap().hp, 7);
+        assert_eq!(engine.state.base.creature(1).unwrap().hp, 10);
+
+        let options = vec![String::from("Fire"), String::from("Cold")];
+        let choice = UserInput {
+            label: String::from("Which element?"),
+            kind: InputKind::Choice(options),
+        };
+        assert_eq!(FirstLegal.answer(&engine.state, &choice), Some(Input::Choice(0)));
+    }
+
+    #[test]
+    fn a_run_tells_what_happened() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.dice = Box::new(FixedDice::new(vec![17, 5]));
+        engine.act(Action::strike(0, 1, Weapon::new("Club", WeaponGroup::Club, 1, 6)));
+        let outcome = engine.run(&Auto, &AtomicBool::new(false));
+        assert_eq!(outcome.status, RunStatus::Stuck);
+        assert_eq!(outcome.final_state.base.creature(1).unwrap().hp, 3);
+        assert!(outcome.events.contains(&OutcomeEvent::Degree {
+            target: 1,
+            degree: Degree::Success
+        }));
+        let damage = outcome.events.iter().find_map(|event| match event {
+            OutcomeEvent::Damage { breakdown, .. } => Some(breakdown.total),
+            _ => None,
+        });
+        assert_eq!(damage, Some(5));
+        assert_eq!(
+            outcome.events.last(),
+            Some(&OutcomeEvent::Changed(Change::Hp {
+                creature: 1,
+                from: 8,
+                to: 3
+            }))
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::world::Creature;
+
+/** NonStackingPool
+ *  A value several effects can grant where only the best one counts, like temporary
+ *  hit points: a new grant replaces the current one only if it offers more.
+ *  Spending from the pool wears down whichever grant is current.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct NonStackingPool {
+    pub(crate) value: i32,
+    pub(crate) source: Option<String>, // What granted the current value; None once it's spent
+}
+
+impl NonStackingPool {
+    // Whether the grant took over the pool
+    pub(crate) fn grant(&mut self, source: &str, amount: i32) -> bool {
+        if amount <= self.value {
+            return false;
+        }
+        self.value = amount;
+        self.source = Some(String::from(source));
+        true
+    }
+
+    // Takes up to `amount` out of the pool, returning how much it covered
+    pub(crate) fn spend(&mut self, amount: i32) -> i32 {
+        let spent = amount.clamp(0, self.value);
+        self.value -= spent;
+        if self.value == 0 {
+            self.source = None;
+        }
+        spent
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = NonStackingPool::default();
+    }
+}
+
+impl Creature {
+    // Temporary hit points from different effects don't add up; the creature keeps the most
+    pub(crate) fn gain_temp_hp(&mut self, source: &str, amount: i32) -> bool {
+        self.temp_hp.grant(source, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_bigger_grant_replaces_the_pool() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        assert!(fighter.gain_temp_hp("False Life", 5));
+        assert!(!fighter.gain_temp_hp("Heroism", 3));
+        assert_eq!(fighter.temp_hp.value, 5);
+        assert_eq!(fighter.temp_hp.source.as_deref(), Some("False Life"));
+        assert!(fighter.gain_temp_hp("Stoneskin", 7));
+        assert_eq!(fighter.temp_hp.value, 7);
+        assert_eq!(fighter.temp_hp.source.as_deref(), Some("Stoneskin"));
+
+        fighter.lose_hp(9);
+        assert_eq!((fighter.temp_hp.value, fighter.hp), (0, 18));
+        assert_eq!(fighter.temp_hp.source, None);
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use crate::engine::{Action, ActionResponse, InvalidAction, State, Tag};
+use crate::world::{CreatureId, World};
+
+// A yes-or-no question about the state, built up from the combinators below
+pub(crate) type Predicate<T> = Box<dyn Fn(&State<T>) -> bool + Send + Sync>;
+
+pub(crate) fn all_of<T: 'static>(predicates: Vec<Predicate<T>>) -> Predicate<T> {
+    Box::new(move |state| predicates.iter().all(|predicate| predicate(state)))
+}
+
+pub(crate) fn any_of<T: 'static>(predicates: Vec<Predicate<T>>) -> Predicate<T> {
+    Box::new(move |state| predicates.iter().any(|predicate| predicate(state)))
+}
+
+pub(crate) fn not<T: 'static>(predicate: Predicate<T>) -> Predicate<T> {
+    Box::new(move |state| !predicate(state))
+}
+
+// Whether at least `amount` of the resource is left; see `filter::spend_resource`
+pub(crate) fn has_resource<T: 'static>(key: &str, amount: u32) -> Predicate<T> {
+    let key = String::from(key);
+    Box::new(move |state| state.meta.resources.get(&key).is_some_and(|left| *left >= amount))
+}
+
+// Whether the creature has a condition of the tag's kind, whatever its value
+pub(crate) fn has_tag(creature: CreatureId, tag: Tag) -> Predicate<World> {
+    Box::new(move |state| {
+        state
+            .base
+            .creature(creature)
+            .is_some_and(|c| c.conditions.iter().any(|condition| condition.same_kind(&tag)))
+    })
+}
+
+// Whether the creature is below the given fraction of its maximum hit points
+pub(crate) fn hp_below(creature: CreatureId, fraction: f32) -> Predicate<World> {
+    Box::new(move |state| {
+        state
+            .base
+            .creature(creature)
+            .is_some_and(|c| (c.hp as f32) < c.max_hp as f32 * fraction)
+    })
+}
+
+impl<T: 'static> Action<T> {
+    // The action is rejected with `BadPredicate` unless the predicate holds when it's taken
+    pub(crate) fn with_predicate(mut self, predicate: Predicate<T>) -> Self {
+        let generator = self.generator;
+        self.generator = Arc::new(move |state, context| {
+            if !predicate(state) {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            }
+            generator(state, context)
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::engine::{Engine, Meta, Resolved, UpdateBuilder};
+    use crate::filter::{refund_resource, spend_resource};
+    use crate::world::{heal, Creature, WorldBuilder};
+
+    fn state(hp: i32, conditions: Vec<Tag>) -> State<World> {
+        let mut ogre = Creature::new("Ogre", 50, 17);
+        ogre.hp = hp;
+        ogre.conditions = conditions;
+        let world = WorldBuilder::new().add_creature(0, ogre).build();
+        State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        }
+    }
+
+    #[test]
+    fn all_of_needs_every_predicate() {
+        let vulnerable = all_of(vec![has_tag(0, Tag::Flatfooted(None)), hp_below(0, 0.5)]);
+        assert!(vulnerable(&state(20, vec![Tag::Flatfooted(None)])));
+        assert!(!vulnerable(&state(30, vec![Tag::Flatfooted(None)])));
+        assert!(!vulnerable(&state(20, vec![Tag::Frightened(1)])));
+
+        let either = any_of(vec![has_tag(0, Tag::Frightened(2)), not(hp_below(0, 0.5))]);
+        assert!(either(&state(20, vec![Tag::Frightened(1)])));
+        assert!(either(&state(30, vec![])));
+        assert!(!either(&state(20, vec![])));
+    }
+
+    #[test]
+    fn focus_spells_spend_focus_points() {
+        let mut state = state(30, vec![]);
+        state.meta.resources.insert(String::from("focus"), 1);
+        let mut engine = Engine::new(state);
+        let lay_on_hands = || {
+            Action::new(Arc::new(|_, context| {
+                let mut update = |filter| {
+                    UpdateBuilder::new()
+                        .filter(filter)
+                        .resolved(Resolved::Resolved)
+                        .build(context.next_id())
+                };
+                ActionResponse::Valid(vec![update(spend_resource("focus", 1)), update(heal(0, 6))])
+            }))
+            .with_predicate(has_resource("focus", 1))
+        };
+        engine.act(lay_on_hands());
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!(engine.rejected, None);
+        assert_eq!(engine.state.meta.resources["focus"], 0);
+        assert_eq!(engine.state.base.creature(0).unwrap().hp, 36);
+
+        engine.act(lay_on_hands());
+        engine.step();
+        assert_eq!(engine.rejected, Some(InvalidAction::BadPredicate));
+
+        // A spell that fizzles can hand its point back
+        let state = refund_resource("focus", 1)(engine.state.clone());
+        assert_eq!(state.meta.resources["focus"], 1);
+    }
+
+    #[test]
+    fn actions_are_rejected_while_their_predicate_fails() {
+        let mut state = state(40, vec![]);
+        state.base.creatures.insert(1, Creature::new("Fighter", 20, 18));
+        let mut engine = Engine::new(state);
+        let finisher = || {
+            let pick = Weapon::new("Pick", WeaponGroup::Pick, 1, 6);
+            Action::strike(1, 0, pick).with_predicate(hp_below(0, 0.5))
+        };
+        engine.act(finisher());
+        engine.step();
+        assert_eq!(engine.rejected, Some(InvalidAction::BadPredicate));
+
+        engine.state.base.creature_mut(0).unwrap().hp = 20;
+        engine.act(finisher());
+        engine.step();
+        assert_eq!(engine.rejected, None);
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use crate::engine::{Action, ActionResponse, InvalidAction, Resolved, State, Tag, UpdateBuilder};
+use crate::world::{clear_condition, CreatureId, World};
+
+impl Action<World> {
+    // Stand: a single action that gets a prone creature back on its feet
+    pub(crate) fn stand(creature: CreatureId) -> Self {
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            let prone = state.base.creature(creature).map(|c| c.conditions.contains(&Tag::Prone));
+            if prone != Some(true) {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            }
+            let update = UpdateBuilder::new()
+                .filter(clear_condition(creature, Tag::Prone))
+                .target(creature)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+        .with_cost(1)
+        .named("Stand")
+        .by(creature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::WeaponGroup;
+    use crate::effective::StatKind;
+    use crate::engine::Engine;
+    use crate::stats::Statistic;
+    use crate::world::{Creature, WorldBuilder};
+
+    #[test]
+    fn prone_creatures_fight_worse_until_they_stand() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18).with_condition(Tag::Prone))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::new_encounter(world, vec![(0, 20), (1, 10)]);
+        let attack = StatKind::Check(Statistic::Weapon(WeaponGroup::Sword));
+        let (_, itemized) = engine.state.effective(0, attack, None);
+        assert_eq!(itemized[1], (String::from("prone"), -2));
+        let fighter = engine.state.base.creature(0).unwrap();
+        // Easier to hit up close, but not from across the room
+        assert_eq!(fighter.ac_against_attack(1, true), 16);
+        assert_eq!(fighter.ac_against_attack(1, false), 18);
+
+        engine.act(Action::stand(0));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert!(engine.state.base.creature(0).unwrap().conditions.is_empty());
+        assert_eq!(engine.state.meta.actions_remaining, 2);
+        assert_eq!(engine.state.base.creature(0).unwrap().ac_against(1), 18);
+    }
+}
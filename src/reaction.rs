@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use crate::engine::{Action, Base, Engine, Meta, State, Tag};
+use crate::tags::TagSet;
+use crate::world::CreatureId;
+
+// Both receive the tag that actually triggered the reaction
+pub(crate) type ReactionCheck<T> = Arc<dyn Fn(&State<T>, &Tag) -> bool + Send + Sync>;
+pub(crate) type ReactionBuilder<T> = Arc<dyn Fn(&State<T>, &Tag) -> Action<T> + Send + Sync>;
+
+/** Reaction
+ *  An action a creature may take in response to a trigger.
+ *  Any tag of the same kind as `trigger` can set it off, if `eligible` agrees.
+ *  The engine only offers it; the owner decides whether to use it.
+ */
+#[derive(Clone)]
+pub(crate) struct Reaction<T> {
+    pub(crate) owner: CreatureId,
+    pub(crate) name: String,
+    pub(crate) trigger: Tag,
+    pub(crate) eligible: ReactionCheck<T>,
+    pub(crate) build: ReactionBuilder<T>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ReactionOption {
+    pub(crate) reaction: usize, // Index into the engine's reactions
+    pub(crate) owner: CreatureId,
+    pub(crate) name: String,
+    pub(crate) trigger: Tag,
+}
+
+impl Meta {
+    // Each creature gets one reaction, back when its next turn starts
+    pub(crate) fn reaction_available(&self, creature: CreatureId) -> bool {
+        !self.reactions_used.contains(&creature)
+    }
+}
+
+impl<T: Base> Engine<T> {
+    fn reaction_options(&self, trigger: &Tag) -> Vec<ReactionOption> {
+        self.reactions
+            .iter()
+            .enumerate()
+            .filter(|(_, reaction)| self.state.meta.reaction_available(reaction.owner))
+            .filter(|(_, reaction)| reaction.trigger.same_kind(trigger))
+            .filter(|(_, reaction)| (reaction.eligible)(&self.state, trigger))
+            .map(|(index, reaction)| ReactionOption {
+                reaction: index,
+                owner: reaction.owner,
+                name: reaction.name.clone(),
+                trigger: trigger.clone(),
+            })
+            .collect()
+    }
+
+    /**
+     * Lists every reaction the trigger makes eligible and pauses the engine
+     * until one is used or all are declined.
+     * Creatures that have already reacted since their turn aren't offered any.
+     * Nothing is opened if no reaction is eligible.
+     */
+    pub(crate) fn open_reaction_window(&mut self, trigger: Tag) -> Vec<ReactionOption> {
+        let options = self.reaction_options(&trigger);
+        if !options.is_empty() {
+            self.window = Some(options.clone());
+        }
+        options
+    }
+
+    // Opens one window for everything the tags of a processed update trigger
+    pub(crate) fn provoke(&mut self, tags: &TagSet) {
+        let options: Vec<ReactionOption> =
+            tags.iter().flat_map(|tag| self.reaction_options(tag)).collect();
+        if !options.is_empty() {
+            self.window = Some(options);
+        }
+    }
+
+    /**
+     * Fires the chosen option from the open window.
+     * Its updates are queued ahead of the rest of the chain.
+     * Returns false if no window is open or the choice isn't in it.
+     */
+    pub(crate) fn use_reaction(&mut self, choice: usize) -> bool {
+        let Some(option) = self.window.as_ref().and_then(|options| options.get(choice)) else {
+            return false;
+        };
+        let action = (self.reactions[option.reaction].build)(&self.state, &option.trigger);
+        self.state.meta.reactions_used.push(option.owner);
+        self.window = None;
+        self.action = Some(action);
+        true
+    }
+
+    pub(crate) fn decline_reactions(&mut self) {
+        self.window = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{ActionResponse, Filter, Resolved, TestBase, Update};
+    use crate::metadata::Metadata;
+    use crate::world::{Creature, WorldBuilder};
+
+    fn engine() -> Engine<TestBase> {
+        Engine::for_base(TestBase::default())
+    }
+
+    fn update(id: usize, tags: TagSet, filter: Filter<TestBase>) -> Update<TestBase> {
+        Update {
+            filter,
+            id,
+            target: 0,
+            tags,
+            applies: TagSet::new(),
+            resolved: Resolved::Resolved,
+            meta: Metadata::new(),
+            outcome: Default::default(),
+        }
+    }
+
+    fn reaction(owner: CreatureId, name: &str, woos: i32) -> Reaction<TestBase> {
+        Reaction {
+            owner,
+            name: String::from(name),
+            trigger: Tag::Manipulate,
+            eligible: Arc::new(|_, _| true),
+            build: Arc::new(move |_, _| {
+                Action::new(Arc::new(move |_, _| {
+                    ActionResponse::Valid(vec![update(
+                        10,
+                        TagSet::new(),
+                        Arc::new(move |mut state| {
+                            // Reactions land before the rest of the chain
+                            assert_eq!(state.base.magics, 1);
+                            state.base.woos = woos;
+                            state
+                        }),
+                    )])
+                }))
+            }),
+        }
+    }
+
+    fn add_magic() -> Filter<TestBase> {
+        Arc::new(|mut state| {
+            state.base.magics += 1;
+            state
+        })
+    }
+
+    #[test]
+    fn window_offers_every_eligible_reaction() {
+        let mut engine = engine();
+        engine.reactions.push(reaction(1, "Attack of Opportunity", 1));
+        engine.reactions.push(reaction(2, "Reactive Strike", 2));
+        engine.updates.push(update(1, TagSet::new(), add_magic()));
+        engine.updates.push(update(2, TagSet::new(), add_magic()));
+        engine.step();
+
+        let options = engine.open_reaction_window(Tag::Manipulate);
+        assert_eq!(
+            options.iter().map(|o| o.owner).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        engine.step(); // Paused until the window is answered
+        assert_eq!(engine.update, 1);
+
+        assert!(engine.use_reaction(1));
+        for _ in 0..3 {
+            engine.step();
+        }
+        assert_eq!(engine.state.base.woos, 2);
+        assert_eq!(engine.state.base.magics, 2);
+    }
+
+    #[test]
+    fn a_used_reaction_is_not_offered_again() {
+        let mut engine = engine();
+        engine.reactions.push(reaction(1, "Attack of Opportunity", 1));
+        engine.reactions.push(reaction(2, "Reactive Strike", 2));
+        engine.updates.push(update(1, TagSet::new(), add_magic()));
+        engine.step();
+        engine.open_reaction_window(Tag::Manipulate);
+        assert!(engine.use_reaction(0));
+        let owners: Vec<CreatureId> = engine
+            .open_reaction_window(Tag::Manipulate)
+            .iter()
+            .map(|option| option.owner)
+            .collect();
+        assert_eq!(owners, vec![2]);
+
+        // Its next turn gives it back
+        let world = WorldBuilder::new().add_creature(1, Creature::new("Fighter", 20, 18)).build();
+        let mut turns = Engine::new(State {
+            meta: engine.state.meta.clone(),
+            base: world,
+            pre_chain: None,
+        });
+        turns.act(Action::start_turn(1));
+        while !turns.is_idle() {
+            turns.step();
+        }
+        assert!(turns.state.meta.reaction_available(1));
+    }
+
+    #[test]
+    fn declining_continues_the_chain() {
+        let mut engine = engine();
+        engine.reactions.push(reaction(1, "Attack of Opportunity", 1));
+        engine.updates.push(update(1, TagSet::new(), add_magic()));
+        engine.updates.push(update(2, TagSet::new(), add_magic()));
+        engine.step();
+        assert_eq!(engine.open_reaction_window(Tag::Manipulate).len(), 1);
+        engine.decline_reactions();
+        engine.step();
+        assert_eq!(engine.state.base.woos, 0);
+        assert_eq!(engine.state.base.magics, 2);
+    }
+}
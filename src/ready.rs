@@ -0,0 +1,116 @@
+use crate::engine::{Action, Base, Engine, Tag};
+use crate::tags::TagSet;
+use crate::world::CreatureId;
+
+/** Readied
+ *  An action set up with the Ready activity, waiting for its trigger.
+ *  Like a reaction, any tag of the same kind as `trigger` sets it off.
+ */
+#[derive(Clone)]
+pub(crate) struct Readied<T> {
+    pub(crate) owner: CreatureId,
+    pub(crate) trigger: Tag,
+    pub(crate) action: Action<T>,
+}
+
+impl<T: Base> Engine<T> {
+    /**
+     * Holds the action until an update carrying the trigger is processed.
+     * It fires as a reaction, so it costs no actions then,
+     * and it lapses if the owner's next turn starts first.
+     */
+    pub(crate) fn ready_action(&mut self, owner: CreatureId, trigger: Tag, action: Action<T>) {
+        self.readied.push(Readied {
+            owner,
+            trigger,
+            action: action.with_cost(0),
+        });
+    }
+
+    // Fires the first readied action the tags trigger, queued ahead of the rest of the chain
+    pub(crate) fn trigger_readied(&mut self, tags: &TagSet) {
+        let started = |owner| tags.contains(&Tag::TurnStart(owner));
+        self.readied.retain(|readied| !started(readied.owner));
+        let Some(at) = self
+            .readied
+            .iter()
+            .position(|readied| tags.iter().any(|tag| readied.trigger.same_kind(tag)))
+        else {
+            return;
+        };
+        self.action = Some(self.readied.remove(at).action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::engine::{ActionResponse, Resolved, State, TestBase, UpdateBuilder};
+
+    fn engine() -> Engine<TestBase> {
+        Engine::for_base(TestBase::default())
+    }
+
+    fn tagged(tag: Tag) -> Action<TestBase> {
+        Action::new(Arc::new(move |_, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(|mut state: State<TestBase>| {
+                    state.base.magics += 1;
+                    state
+                }))
+                .tag(tag.clone())
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+    }
+
+    fn woo() -> Action<TestBase> {
+        Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(|mut state: State<TestBase>| {
+                    state.base.woos += 1;
+                    state
+                }))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+        .with_cost(2)
+    }
+
+    fn run(engine: &mut Engine<TestBase>, action: Action<TestBase>) {
+        engine.act(action);
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    #[test]
+    fn readied_actions_fire_on_their_trigger() {
+        let mut engine = engine();
+        engine.ready_action(1, Tag::Manipulate, woo());
+        run(&mut engine, tagged(Tag::Frightened(1)));
+        assert_eq!(engine.state.base.woos, 0);
+        run(&mut engine, tagged(Tag::Manipulate));
+        assert_eq!(engine.state.base.woos, 1);
+        assert_eq!(engine.state.meta.actions_remaining, 3);
+        // Used up once fired
+        run(&mut engine, tagged(Tag::Manipulate));
+        assert_eq!(engine.state.base.woos, 1);
+    }
+
+    #[test]
+    fn readied_actions_lapse_at_the_owners_next_turn() {
+        let mut engine = engine();
+        engine.ready_action(1, Tag::Manipulate, woo());
+        run(&mut engine, tagged(Tag::TurnStart(2)));
+        assert_eq!(engine.readied.len(), 1);
+        run(&mut engine, tagged(Tag::TurnStart(1)));
+        assert!(engine.readied.is_empty());
+        run(&mut engine, tagged(Tag::Manipulate));
+        assert_eq!(engine.state.base.woos, 0);
+    }
+}
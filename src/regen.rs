@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use crate::engine::{Action, ActionResponse, Resolved, Rule, RuleResponse, Tag, UpdateBuilder};
+use crate::world::{heal, Creature, DamageType, World};
+
+/** Regen
+ *  Regeneration: healing at the start of each turn, unless the creature
+ *  has taken one of the deactivating damage types since its last turn.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Regen {
+    pub(crate) amount: i32,
+    pub(crate) deactivated_by: Vec<DamageType>,
+}
+
+impl Creature {
+    pub(crate) fn regenerating(&self) -> bool {
+        self.regeneration.as_ref().is_some_and(|regen| {
+            !regen.deactivated_by.iter().any(|kind| self.damage_taken.contains(kind))
+        })
+    }
+
+    // Out of HP and not regenerating; down, though only dying until `dead` is set
+    pub(crate) fn is_down(&self) -> bool {
+        self.hp == 0 && !self.regenerating()
+    }
+}
+
+// Heals each regenerating creature as its turn starts
+pub(crate) fn regeneration() -> Rule<World> {
+    Arc::new(|state, update| {
+        let Some(id) = update.tags.iter().find_map(|tag| match tag {
+            Tag::TurnStart(id) => Some(*id),
+            _ => None,
+        }) else {
+            return RuleResponse::Skip;
+        };
+        let Some(creature) = state.base.creature(id) else {
+            return RuleResponse::Skip;
+        };
+        let Some(regen) = creature.regeneration.as_ref().filter(|_| creature.regenerating()) else {
+            return RuleResponse::Skip;
+        };
+        let amount = regen.amount;
+        RuleResponse::Inject(Action::new(Arc::new(move |_, context| {
+            let update = UpdateBuilder::new()
+                .filter(heal(id, amount))
+                .target(id)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        })))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+    use crate::world::{typed_damage, WorldBuilder};
+
+    fn troll() -> Engine<World> {
+        let mut troll = Creature::new("Troll", 115, 20);
+        troll.regeneration = Some(Regen {
+            amount: 20,
+            deactivated_by: vec![DamageType::Acid, DamageType::Fire],
+        });
+        let world = WorldBuilder::new().add_creature(0, troll).build();
+        let mut engine = Engine::for_base(world);
+        engine.rules.push(regeneration());
+        engine
+    }
+
+    fn run(engine: &mut Engine<World>, action: Action<World>) {
+        engine.act(action);
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    fn hit(engine: &mut Engine<World>, amount: i32, kind: DamageType) {
+        let update = UpdateBuilder::new()
+            .filter(typed_damage(0, amount, kind))
+            .resolved(Resolved::Resolved)
+            .build(engine.next_id());
+        engine.updates.push(update);
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    #[test]
+    fn regeneration_heals_at_turn_start() {
+        let mut engine = troll();
+        hit(&mut engine, 115, DamageType::Slashing);
+        let troll = engine.state.base.creature(0).unwrap();
+        assert_eq!(troll.hp, 0);
+        assert!(!troll.is_down());
+
+        run(&mut engine, Action::start_turn(0));
+        assert_eq!(engine.state.base.creature(0).unwrap().hp, 20);
+    }
+
+    #[test]
+    fn fire_suppresses_regeneration_for_a_turn() {
+        let mut engine = troll();
+        hit(&mut engine, 40, DamageType::Fire);
+        run(&mut engine, Action::start_turn(0));
+        assert_eq!(engine.state.base.creature(0).unwrap().hp, 75);
+
+        // Fire taken before this turn no longer counts
+        run(&mut engine, Action::start_turn(0));
+        assert_eq!(engine.state.base.creature(0).unwrap().hp, 95);
+
+        hit(&mut engine, 95, DamageType::Acid);
+        assert!(engine.state.base.creature(0).unwrap().is_down());
+    }
+}
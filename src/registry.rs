@@ -0,0 +1,223 @@
+use crate::engine::{Action, Base, Engine, EngineStatus, State};
+
+// Enough steps for any real activity; only a chain that never settles gets cut off
+const STEP_LIMIT: usize = 1000;
+
+/** EconomySummary
+ *  What the creature whose turn it is has left to spend, and which registered
+ *  actions still fit in it, like "1 action and your reaction: Strike".
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct EconomySummary {
+    pub(crate) actions: u8,
+    pub(crate) reaction: bool, // False outside of any creature's turn
+    pub(crate) affordable: Vec<String>,
+}
+
+// How a registered action turns out, tried out in a fork
+enum Trial<T> {
+    Illegal,
+    NeedsInput,
+    Outcome(Box<State<T>>),
+}
+
+impl<T: Base> Engine<T> {
+    pub(crate) fn register(&mut self, name: &str, action: Action<T>) {
+        let action = match action.name {
+            Some(_) => action,
+            None => action.named(name),
+        };
+        self.registry.push((String::from(name), action));
+    }
+
+    /**
+     * Runs the action to completion in a fork, declining any reaction windows.
+     * The engine itself is untouched.
+     */
+    fn trial(&self, action: Action<T>, branch: u64) -> Trial<T> {
+        if action.cost > self.state.meta.actions_remaining {
+            return Trial::Illegal;
+        }
+        let mut fork = self.fork(branch);
+        fork.act(action);
+        for _ in 0..STEP_LIMIT {
+            match fork.status() {
+                EngineStatus::AwaitingInput(_) => return Trial::NeedsInput,
+                EngineStatus::AwaitingReactions(_) => fork.decline_reactions(),
+                EngineStatus::Idle | EngineStatus::Error(_) => break,
+                EngineStatus::ActionPending | EngineStatus::Resolving => fork.step(),
+            }
+        }
+        match fork.rejected {
+            Some(_) => Trial::Illegal,
+            None => Trial::Outcome(Box::new(fork.state)),
+        }
+    }
+
+    fn trials(&self) -> impl Iterator<Item = (&String, Trial<T>)> + '_ {
+        self.registry
+            .iter()
+            .enumerate()
+            .map(|(branch, (name, action))| (name, self.trial(action.clone(), branch as u64)))
+    }
+
+    // The registered actions that can be taken right now, by name
+    pub(crate) fn legal_actions(&self) -> Vec<String> {
+        self.trials()
+            .filter(|(_, trial)| !matches!(trial, Trial::Illegal))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    // The state the action would leave behind, if it is legal and needs no input
+    pub(crate) fn preview(&self, action: Action<T>) -> Option<State<T>> {
+        match self.trial(action, 0) {
+            Trial::Outcome(state) => Some(*state),
+            _ => None,
+        }
+    }
+
+    /**
+     * The resulting state of every legal registered action, for scoring them.
+     * Actions that would prompt for input are left to `actions_needing_input`.
+     */
+    pub(crate) fn enumerate_outcomes(&self) -> Vec<(String, State<T>)> {
+        self.trials()
+            .filter_map(|(name, trial)| match trial {
+                Trial::Outcome(state) => Some((name.clone(), *state)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn actions_needing_input(&self) -> Vec<String> {
+        self.trials()
+            .filter(|(_, trial)| matches!(trial, Trial::NeedsInput))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    // Legal actions already cost no more than is left, including those that will ask for input
+    pub(crate) fn remaining_economy(&self) -> EconomySummary {
+        let meta = &self.state.meta;
+        EconomySummary {
+            actions: meta.actions_remaining,
+            reaction: T::current_turn(&self.state).is_some_and(|id| meta.reaction_available(id)),
+            affordable: self.legal_actions(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::engine::{
+        ActionResponse, Filter, InputKind, InvalidAction, Resolved, TestBase, UpdateBuilder,
+        UserInput,
+    };
+    use crate::world::{Creature, WorldBuilder};
+
+    fn action(filter: fn() -> Filter<TestBase>) -> Action<TestBase> {
+        Action::new(Arc::new(move |_, context| {
+            let update = UpdateBuilder::new()
+                .filter(filter())
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+        .with_cost(1)
+    }
+
+    fn engine() -> Engine<TestBase> {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.register(
+            "Magic",
+            action(|| {
+                Arc::new(|mut state| {
+                    state.base.magics += 1;
+                    state
+                })
+            }),
+        );
+        engine.register(
+            "Woo",
+            action(|| {
+                Arc::new(|mut state| {
+                    state.base.woos += 1;
+                    state
+                })
+            }),
+        );
+        engine.register(
+            "Fizzle",
+            Action::new(Arc::new(|_, _| ActionResponse::Invalid(InvalidAction::BadPredicate))),
+        );
+        engine.register(
+            "Choose",
+            Action::new(Arc::new(|_, _| {
+                ActionResponse::RequestInput(UserInput {
+                    label: String::from("Pick a number"),
+                    kind: InputKind::Number { min: 1, max: 3 },
+                })
+            })),
+        );
+        engine
+    }
+
+    #[test]
+    fn outcomes_cover_each_legal_action() {
+        let engine = engine();
+        let outcomes = engine.enumerate_outcomes();
+        let summary: Vec<(&str, u8, i32)> = outcomes
+            .iter()
+            .map(|(name, state)| (name.as_str(), state.base.magics, state.base.woos))
+            .collect();
+        assert_eq!(summary, vec![("Magic", 1, 0), ("Woo", 0, 1)]);
+        assert!(outcomes.iter().all(|(_, state)| state.meta.actions_remaining == 2));
+        let preview = |name: &str| {
+            let (_, action) = engine.registry.iter().find(|(n, _)| n == name).unwrap();
+            engine.preview(action.clone()).map(|state| state.base.woos)
+        };
+        assert_eq!(preview("Woo"), Some(1));
+        assert_eq!(preview("Choose"), None);
+        assert_eq!(engine.actions_needing_input(), vec![String::from("Choose")]);
+        assert_eq!(engine.legal_actions(), vec!["Magic", "Woo", "Choose"]);
+        // Nothing happened to the engine itself
+        assert_eq!(engine.state.base.magics, 0);
+        assert!(engine.is_idle());
+    }
+
+    #[test]
+    fn unaffordable_actions_are_not_legal() {
+        let mut engine = engine();
+        engine.state.meta.actions_remaining = 0;
+        assert_eq!(engine.legal_actions(), vec!["Choose"]);
+        assert!(engine.enumerate_outcomes().is_empty());
+    }
+
+    #[test]
+    fn only_what_fits_in_the_last_action_is_affordable() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::new_encounter(world, vec![(0, 20), (1, 10)]);
+        let sword = || Weapon::new("Longsword", WeaponGroup::Sword, 1, 8);
+        engine.register("Strike", Action::strike(0, 1, sword()));
+        engine.register("Double Slice", Action::double_slice(0, 1, [sword(), sword()], 0));
+        engine.state.meta.actions_remaining = 1;
+        assert_eq!(
+            engine.remaining_economy(),
+            EconomySummary {
+                actions: 1,
+                reaction: true,
+                affordable: vec![String::from("Strike")],
+            }
+        );
+        engine.state.meta.reactions_used.push(0);
+        assert!(!engine.remaining_economy().reaction);
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use crate::engine::{Action, ActionResponse, Update};
+
+pub(crate) type Repetition<T> = Box<dyn Fn(usize) -> Action<T> + Send + Sync>;
+// Whether the activity ends after the repetition that made these updates
+pub(crate) type StopSignal<T> = Box<dyn Fn(&[Update<T>]) -> bool + Send + Sync>;
+
+impl<T: 'static> Action<T> {
+    // `count` sub-actions in one chain, each built from its index
+    pub(crate) fn repeat(count: usize, per_iter: Repetition<T>) -> Self {
+        Action::repeat_until(count, per_iter, Box::new(|_| false))
+    }
+
+    /**
+     * As `repeat`, but finishing early once `stop` says so about a repetition's
+     * updates, like a critical hit that ends the activity.
+     * The whole activity costs what its repetitions would and is targeted as the first is.
+     * Each repetition rolls with only its own modifiers, so a penalty doesn't carry over.
+     */
+    pub(crate) fn repeat_until(count: usize, per_iter: Repetition<T>, stop: StopSignal<T>) -> Self {
+        let cost = (0..count).map(|i| per_iter(i).cost).sum();
+        let targeting = (count > 0).then(|| per_iter(0).targeting).flatten();
+        let mut repeated = Action::new(Arc::new(move |state, context| {
+            let mut updates = vec![];
+            for i in 0..count {
+                let outer = context.modifiers.clone();
+                let response = per_iter(i).apply(state, context);
+                context.modifiers = outer;
+                match response {
+                    ActionResponse::Valid(repetition) => {
+                        let done = stop(&repetition);
+                        updates.extend(repetition);
+                        if done {
+                            break;
+                        }
+                    }
+                    other => return other,
+                }
+            }
+            ActionResponse::Valid(updates)
+        }))
+        .with_cost(cost);
+        repeated.targeting = targeting;
+        repeated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::Degree;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::dice::FixedDice;
+    use crate::engine::{Engine, Tag};
+    use crate::world::{Creature, World, WorldBuilder};
+
+    fn engine() -> Engine<World> {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Ogre", 60, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        // Each attack rolls a 16 and, on a hit, a 4 for damage
+        engine.dice = Box::new(FixedDice::new(vec![16, 4]));
+        engine
+    }
+
+    fn strike(attack: usize) -> Action<World> {
+        Action::strike_as(0, 1, Weapon::new("Longsword", WeaponGroup::Sword, 1, 8), attack)
+    }
+
+    fn degrees(engine: &mut Engine<World>) -> Vec<Degree> {
+        engine.step();
+        engine
+            .updates
+            .iter()
+            .flat_map(|update| update.tags.iter())
+            .filter_map(|tag| match tag {
+                Tag::Degree(degree) => Some(*degree),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_second_strike_takes_the_penalty() {
+        let mut engine = engine();
+        engine.act(Action::repeat(2, Box::new(strike)));
+        assert_eq!(degrees(&mut engine), vec![Degree::Success, Degree::Failure]);
+        assert_eq!(engine.state.meta.actions_remaining, 1);
+    }
+
+    #[test]
+    fn repetitions_can_end_the_activity_early() {
+        let mut engine = engine();
+        let hit = |updates: &[Update<World>]| {
+            updates.iter().any(|u| u.tags.contains(&Tag::Degree(Degree::Success)))
+        };
+        engine.act(Action::repeat_until(3, Box::new(strike), Box::new(hit)));
+        assert_eq!(degrees(&mut engine), vec![Degree::Success]);
+    }
+}
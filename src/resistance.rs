@@ -0,0 +1,109 @@
+use crate::world::{Creature, DamageType};
+
+// Special metals a weapon can be made of, which some resistances don't stop
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Material {
+    ColdIron,
+    Silver,
+}
+
+// The damage a resistance covers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum ResistanceScope {
+    Type(DamageType),
+    Physical, // Bludgeoning, piercing and slashing
+    All,
+}
+
+// Damage that gets through a resistance it would otherwise fall under
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Bypass {
+    Type(DamageType),
+    Material(Material),
+}
+
+/** Resistance
+ *  Taken off each instance of damage in its scope, unless the instance is one of
+ *  the exceptions, as in "resistance 5 to physical (except silver)".
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Resistance {
+    pub(crate) scope: ResistanceScope,
+    pub(crate) amount: i32,
+    pub(crate) except: Vec<Bypass>,
+}
+
+impl Resistance {
+    pub(crate) fn new(scope: ResistanceScope, amount: i32) -> Self {
+        Self {
+            scope,
+            amount,
+            except: vec![],
+        }
+    }
+
+    pub(crate) fn except(mut self, bypass: Bypass) -> Self {
+        self.except.push(bypass);
+        self
+    }
+
+    pub(crate) fn applies(&self, kind: DamageType, material: Option<Material>) -> bool {
+        let covered = match self.scope {
+            ResistanceScope::Type(covered) => covered == kind,
+            ResistanceScope::Physical => kind.is_physical(),
+            ResistanceScope::All => true,
+        };
+        covered
+            && !self.except.iter().any(|bypass| match bypass {
+                Bypass::Type(bypassing) => *bypassing == kind,
+                Bypass::Material(bypassing) => Some(*bypassing) == material,
+            })
+    }
+}
+
+impl Creature {
+    // Only the highest resistance that applies counts
+    pub(crate) fn resistance_to(&self, kind: DamageType, material: Option<Material>) -> i32 {
+        self.resistances
+            .iter()
+            .filter(|resistance| resistance.applies(kind, material))
+            .map(|resistance| resistance.amount)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Meta, State};
+    use crate::world::{typed_material_damage, WorldBuilder};
+
+    #[test]
+    fn silver_gets_through_physical_resistance_except_silver() {
+        let mut werewolf = Creature::new("Werewolf", 60, 19);
+        let physical = Resistance::new(ResistanceScope::Physical, 5);
+        werewolf.resistances.push(physical.except(Bypass::Material(Material::Silver)));
+        let world = WorldBuilder::new().add_creature(0, werewolf).build();
+        let state = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+        let taken = |kind, material| {
+            let after = typed_material_damage(0, 8, kind, material)(state.clone());
+            60 - after.base.creature(0).unwrap().hp
+        };
+        assert_eq!(taken(DamageType::Slashing, None), 3);
+        assert_eq!(taken(DamageType::Slashing, Some(Material::Silver)), 8);
+        assert_eq!(taken(DamageType::Fire, None), 8);
+
+        let all = Resistance::new(ResistanceScope::All, 3).except(Bypass::Type(DamageType::Force));
+        assert!(all.applies(DamageType::Fire, Some(Material::ColdIron)));
+        assert!(!all.applies(DamageType::Force, None));
+    }
+}
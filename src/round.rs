@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use crate::engine::{Action, Base, Engine, State};
+use crate::world::{CreatureId, World};
+
+impl<T: Base> Engine<T> {
+    // Each hook runs to the end before the next; one stopping for input or reactions is left there
+    fn resolve_hooks(&mut self, hooks: Vec<Action<T>>) {
+        for hook in hooks {
+            self.act(hook);
+            while !self.is_idle() && self.window.is_none() && self.input.is_none() {
+                self.step();
+            }
+        }
+    }
+}
+
+/** FastForwardResult
+ *  How far `fast_forward` got: the rounds it advanced, who died along the way,
+ *  and whether things settled before the cap.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct FastForwardResult {
+    pub(crate) rounds: u32,
+    pub(crate) deaths: Vec<(CreatureId, u32)>, // With the round they died in, counting from 1
+    pub(crate) settled: bool,                  // A whole round went by without changing anyone
+}
+
+impl Engine<World> {
+    // Steps to the end of whatever is pending, declining any reactions along the way
+    fn run_unattended(&mut self) {
+        while !self.is_idle() && self.input.is_none() {
+            match self.window {
+                Some(_) => self.decline_reactions(),
+                None => self.step(),
+            }
+        }
+    }
+
+    /**
+     * Advances whole rounds with nobody acting, so only what happens by itself does:
+     * persistent damage and affliction saves as each turn ends, and whatever rules fire
+     * as turns start, like regeneration. Stops once a round changes no creature,
+     * or after `max_rounds`. The current creature's turn is taken to have begun.
+     */
+    pub(crate) fn fast_forward(&mut self, max_rounds: u32) -> FastForwardResult {
+        // Only death counts; a creature that is merely dying may yet recover
+        let dead = |world: &World, id: &CreatureId| world.creature(*id).is_some_and(|c| c.dead);
+        let mut deaths = vec![];
+        for round in 1..=max_rounds {
+            let before = self.state.base.clone();
+            for _ in 0..self.state.base.initiative.order.len() {
+                let Some(creature) = self.state.base.initiative.current() else {
+                    break;
+                };
+                self.act(Action::persistent_damage(creature));
+                self.run_unattended();
+                self.next_turn();
+                self.run_unattended();
+            }
+            let world = &self.state.base;
+            deaths.extend(
+                world
+                    .creatures
+                    .keys()
+                    .filter(|id| dead(world, id) && !dead(&before, id))
+                    .map(|id| (*id, round)),
+            );
+            if world.creatures == before.creatures {
+                return FastForwardResult {
+                    rounds: round,
+                    deaths,
+                    settled: true,
+                };
+            }
+        }
+        FastForwardResult {
+            rounds: max_rounds,
+            deaths,
+            settled: false,
+        }
+    }
+
+    /**
+     * Ends the current turn and starts the next creature's, leaving its start pending.
+     * The ending creature first saves against each of its afflictions,
+     * then its conditions wear off, as `Creature::end_turn`.
+     * When the initiative order wraps, the round-end hooks are resolved first,
+     * then the round-start ones, so hazards and lair actions act once a round
+     * rather than once a turn.
+     */
+    pub(crate) fn next_turn(&mut self) -> Option<CreatureId> {
+        let initiative = &self.state.base.initiative;
+        if initiative.order.is_empty() {
+            return None;
+        }
+        let wraps = initiative.current + 1 >= initiative.order.len();
+        if let Some(ending) = initiative.current() {
+            let afflictions = self.state.base.creature(ending).map(|c| c.afflictions.clone());
+            let saves = afflictions.unwrap_or_default().into_iter();
+            self.resolve_hooks(saves.map(|a| Action::affliction_save(ending, &a.name)).collect());
+            self.apply_filter(Arc::new(move |mut state: State<World>| {
+                let registry = state.base.custom_conditions.clone();
+                if let Some(creature) = state.base.creature_mut(ending) {
+                    creature.end_turn(&registry);
+                }
+                state
+            }));
+        }
+        self.end_turn();
+        if wraps {
+            self.resolve_hooks(self.round_end_rules.clone());
+        }
+        self.apply_filter(Arc::new(|mut state: State<World>| {
+            state.base.initiative.advance();
+            state
+        }));
+        if wraps {
+            self.resolve_hooks(self.round_start_rules.clone());
+        }
+        let creature = self.state.base.initiative.current()?;
+        self.act(Action::start_turn(creature));
+        Some(creature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::condition::{ConditionDef, StatCategory};
+    use crate::engine::{ActionResponse, Resolved, Tag, UpdateBuilder};
+    use crate::modifier::ModifierKind;
+    use crate::dice::FixedDice;
+    use crate::dying::dying;
+    use crate::world::{damage, Creature, DamageType, WorldBuilder};
+
+    #[test]
+    fn round_hooks_fire_once_per_cycle() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .add_creature(2, Creature::new("Wizard", 16, 15))
+            .build();
+        let mut engine = Engine::new_encounter(world, vec![(0, 20), (1, 15), (2, 10)]);
+        let scald = |target, amount| {
+            Action::new(Arc::new(move |_, context| {
+                let update = UpdateBuilder::new()
+                    .filter(damage(target, amount))
+                    .target(target)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![update])
+            }))
+        };
+        engine.round_start_rules.push(scald(0, 1));
+        engine.round_end_rules.push(scald(2, 2));
+        let mut turns = vec![];
+        for _ in 0..7 {
+            turns.push(engine.next_turn().unwrap());
+            while !engine.is_idle() {
+                engine.step();
+            }
+        }
+        assert_eq!(turns, vec![1, 2, 0, 1, 2, 0, 1]);
+        // The order wrapped twice, going back to the fighter
+        assert_eq!(engine.state.base.creature(0).unwrap().hp, 18);
+        assert_eq!(engine.state.base.creature(2).unwrap().hp, 12);
+    }
+
+    #[test]
+    fn conditions_fade_as_their_holders_turn_ends() {
+        let hexed = |value| Tag::Custom {
+            key: String::from("hexed"),
+            value,
+        };
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18).with_condition(hexed(2)))
+            .add_creature(1, Creature::new("Goblin", 8, 16).with_condition(hexed(2)))
+            .build();
+        world.custom_conditions.register(
+            "hexed",
+            ConditionDef {
+                affects: StatCategory::Everything,
+                modifier: -1,
+                valued: true,
+                decrements_eot: true,
+                kind: ModifierKind::Status,
+            },
+        );
+        let mut engine = Engine::new_encounter(world, vec![(0, 20), (1, 15)]);
+        engine.next_turn();
+        while !engine.is_idle() {
+            engine.step();
+        }
+        // Only the fighter's turn has ended
+        assert_eq!(engine.state.base.creature(0).unwrap().conditions, vec![hexed(1)]);
+        assert_eq!(engine.state.base.creature(1).unwrap().conditions, vec![hexed(2)]);
+    }
+
+    #[test]
+    fn fast_forwarding_burns_until_the_creature_dies() {
+        let burn = |wounded| {
+            let mut goblin = Creature::new("Goblin", 5, 16).with_condition(Tag::Wounded(wounded));
+            goblin.add_persistent_damage(DamageType::Fire, 2);
+            let world = WorldBuilder::new()
+                .add_creature(0, Creature::new("Fighter", 20, 18))
+                .add_creature(1, goblin)
+                .build();
+            let mut engine = Engine::new_encounter(world, vec![(0, 20), (1, 15)]);
+            engine.rules.push(dying());
+            // Every flat check fails, so the goblin burns at 5, 3, then 1 HP
+            engine.dice = Box::new(FixedDice::new(vec![3; 4]));
+            let result = engine.fast_forward(10);
+            assert_eq!(engine.state.base.creature(1).unwrap().hp, 0);
+            assert_eq!(engine.state.base.creature(0).unwrap().hp, 20);
+            result
+        };
+        // Wounded 3 makes the fall Dying 4, which is death
+        assert_eq!(
+            burn(3),
+            FastForwardResult {
+                rounds: 4,
+                deaths: vec![(1, 3)],
+                settled: true,
+            }
+        );
+        // Only dying, it isn't counted
+        assert_eq!(burn(1).deaths, vec![]);
+    }
+}
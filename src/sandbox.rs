@@ -0,0 +1,71 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::engine::{Base, Engine, Rule, RuleResponse, State, Update};
+
+// A rule that panicked instead of answering, and was skipped
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct RuleError {
+    pub(crate) update_id: usize,
+    pub(crate) rule_index: usize,
+    pub(crate) message: String, // The panic's message, if it had one
+}
+
+/**
+ * Asks the rule about the update, catching a panic rather than letting
+ * buggy content take the engine down with it.
+ * Rules only see shared references, so nothing is left half-changed.
+ */
+pub(crate) fn ask<T>(
+    rule: &Rule<T>,
+    state: &State<T>,
+    update: &Update<T>,
+) -> Result<RuleResponse<T>, String> {
+    catch_unwind(AssertUnwindSafe(|| rule(state, update))).map_err(|payload| {
+        match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+            (Some(message), _) => String::from(*message),
+            (None, Some(message)) => message.clone(),
+            (None, None) => String::new(),
+        }
+    })
+}
+
+impl<T: Base> Engine<T> {
+    pub(crate) fn rule_errors(&self) -> &[RuleError] {
+        &self.rule_errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::engine::{
+        Action, ActionResponse, Engine, Resolved, RuleResponse, State, Tag, TestBase,
+        UpdateBuilder,
+    };
+
+    #[test]
+    fn panicking_rules_are_skipped() {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.rules.push(Arc::new(|_, _| panic!("bad content")));
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Manipulate)));
+        engine.act(Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(|mut state: State<TestBase>| {
+                    state.base.magics += 1;
+                    state
+                }))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        })));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!(engine.state.base.magics, 1);
+        assert!(engine.log()[0].tags.contains(&Tag::Manipulate));
+        let errors = engine.rule_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].rule_index, errors[0].message.as_str()), (0, "bad content"));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::engine::{Engine, Meta, State, Tag};
+use crate::initiative::Initiative;
+use crate::world::World;
+
+// Something in a scenario that can't be saved or loaded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ScenarioError {
+    UnknownRule(String),
+    UnknownAction(String),
+    UnnamedRule(usize), // The rule at this place in the order has no name to save it by
+}
+
+// A rule as a scenario saves it: its name, and how it was added
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct ScenarioRule {
+    pub(crate) name: String,
+    pub(crate) priority: i32,
+    pub(crate) trigger: Option<Tag>,
+}
+
+/** Scenario
+ *  A starting point to share as one file: the world, the initiative order,
+ *  and the rules and actions to load. Closures can't be saved, so rules and
+ *  actions are saved by name and looked up again in an engine that has them.
+ */
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Scenario {
+    pub(crate) world: World, // Its own initiative is left empty; see `initiative`
+    pub(crate) initiative: Initiative,
+    pub(crate) rules: Vec<ScenarioRule>, // In the order the engine asks them
+    pub(crate) actions: Vec<String>,     // Registered for a player or AI to choose from
+}
+
+impl Engine<World> {
+    /**
+     * Loads the scenario with fresh meta, taking each rule from `content`'s named rules
+     * and each action from its registry, so content is set up once however it is shared.
+     * Every name must be there; the first that isn't is the error.
+     */
+    pub(crate) fn from_scenario(
+        scenario: Scenario,
+        content: &Engine<World>,
+    ) -> Result<Self, ScenarioError> {
+        let mut world = scenario.world;
+        world.initiative = scenario.initiative;
+        let mut engine = Engine::new(State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        });
+        for saved in scenario.rules {
+            let (rule, _) = content
+                .rule_names
+                .iter()
+                .find(|(_, name)| *name == saved.name)
+                .ok_or_else(|| ScenarioError::UnknownRule(saved.name.clone()))?;
+            engine.add_labelled_rule(&saved.name, rule.clone(), saved.priority, saved.trigger);
+        }
+        for name in scenario.actions {
+            let (_, action) = content
+                .registry
+                .iter()
+                .find(|(n, _)| *n == name)
+                .ok_or_else(|| ScenarioError::UnknownAction(name.clone()))?;
+            engine.register(&name, action.clone());
+        }
+        Ok(engine)
+    }
+
+    // The engine as a scenario to start from again; a rule added without a name is an error
+    pub(crate) fn scenario(&self) -> Result<Scenario, ScenarioError> {
+        let mut world = self.state.base.clone();
+        let initiative = std::mem::take(&mut world.initiative);
+        let rules = self
+            .rule_order()
+            .into_iter()
+            .map(|rule| match rule.label {
+                Some(name) => Ok(ScenarioRule {
+                    name,
+                    priority: rule.priority,
+                    trigger: rule.trigger,
+                }),
+                None => Err(ScenarioError::UnnamedRule(rule.index)),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Scenario {
+            world,
+            initiative,
+            rules,
+            actions: self.registry.iter().map(|(name, _)| name.clone()).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::Degree;
+    use crate::combat::{critical_specialization, Weapon, WeaponGroup};
+    use crate::dying::dying;
+    use crate::engine::Action;
+    use crate::world::{Creature, WorldBuilder};
+
+    #[test]
+    fn a_saved_scenario_loads_back_the_same() {
+        let club = Weapon::new("Club", WeaponGroup::Club, 1, 6);
+        let mut content = Engine::new_encounter(World::default(), vec![]);
+        content.add_named_rule("Critical Specialization", critical_specialization());
+        content.add_named_rule("Dying", dying());
+        content.register("Strike", Action::strike(0, 1, club));
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::new_encounter(world, vec![(0, 12), (1, 17)]);
+        let critical = Some(Tag::Degree(Degree::CriticalSuccess));
+        engine.add_labelled_rule("Critical Specialization", critical_specialization(), 2, critical);
+        engine.add_named_rule("Dying", dying());
+        engine.register("Strike", content.registry[0].1.clone());
+
+        let mut scenario = engine.scenario().unwrap();
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&scenario).unwrap();
+            scenario = serde_json::from_str(&json).unwrap();
+        }
+        let loaded = Engine::from_scenario(scenario.clone(), &content).unwrap();
+        assert_eq!(loaded.state.base, engine.state.base);
+        assert_eq!(loaded.state.base.initiative.order, vec![1, 0]);
+        assert_eq!(loaded.rule_order(), engine.rule_order());
+        assert_eq!(loaded.legal_actions(), vec![String::from("Strike")]);
+
+        scenario.rules[1].name = String::from("Flanking");
+        assert_eq!(
+            Engine::from_scenario(scenario, &content).err(),
+            Some(ScenarioError::UnknownRule(String::from("Flanking")))
+        );
+    }
+
+    #[test]
+    fn a_rule_without_a_name_cannot_be_saved() {
+        let mut engine = Engine::new_encounter(World::default(), vec![]);
+        engine.add_named_rule("Dying", dying());
+        engine.rules.push(critical_specialization());
+        assert_eq!(engine.scenario().err(), Some(ScenarioError::UnnamedRule(1)));
+    }
+}
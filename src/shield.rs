@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use crate::combat::WeaponGroup;
+use crate::engine::{Action, ActionResponse, InvalidAction, Resolved, State, Tag, UpdateBuilder};
+use crate::reaction::Reaction;
+use crate::world::{heal, CreatureId, World};
+
+impl Action<World> {
+    // Raise a Shield: +2 circumstance to AC until the creature's next turn starts
+    pub(crate) fn raise_shield(creature: CreatureId) -> Self {
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            if state.base.creature(creature).is_none() {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            }
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(|state| state))
+                .target(creature)
+                .applies(Tag::ShieldRaised)
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+        .with_cost(1)
+        .named("Raise a Shield")
+        .by(creature)
+    }
+}
+
+/**
+ * Shield Block: when the owner is attacked with its shield raised,
+ * the shield soaks up to its hardness of the damage.
+ * Reactions come once the hit has landed, so the owner gets that much back.
+ */
+pub(crate) fn shield_block(owner: CreatureId, hardness: i32) -> Reaction<World> {
+    Reaction {
+        owner,
+        name: String::from("Shield Block"),
+        trigger: Tag::Attack {
+            attacker: owner,
+            target: owner,
+            group: WeaponGroup::Club,
+            dice: 0,
+            weapon: String::new(),
+        },
+        eligible: Arc::new(move |state, trigger| {
+            let Tag::Attack { target, .. } = trigger else {
+                return false;
+            };
+            *target == owner
+                && state
+                    .base
+                    .creature(owner)
+                    .is_some_and(|c| c.conditions.contains(&Tag::ShieldRaised))
+        }),
+        build: Arc::new(move |_, _| {
+            Action::new(Arc::new(move |_, context| {
+                let update = UpdateBuilder::new()
+                    .filter(heal(owner, hardness))
+                    .target(owner)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![update])
+            }))
+            .named("Shield Block")
+            .by(owner)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effective::StatKind;
+    use crate::engine::Engine;
+    use crate::world::{Creature, WorldBuilder};
+
+    fn run(engine: &mut Engine<World>, action: Action<World>) {
+        engine.act(action);
+        while !engine.is_idle() {
+            engine.step();
+        }
+    }
+
+    #[test]
+    fn a_raised_shield_lasts_until_the_next_turn() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.reactions.push(shield_block(0, 3));
+        let attacked = Tag::Attack {
+            attacker: 1,
+            target: 0,
+            group: WeaponGroup::Club,
+            dice: 1,
+            weapon: String::from("Club"),
+        };
+        assert!(engine.open_reaction_window(attacked.clone()).is_empty());
+
+        run(&mut engine, Action::raise_shield(0));
+        assert_eq!(engine.state.effective(0, StatKind::ArmorClass, None).0, 20);
+        assert_eq!(engine.state.base.creature(0).unwrap().ac_against(1), 20);
+        assert_eq!(engine.open_reaction_window(attacked.clone()).len(), 1);
+        engine.decline_reactions();
+
+        run(&mut engine, Action::start_turn(1));
+        assert_eq!(engine.state.effective(0, StatKind::ArmorClass, None).0, 20);
+        run(&mut engine, Action::start_turn(0));
+        assert_eq!(engine.state.effective(0, StatKind::ArmorClass, None).0, 18);
+        assert!(engine.open_reaction_window(attacked).is_empty());
+    }
+}
@@ -0,0 +1,38 @@
+use crate::world::Creature;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(dead_code)] // Every size in the rules, whether or not any creature here is it yet
+pub(crate) enum Size {
+    Tiny,
+    Small,
+    #[default]
+    Medium,
+    Large,
+    Huge,
+    Gargantuan,
+}
+
+impl Size {
+    // How far a tall creature of this size reaches, in feet, with nothing in hand
+    pub(crate) fn reach(&self) -> i32 {
+        match self {
+            Size::Tiny => 0,
+            Size::Small | Size::Medium => 5,
+            Size::Large => 10,
+            Size::Huge => 15,
+            Size::Gargantuan => 20,
+        }
+    }
+}
+
+impl Creature {
+    /**
+     * How far the creature reaches with a weapon of `weapon_reach` feet.
+     * Weapons give their reach as a Medium creature would have it,
+     * so a reach weapon adds its extra 5 feet to whatever the creature's size gives.
+     */
+    pub(crate) fn melee_reach(&self, weapon_reach: i32) -> i32 {
+        self.size.reach() + weapon_reach - Size::Medium.reach()
+    }
+}
@@ -0,0 +1,111 @@
+use crate::engine::{Meta, State};
+
+/** StateSnapshot
+ *  Everything in a State that can be stored, detached from any engine.
+ *  It encodes compactly, behind a versioned header; see the `binary` feature.
+ */
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct StateSnapshot<T> {
+    pub(crate) meta: Meta,
+    pub(crate) base: T,
+}
+
+impl<T: Clone> From<&State<T>> for StateSnapshot<T> {
+    fn from(state: &State<T>) -> Self {
+        StateSnapshot {
+            meta: state.meta.clone(),
+            base: state.base.clone(),
+        }
+    }
+}
+
+impl<T> StateSnapshot<T> {
+    pub(crate) fn into_state(self) -> State<T> {
+        State {
+            meta: self.meta,
+            base: self.base,
+            pre_chain: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum SnapshotError {
+    BadHeader,    // Not a snapshot at all
+    Version(u16), // A snapshot in a format this build can't read
+    Corrupt,      // The header is fine but the body doesn't decode
+}
+
+// "PF2S" then a little-endian format version; bump it whenever the encoding changes
+const MAGIC: [u8; 4] = *b"PF2S";
+const VERSION: u16 = 1;
+
+impl<T: serde::Serialize + serde::de::DeserializeOwned> StateSnapshot<T> {
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(VERSION.to_le_bytes());
+        // Only a failing writer can fail, and a Vec never does
+        bytes.extend(postcard::to_allocvec(self).expect("snapshots always encode"));
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if bytes.len() < 6 || bytes[..4] != MAGIC {
+            return Err(SnapshotError::BadHeader);
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(SnapshotError::Version(version));
+        }
+        postcard::from_bytes(&bytes[6..]).map_err(|_| SnapshotError::Corrupt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Tag;
+    use crate::regen::Regen;
+    use crate::stats::{Proficiency, Skill, Statistic};
+    use crate::sustain::SustainedEffect;
+    use crate::world::{Creature, DamageType, Position, World, WorldBuilder};
+
+    fn snapshot() -> StateSnapshot<World> {
+        let mut troll = Creature::new("Troll", 115, 20);
+        troll.position = Position::new(3, -2);
+        troll.proficiencies.insert(Statistic::Skill(Skill::Athletics), Proficiency::Expert);
+        troll.conditions = vec![Tag::Frightened(2), Tag::Flatfooted(None), Tag::Clumsy(1)];
+        troll.regeneration = Some(Regen {
+            amount: 20,
+            deactivated_by: vec![DamageType::Fire],
+        });
+        troll.damage_taken = vec![DamageType::Slashing];
+        let world = WorldBuilder::new().add_creature(4, troll).build();
+        let mut meta = Meta::default();
+        meta.captures.insert(String::from("hp"), 12);
+        meta.sustained.push(SustainedEffect::new(1, "Summon Animal"));
+        StateSnapshot { meta, base: world }
+    }
+
+    #[test]
+    fn binary_round_trip_is_lossless() {
+        let snapshot = snapshot();
+        let bytes = snapshot.to_bytes();
+        let decoded = StateSnapshot::from_bytes(&bytes);
+        assert_eq!(decoded, Ok(snapshot));
+        let state = decoded.unwrap().into_state();
+        assert_eq!(state.meta.captures.get("hp"), Some(&12));
+        assert!(state.pre_chain.is_none());
+    }
+
+    #[test]
+    fn other_versions_are_refused() {
+        let mut bytes = snapshot().to_bytes();
+        bytes[4] = 9;
+        assert_eq!(StateSnapshot::<World>::from_bytes(&bytes), Err(SnapshotError::Version(9)));
+        assert_eq!(StateSnapshot::<World>::from_bytes(b"JSON"), Err(SnapshotError::BadHeader));
+        let truncated = &snapshot().to_bytes()[..12];
+        assert_eq!(StateSnapshot::<World>::from_bytes(truncated), Err(SnapshotError::Corrupt));
+    }
+}
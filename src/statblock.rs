@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use crate::field::Ability;
+use crate::stats::{Proficiency, Save, Skill, Statistic};
+use crate::world::Creature;
+
+// Why a stat block couldn't be turned into a creature
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ParseError {
+    Json(String), // Not JSON, or a field holding the wrong kind of value
+    MissingField(&'static str),
+    UnknownAbility(String),
+    UnknownStatistic(String),
+    UnknownProficiency(String),
+}
+
+/** StatBlock
+ *  The fields a bestiary entry has in common, as it writes them:
+ *  ability modifiers rather than scores, and proficiency ranks by name,
+ *  like `"saves": {"reflex": "expert"}`. Anything else in the entry is ignored.
+ */
+#[derive(serde::Deserialize)]
+struct StatBlock {
+    name: Option<String>,
+    level: Option<i32>,
+    hp: Option<i32>,
+    ac: Option<i32>,
+    speed: Option<i32>,
+    #[serde(default)]
+    abilities: BTreeMap<String, i32>,
+    #[serde(default)]
+    saves: BTreeMap<String, String>,
+    #[serde(default)]
+    proficiencies: BTreeMap<String, String>, // Skills and perception
+}
+
+// Matches names the way they're written in stat blocks, in lower case
+fn named<T: std::fmt::Debug + Copy>(all: &[T], name: &str) -> Option<T> {
+    all.iter().copied().find(|t| format!("{t:?}").to_lowercase() == name.to_lowercase())
+}
+
+fn ability(name: &str) -> Result<Ability, ParseError> {
+    let short = |ability: &Ability| format!("{ability:?}")[..3].to_lowercase();
+    Ability::ALL
+        .into_iter()
+        .find(|ability| short(ability) == name.to_lowercase())
+        .or_else(|| named(&Ability::ALL, name))
+        .ok_or_else(|| ParseError::UnknownAbility(String::from(name)))
+}
+
+fn proficiency(rank: &str) -> Result<Proficiency, ParseError> {
+    use Proficiency::*;
+    named(&[Untrained, Trained, Expert, Master, Legendary], rank)
+        .ok_or_else(|| ParseError::UnknownProficiency(String::from(rank)))
+}
+
+fn statistic(name: &str) -> Result<Statistic, ParseError> {
+    if name.eq_ignore_ascii_case("perception") {
+        return Ok(Statistic::Perception);
+    }
+    named(&Skill::ALL, name)
+        .map(Statistic::Skill)
+        .ok_or_else(|| ParseError::UnknownStatistic(String::from(name)))
+}
+
+impl Creature {
+    /**
+     * Reads a creature from a JSON stat block. The name, HP and AC are required;
+     * a missing level is 1, missing abilities are +0, and missing proficiencies untrained.
+     */
+    pub(crate) fn from_stat_block(json: &str) -> Result<Creature, ParseError> {
+        let block: StatBlock =
+            serde_json::from_str(json).map_err(|error| ParseError::Json(error.to_string()))?;
+        let name = block.name.ok_or(ParseError::MissingField("name"))?;
+        let hp = block.hp.ok_or(ParseError::MissingField("hp"))?;
+        let ac = block.ac.ok_or(ParseError::MissingField("ac"))?;
+        let mut creature = Creature::new(&name, hp, ac);
+        creature.level = block.level.unwrap_or(1);
+        if let Some(speed) = block.speed {
+            creature.speed = speed;
+            creature.movement_left = speed;
+        }
+        for (name, modifier) in block.abilities {
+            let score = (10 + 2 * modifier).clamp(0, u8::MAX as i32);
+            creature.abilities[ability(&name)? as usize] = score as u8;
+        }
+        for (name, rank) in block.saves {
+            let save = named(&[Save::Fortitude, Save::Reflex, Save::Will], &name)
+                .ok_or_else(|| ParseError::UnknownStatistic(name.clone()))?;
+            creature.proficiencies.insert(Statistic::Save(save), proficiency(&rank)?);
+        }
+        for (name, rank) in block.proficiencies {
+            creature.proficiencies.insert(statistic(&name)?, proficiency(&rank)?);
+        }
+        Ok(creature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_goblin_loads_from_its_stat_block() {
+        let goblin = Creature::from_stat_block(
+            r#"{
+                "name": "Goblin Warrior",
+                "level": -1,
+                "traits": ["goblin", "humanoid"],
+                "hp": 6,
+                "ac": 16,
+                "speed": 25,
+                "abilities": {"str": 0, "dex": 3, "con": 1, "int": 0, "wis": -1, "cha": 1},
+                "saves": {"fortitude": "trained", "reflex": "expert", "will": "trained"},
+                "proficiencies": {"perception": "trained", "stealth": "expert"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!((goblin.name.as_str(), goblin.hp, goblin.ac), ("Goblin Warrior", 6, 16));
+        assert_eq!(goblin.modifier(Ability::Dexterity), 3);
+        // Expert at level -1 is 3, plus the dexterity
+        assert_eq!(goblin.save_modifier(Save::Reflex), 6);
+        assert_eq!(goblin.skill_modifier(Skill::Stealth), 6);
+
+        assert_eq!(
+            Creature::from_stat_block(r#"{"name": "Goblin", "ac": 16}"#),
+            Err(ParseError::MissingField("hp"))
+        );
+        let lucky = r#"{"name": "Goblin", "hp": 6, "ac": 16, "saves": {"luck": "expert"}}"#;
+        assert_eq!(
+            Creature::from_stat_block(lucky),
+            Err(ParseError::UnknownStatistic(String::from("luck")))
+        );
+    }
+}
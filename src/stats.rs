@@ -0,0 +1,198 @@
+use crate::combat::WeaponGroup;
+use crate::defense::Defense;
+use crate::field::{modifier, Ability};
+use crate::world::Creature;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(dead_code)] // The top ranks no creature here has reached yet
+pub(crate) enum Proficiency {
+    Untrained,
+    Trained,
+    Expert,
+    Master,
+    Legendary,
+}
+
+impl Proficiency {
+    // Untrained adds nothing; every other rank adds the creature's level
+    pub(crate) fn bonus(&self, level: i32) -> i32 {
+        match self {
+            Proficiency::Untrained => 0,
+            Proficiency::Trained => 2 + level,
+            Proficiency::Expert => 4 + level,
+            Proficiency::Master => 6 + level,
+            Proficiency::Legendary => 8 + level,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Save {
+    Fortitude,
+    Reflex,
+    Will,
+}
+
+impl Save {
+    pub(crate) fn ability(&self) -> Ability {
+        match self {
+            Save::Fortitude => Ability::Constitution,
+            Save::Reflex => Ability::Dexterity,
+            Save::Will => Ability::Wisdom,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(dead_code)] // Every skill in the rules, whether or not anything here checks it yet
+pub(crate) enum Skill {
+    Acrobatics,
+    Arcana,
+    Athletics,
+    Crafting,
+    Deception,
+    Diplomacy,
+    Intimidation,
+    Medicine,
+    Nature,
+    Occultism,
+    Performance,
+    Religion,
+    Society,
+    Stealth,
+    Survival,
+    Thievery,
+}
+
+impl Skill {
+    #[cfg(feature = "json")]
+    pub(crate) const ALL: [Skill; 16] = [
+        Skill::Acrobatics,
+        Skill::Arcana,
+        Skill::Athletics,
+        Skill::Crafting,
+        Skill::Deception,
+        Skill::Diplomacy,
+        Skill::Intimidation,
+        Skill::Medicine,
+        Skill::Nature,
+        Skill::Occultism,
+        Skill::Performance,
+        Skill::Religion,
+        Skill::Society,
+        Skill::Stealth,
+        Skill::Survival,
+        Skill::Thievery,
+    ];
+
+    pub(crate) fn ability(&self) -> Ability {
+        match self {
+            Skill::Athletics => Ability::Strength,
+            Skill::Acrobatics | Skill::Stealth | Skill::Thievery => Ability::Dexterity,
+            Skill::Arcana | Skill::Crafting | Skill::Occultism | Skill::Society => {
+                Ability::Intelligence
+            }
+            Skill::Medicine | Skill::Nature | Skill::Religion | Skill::Survival => Ability::Wisdom,
+            Skill::Deception | Skill::Diplomacy | Skill::Intimidation | Skill::Performance => {
+                Ability::Charisma
+            }
+        }
+    }
+}
+
+// Anything a creature can be proficient in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Statistic {
+    Save(Save),
+    Perception,
+    Spell,
+    Class,
+    Weapon(WeaponGroup),
+    Skill(Skill),
+    Armor, // Proficiency in the armor worn, which goes into AC
+}
+
+impl Creature {
+    pub(crate) fn modifier(&self, ability: Ability) -> i32 {
+        modifier(self.abilities[ability as usize])
+    }
+
+    pub(crate) fn proficiency(&self, statistic: Statistic) -> Proficiency {
+        self.proficiencies
+            .get(&statistic)
+            .copied()
+            .unwrap_or(Proficiency::Untrained)
+    }
+
+    pub(crate) fn proficiency_bonus(&self, statistic: Statistic) -> i32 {
+        self.proficiency(statistic).bonus(self.level)
+    }
+
+    // Read from the creature's cached defenses, so the best item bonus counts
+    pub(crate) fn save_modifier(&self, save: Save) -> i32 {
+        self.defenses.get(Defense::Save(save), self)
+    }
+
+    // The DC others roll against to affect this save, like Demoralize against Will
+    pub(crate) fn save_dc(&self, save: Save) -> i32 {
+        10 + self.save_modifier(save)
+    }
+
+    pub(crate) fn skill_modifier(&self, skill: Skill) -> i32 {
+        self.modifier(skill.ability()) + self.proficiency_bonus(Statistic::Skill(skill))
+    }
+
+    // The check modifier for any statistic; those without their own ability use the key ability
+    pub(crate) fn statistic_modifier(&self, statistic: Statistic) -> i32 {
+        match statistic {
+            Statistic::Save(save) => self.save_modifier(save),
+            Statistic::Skill(skill) => self.skill_modifier(skill),
+            Statistic::Perception => {
+                self.modifier(Ability::Wisdom) + self.proficiency_bonus(Statistic::Perception)
+            }
+            Statistic::Armor => {
+                self.modifier(Ability::Dexterity) + self.proficiency_bonus(statistic)
+            }
+            Statistic::Weapon(_) => {
+                self.modifier(Ability::Strength) + self.proficiency_bonus(statistic)
+            }
+            Statistic::Spell | Statistic::Class => {
+                self.modifier(self.key_ability) + self.proficiency_bonus(statistic)
+            }
+        }
+    }
+}
+
+// 10 + key ability modifier + proficiency bonus (which includes level)
+fn dc(creature: &Creature, statistic: Statistic) -> i32 {
+    10 + creature.modifier(creature.key_ability) + creature.proficiency_bonus(statistic)
+}
+
+pub(crate) fn spell_dc(caster: &Creature) -> i32 {
+    dc(caster, Statistic::Spell)
+}
+
+pub(crate) fn class_dc(creature: &Creature) -> i32 {
+    dc(creature, Statistic::Class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expert_level_five_caster_has_dc_23() {
+        let mut wizard = Creature::new("Wizard", 30, 17);
+        wizard.level = 5;
+        wizard.key_ability = Ability::Intelligence;
+        wizard.abilities[Ability::Intelligence as usize] = 18;
+        wizard.proficiencies.insert(Statistic::Spell, Proficiency::Expert);
+        assert_eq!(spell_dc(&wizard), 23);
+        // Untrained in class DC, so no proficiency or level
+        assert_eq!(class_dc(&wizard), 14);
+    }
+}
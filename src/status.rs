@@ -0,0 +1,104 @@
+use crate::engine::{Engine, Tag};
+use crate::world::{condition_name, CreatureId, World};
+
+// How much longer a condition lasts, by the rule that ends it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Remaining {
+    Rounds(u8),     // Drops by one at the end of each of the holder's turns, like frightened
+    UntilTurnStart, // Ends as the holder's next turn begins, like a raised shield
+    Permanent,      // Until something removes it, like drained
+}
+
+/** ConditionStatus
+ *  One entry in a condition tray: who has the condition, how long it has left,
+ *  and whether it is still on an update waiting to be applied.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ConditionStatus {
+    pub(crate) creature: CreatureId,
+    pub(crate) condition: Tag,
+    pub(crate) remaining: Remaining,
+    pub(crate) pending: bool,
+}
+
+impl ConditionStatus {
+    // As a tray shows it, like "frightened 1 (1 round left)"
+    pub(crate) fn label(&self) -> String {
+        let remaining = match self.remaining {
+            Remaining::Rounds(1) => String::from("1 round left"),
+            Remaining::Rounds(rounds) => format!("{rounds} rounds left"),
+            Remaining::UntilTurnStart => String::from("until its next turn"),
+            Remaining::Permanent => String::from("permanent"),
+        };
+        format!("{} ({remaining})", condition_name(&self.condition))
+    }
+}
+
+impl Engine<World> {
+    fn remaining(&self, condition: &Tag) -> Remaining {
+        let registry = &self.state.base.custom_conditions;
+        match condition {
+            Tag::Frightened(value) => Remaining::Rounds(*value),
+            Tag::Custom { value, .. } if registry.decrements(condition) => {
+                Remaining::Rounds(*value)
+            }
+            Tag::ShieldRaised => Remaining::UntilTurnStart,
+            _ => Remaining::Permanent,
+        }
+    }
+
+    /**
+     * Every condition on every creature, in creature order, followed by those
+     * still on pending updates in queue order.
+     */
+    pub(crate) fn active_conditions(&self) -> Vec<ConditionStatus> {
+        let held = self.state.base.creatures.iter().flat_map(|(id, creature)| {
+            creature.conditions.iter().map(move |condition| (*id, condition, false))
+        });
+        let pending = self.updates.iter().flat_map(|update| {
+            let conditions = update.sorted_tags().into_iter().filter(|tag| tag.is_condition());
+            conditions.map(move |condition| (update.target, condition, true))
+        });
+        held.chain(pending)
+            .map(|(creature, condition, pending)| ConditionStatus {
+                creature,
+                condition: condition.clone(),
+                remaining: self.remaining(condition),
+                pending,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    use crate::world::{Creature, WorldBuilder};
+
+    #[test]
+    fn the_tray_shows_how_long_each_condition_has_left() {
+        let goblin = Creature::new("Goblin", 8, 16)
+            .with_condition(Tag::Frightened(2))
+            .with_condition(Tag::Drained(2));
+        let world = WorldBuilder::new().add_creature(0, goblin).build();
+        let mut engine = Engine::for_base(world);
+        let statuses = engine.active_conditions();
+        let remaining: Vec<_> =
+            statuses.iter().map(|status| (status.condition.clone(), status.remaining)).collect();
+        assert_eq!(
+            remaining,
+            vec![
+                (Tag::Frightened(2), Remaining::Rounds(2)),
+                (Tag::Drained(2), Remaining::Permanent),
+            ]
+        );
+        assert!(statuses.iter().all(|status| status.creature == 0 && !status.pending));
+        assert_eq!(statuses[1].label(), "drained 2 (permanent)");
+
+        // The goblin's turn ending takes a round off the frightened
+        let registry = engine.state.base.custom_conditions.clone();
+        engine.state.base.creature_mut(0).unwrap().end_turn(&registry);
+        assert_eq!(engine.active_conditions()[0].label(), "frightened 1 (1 round left)");
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use crate::engine::{
+    Action, ActionResponse, Base, Engine, InvalidAction, Resolved, State, UpdateBuilder,
+};
+
+/** SustainedEffect
+ *  An ongoing effect, like a summoned creature, that lasts only while its
+ *  caster spends an action each turn to keep it going.
+ */
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct SustainedEffect {
+    pub(crate) id: usize,
+    pub(crate) name: String,
+    pub(crate) sustained: bool, // Refreshed since the last end of turn
+}
+
+impl SustainedEffect {
+    pub(crate) fn new(id: usize, name: &str) -> Self {
+        // Starting an effect counts as sustaining it for that turn
+        SustainedEffect {
+            id,
+            name: String::from(name),
+            sustained: true,
+        }
+    }
+}
+
+impl<T: Base> Action<T> {
+    // Sustain: a single action that keeps one effect running through the next end of turn
+    pub(crate) fn sustain(effect_id: usize) -> Self {
+        Action::new(Arc::new(move |state: &State<T>, context| {
+            if !state.meta.sustained.iter().any(|e| e.id == effect_id) {
+                return ActionResponse::Invalid(InvalidAction::BadPredicate);
+            }
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(move |mut state: State<T>| {
+                    let effect = state.meta.sustained.iter_mut().find(|e| e.id == effect_id);
+                    if let Some(effect) = effect {
+                        effect.sustained = true;
+                    }
+                    state
+                }))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }))
+        .with_cost(1)
+    }
+}
+
+impl<T: Base> Engine<T> {
+    /**
+     * Ends every sustained effect that wasn't sustained this turn.
+     * The rest must be sustained again before the next end of turn.
+     * Rules added for a number of turns may expire here too.
+     */
+    pub(crate) fn end_turn(&mut self) {
+        self.state.meta.sustained.retain(|effect| effect.sustained);
+        for effect in self.state.meta.sustained.iter_mut() {
+            effect.sustained = false;
+        }
+        self.state.meta.turn += 1;
+        self.state.meta.expire_immunities();
+        self.expire_rules();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Meta, TestBase};
+
+    #[test]
+    fn unsustained_effects_end_at_end_of_turn() {
+        let mut meta = Meta::default();
+        meta.sustained.push(SustainedEffect::new(1, "Summon Animal"));
+        meta.sustained.push(SustainedEffect::new(2, "Light"));
+        let mut engine = Engine::new(State {
+            meta,
+            base: TestBase::default(),
+            pre_chain: None,
+        });
+        engine.end_turn(); // Both were started this turn
+
+        engine.act(Action::sustain(1));
+        engine.step();
+        engine.step();
+        assert_eq!(engine.state.meta.actions_remaining, 2);
+        engine.end_turn();
+        let ids: Vec<usize> = engine.state.meta.sustained.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![1]);
+
+        engine.end_turn();
+        assert!(engine.state.meta.sustained.is_empty());
+    }
+
+    #[test]
+    fn sustaining_a_missing_effect_is_invalid() {
+        let mut engine = Engine::for_base(TestBase::default());
+        engine.act(Action::sustain(7));
+        engine.step();
+        assert_eq!(engine.rejected, Some(InvalidAction::BadPredicate));
+        assert_eq!(engine.state.meta.actions_remaining, 3);
+    }
+}
@@ -0,0 +1,427 @@
+use crate::engine::Tag;
+
+// Every valueless tag, and flat-footed with no source; a tag's index here is its bit
+// (One missing from this list still works, it just lands in the side table)
+static UNIT_TAGS: [Tag; 11] = [
+    Tag::Flatfooted(None),
+    Tag::Unconscious,
+    Tag::Paralyzed,
+    Tag::ShieldRaised,
+    Tag::Grabbed,
+    Tag::Concealed,
+    Tag::Manipulate,
+    Tag::Hit,
+    Tag::Prone,
+    Tag::Fortune,
+    Tag::Misfortune,
+];
+
+fn bit(tag: &Tag) -> Option<u32> {
+    UNIT_TAGS.iter().position(|t| t == tag).map(|i| 1 << i)
+}
+
+/** TagSet
+ *  A set of tags that doesn't allocate for the valueless ones:
+ *  those live in a bitset, and only tags carrying values go in the side table.
+ *  The side table is kept sorted so equal sets compare and hash equal.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) struct TagSet {
+    units: u32,
+    valued: Vec<Tag>,
+}
+
+impl TagSet {
+    pub(crate) fn new() -> Self {
+        TagSet::default()
+    }
+
+    // Returns false if the tag was already present
+    pub(crate) fn insert(&mut self, tag: Tag) -> bool {
+        if let Some(bit) = bit(&tag) {
+            let fresh = self.units & bit == 0;
+            self.units |= bit;
+            return fresh;
+        }
+        match self.valued.binary_search(&tag) {
+            Ok(_) => false,
+            Err(at) => {
+                self.valued.insert(at, tag);
+                true
+            }
+        }
+    }
+
+    pub(crate) fn contains(&self, tag: &Tag) -> bool {
+        match bit(tag) {
+            Some(bit) => self.units & bit != 0,
+            None => self.valued.binary_search(tag).is_ok(),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, tag: &Tag) -> bool {
+        if let Some(bit) = bit(tag) {
+            let present = self.units & bit != 0;
+            self.units &= !bit;
+            return present;
+        }
+        match self.valued.binary_search(tag) {
+            Ok(at) => {
+                self.valued.remove(at);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&Tag) -> bool) {
+        for (i, tag) in UNIT_TAGS.iter().enumerate() {
+            if !keep(tag) {
+                self.units &= !(1 << i);
+            }
+        }
+        self.valued.retain(keep);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.units == 0 && self.valued.is_empty()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.units.count_ones() as usize + self.valued.len()
+    }
+
+    // Valueless tags first, then the valued ones in canonical order
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Tag> + '_ {
+        UNIT_TAGS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.units & (1 << i) != 0)
+            .map(|(_, tag)| tag)
+            .chain(self.valued.iter())
+    }
+}
+
+impl<const N: usize> From<[Tag; N]> for TagSet {
+    fn from(tags: [Tag; N]) -> Self {
+        tags.into_iter().collect()
+    }
+}
+
+impl FromIterator<Tag> for TagSet {
+    fn from_iter<I: IntoIterator<Item = Tag>>(tags: I) -> Self {
+        let mut set = TagSet::new();
+        for tag in tags {
+            set.insert(tag);
+        }
+        set
+    }
+}
+
+/**
+ * Content files write tags as {"condition": "frightened", "value": 2},
+ * with no value at all for valueless ones. That shape needs a self-describing
+ * format, so binary snapshots keep serde's compact default instead.
+ * Both mirrors must list every variant of `Tag`, which the compiler checks.
+ */
+#[cfg(feature = "serde")]
+mod representation {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::check::Degree;
+    use crate::combat::WeaponGroup;
+    use crate::cover::CoverLevel;
+    use crate::damage::DamageBreakdown;
+    use crate::engine::Tag;
+    use crate::movement::Movement;
+    use crate::world::{CreatureId, Position};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "Tag", tag = "condition", content = "value", rename_all = "snake_case")]
+    enum Named {
+        Flatfooted(Option<CreatureId>),
+        Frightened(u8),
+        Clumsy(u8),
+        Enfeebled(u8),
+        Stupefied(u8),
+        Dying(u8),
+        Wounded(u8),
+        Drained(u8),
+        Stunned(u8),
+        Unconscious,
+        Paralyzed,
+        ShieldRaised,
+        Grabbed,
+        Prone,
+        Fortune,
+        Misfortune,
+        Concealed,
+        Cover(CoverLevel),
+        Custom { key: String, value: u8 },
+        Manipulate,
+        Casting { caster: CreatureId, rank: u8 },
+        Attack {
+            attacker: CreatureId,
+            target: CreatureId,
+            group: WeaponGroup,
+            dice: u8,
+            weapon: String,
+        },
+        Hit,
+        Degree(Degree),
+        FlatCheck { dc: u8, passed: bool },
+        Damage(DamageBreakdown),
+        Move {
+            mover: CreatureId,
+            from: Position,
+            to: Position,
+            kind: Movement,
+        },
+        TurnStart(CreatureId),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "Tag")]
+    enum Compact {
+        Flatfooted(Option<CreatureId>),
+        Frightened(u8),
+        Clumsy(u8),
+        Enfeebled(u8),
+        Stupefied(u8),
+        Dying(u8),
+        Wounded(u8),
+        Drained(u8),
+        Stunned(u8),
+        Unconscious,
+        Paralyzed,
+        ShieldRaised,
+        Grabbed,
+        Prone,
+        Fortune,
+        Misfortune,
+        Concealed,
+        Cover(CoverLevel),
+        Custom { key: String, value: u8 },
+        Manipulate,
+        Casting { caster: CreatureId, rank: u8 },
+        Attack {
+            attacker: CreatureId,
+            target: CreatureId,
+            group: WeaponGroup,
+            dice: u8,
+            weapon: String,
+        },
+        Hit,
+        Degree(Degree),
+        FlatCheck { dc: u8, passed: bool },
+        Damage(DamageBreakdown),
+        Move {
+            mover: CreatureId,
+            from: Position,
+            to: Position,
+            kind: Movement,
+        },
+        TurnStart(CreatureId),
+    }
+
+    impl Serialize for Tag {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                Named::serialize(self, serializer)
+            } else {
+                Compact::serialize(self, serializer)
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Tag {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                Named::deserialize(deserializer)
+            } else {
+                Compact::deserialize(deserializer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::collections::HashSet;
+
+    // Counts this thread's allocations, so tests running alongside don't skew it
+    struct Counting;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    #[global_allocator]
+    static COUNTING: Counting = Counting;
+
+    fn allocations(run: impl FnOnce()) -> usize {
+        let before = ALLOCATIONS.with(Cell::get);
+        run();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+
+    // A strike's chain: each update gets its tags, is checked by the rules and
+    // cloned into the history
+    const CHAIN: usize = 100;
+    const STRIKE: [Tag; 3] = [Tag::Manipulate, Tag::Flatfooted(None), Tag::Hit];
+
+    #[test]
+    fn a_chain_of_valueless_tags_does_not_allocate() {
+        let mut history = Vec::with_capacity(CHAIN);
+        let tagsets = allocations(|| {
+            for _ in 0..CHAIN {
+                let mut tags = TagSet::new();
+                for tag in STRIKE {
+                    tags.insert(tag);
+                }
+                assert!(tags.contains(&Tag::Hit));
+                history.push(tags.clone());
+            }
+        });
+        let mut history = Vec::with_capacity(CHAIN);
+        let hashsets = allocations(|| {
+            for _ in 0..CHAIN {
+                let mut tags = HashSet::new();
+                for tag in STRIKE {
+                    tags.insert(tag);
+                }
+                assert!(tags.contains(&Tag::Hit));
+                history.push(tags.clone());
+            }
+        });
+        assert_eq!(tagsets, 0);
+        // Each set and its clone need a table of their own
+        assert!(hashsets >= 2 * CHAIN, "{hashsets}");
+    }
+
+    #[test]
+    fn valueless_tags_do_not_allocate() {
+        let mut set = TagSet::new();
+        assert!(set.insert(Tag::Flatfooted(None)));
+        assert!(!set.insert(Tag::Flatfooted(None)));
+        assert!(set.insert(Tag::Manipulate));
+        assert_eq!(set.valued.capacity(), 0);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn behaves_like_a_set() {
+        let mut set = TagSet::from([Tag::Frightened(2), Tag::Flatfooted(None), Tag::Frightened(1)]);
+        assert!(set.contains(&Tag::Frightened(1)));
+        assert!(!set.contains(&Tag::Manipulate));
+        assert_eq!(
+            set.iter().cloned().collect::<Vec<_>>(),
+            vec![Tag::Flatfooted(None), Tag::Frightened(1), Tag::Frightened(2)]
+        );
+        assert!(set.remove(&Tag::Frightened(1)));
+        set.retain(|tag| !matches!(tag, Tag::Flatfooted(_)));
+        assert_eq!(set, TagSet::from([Tag::Frightened(2)]));
+        // Insertion order doesn't matter to equality
+        assert_eq!(
+            TagSet::from([Tag::Manipulate, Tag::Frightened(1)]),
+            TagSet::from([Tag::Frightened(1), Tag::Manipulate])
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn every_tag_round_trips_through_json() {
+        use crate::check::Degree;
+        use crate::combat::WeaponGroup;
+        use crate::cover::CoverLevel;
+        use crate::damage::{DamageBreakdown, DamageCategory};
+        use crate::movement::Movement;
+        use crate::world::{DamageType, Position};
+
+        assert_eq!(
+            serde_json::to_string(&Tag::Frightened(2)).unwrap(),
+            r#"{"condition":"frightened","value":2}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Tag::Flatfooted(None)).unwrap(),
+            r#"{"condition":"flatfooted","value":null}"#
+        );
+        // Content can still leave the source out
+        assert_eq!(
+            serde_json::from_str::<Tag>(r#"{"condition":"flatfooted"}"#).unwrap(),
+            Tag::Flatfooted(None)
+        );
+        let tags = [
+            Tag::Flatfooted(None),
+            Tag::Frightened(2),
+            Tag::Clumsy(1),
+            Tag::Enfeebled(2),
+            Tag::Stupefied(1),
+            Tag::Dying(3),
+            Tag::Wounded(1),
+            Tag::Drained(1),
+            Tag::Stunned(2),
+            Tag::Unconscious,
+            Tag::ShieldRaised,
+            Tag::Grabbed,
+            Tag::Prone,
+            Tag::Misfortune,
+            Tag::Concealed,
+            Tag::Cover(CoverLevel::Greater),
+            Tag::Custom {
+                key: String::from("hexed"),
+                value: 1,
+            },
+            Tag::Manipulate,
+            Tag::Casting { caster: 0, rank: 3 },
+            Tag::Attack {
+                attacker: 0,
+                target: 1,
+                group: WeaponGroup::Sword,
+                dice: 1,
+                weapon: String::from("Longsword"),
+            },
+            Tag::Hit,
+            Tag::Degree(Degree::CriticalSuccess),
+            Tag::FlatCheck {
+                dc: 5,
+                passed: false,
+            },
+            Tag::Damage(DamageBreakdown {
+                category: DamageCategory::Persistent,
+                kind: Some(DamageType::Fire),
+                die: 6,
+                rolls: vec![3, 5],
+                modifier: 4,
+                multiplier: 1,
+                total: 12,
+                nonlethal: true,
+            }),
+            Tag::Move {
+                mover: 1,
+                from: Position::new(0, 0),
+                to: Position::new(1, 1),
+                kind: Movement::Stride,
+            },
+            Tag::TurnStart(2),
+        ];
+        for tag in tags {
+            let json = serde_json::to_string(&tag).unwrap();
+            assert_eq!(serde_json::from_str::<Tag>(&json).unwrap(), tag, "{json}");
+        }
+    }
+}
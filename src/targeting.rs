@@ -0,0 +1,147 @@
+use crate::engine::{InvalidAction, State};
+use crate::world::{line_of_effect, CreatureId, World};
+
+// Who a targeted action is aimed at, and how far it can reach
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Targeting {
+    pub(crate) actor: CreatureId,
+    pub(crate) target: CreatureId,
+    pub(crate) range: i32, // In feet
+    pub(crate) melee: bool, // The range is a weapon's reach, which the actor's size stretches
+}
+
+/** TargetValidator
+ *  Decides whether a targeted action may go ahead, before it generates anything.
+ *  Keeping range math here means generators don't each repeat it.
+ */
+pub(crate) trait TargetValidator<T>: Send + Sync {
+    fn validate(&self, state: &State<T>, targeting: &Targeting) -> Result<(), InvalidAction>;
+}
+
+// Measures range on the battle grid; anything not on it, or behind a wall, can't be targeted
+pub(crate) struct GridValidator;
+
+impl TargetValidator<World> for GridValidator {
+    fn validate(&self, state: &State<World>, targeting: &Targeting) -> Result<(), InvalidAction> {
+        let (Some(actor), Some(target)) = (
+            state.base.creature(targeting.actor),
+            state.base.creature(targeting.target),
+        ) else {
+            return Err(InvalidAction::NoValidTarget);
+        };
+        if !line_of_effect(actor.position, target.position, &state.base.grid) {
+            return Err(InvalidAction::NoValidTarget);
+        }
+        let range = match targeting.melee {
+            true => actor.melee_reach(targeting.range),
+            false => targeting.range,
+        };
+        let actual = actor.position.distance(&target.position);
+        if actual > range {
+            return Err(InvalidAction::OutOfRange {
+                needed: range,
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::engine::{Action, Engine, Meta};
+    use crate::size::Size;
+    use crate::world::{Creature, Position, WorldBuilder};
+
+    #[test]
+    fn melee_strike_out_of_reach_is_rejected() {
+        let mut goblin = Creature::new("Goblin", 8, 16);
+        goblin.position = Position::new(6, 0);
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, goblin)
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.validator = Some(Arc::new(GridValidator));
+        let sword = Weapon::new("Longsword", WeaponGroup::Sword, 1, 8);
+
+        engine.act(Action::strike(0, 1, sword.clone()));
+        engine.step();
+        assert_eq!(
+            engine.rejected,
+            Some(InvalidAction::OutOfRange {
+                needed: 5,
+                actual: 30
+            })
+        );
+        assert_eq!(engine.state.meta.actions_remaining, 3);
+
+        // A bow reaches where the sword can't
+        let bow = Weapon::new("Shortbow", WeaponGroup::Bow, 1, 6).with_range(60);
+        engine.act(Action::strike(0, 1, bow));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!(engine.rejected, None);
+        assert_eq!(engine.state.meta.actions_remaining, 2);
+
+        engine.act(Action::strike(0, 7, sword));
+        engine.step();
+        assert_eq!(engine.rejected, Some(InvalidAction::NoValidTarget));
+    }
+
+    #[test]
+    fn large_creatures_reach_further() {
+        let fist = Weapon::new("Fist", WeaponGroup::Brawling, 1, 4);
+        let strike = |size| {
+            let mut ogre = Creature::new("Ogre", 50, 17);
+            ogre.size = size;
+            let mut goblin = Creature::new("Goblin", 8, 16);
+            goblin.position = Position::new(2, 0);
+            let world = WorldBuilder::new()
+                .add_creature(0, ogre)
+                .add_creature(1, goblin)
+                .build();
+            let mut engine = Engine::for_base(world);
+            engine.validator = Some(Arc::new(GridValidator));
+            engine.act(Action::strike(0, 1, fist.clone()));
+            engine.step();
+            engine.rejected.clone()
+        };
+        let short = InvalidAction::OutOfRange {
+            needed: 5,
+            actual: 10,
+        };
+        assert_eq!(strike(Size::Medium), Some(short));
+        assert_eq!(strike(Size::Large), None);
+    }
+
+    #[test]
+    fn targets_behind_walls_are_rejected() {
+        let validator = GridValidator;
+        let mut world = World::default();
+        for (id, x) in [(0, 0), (1, 3), (2, -3)] {
+            let mut creature = Creature::new("Archer", 16, 16);
+            creature.position = Position::new(x, 0);
+            world.creatures.insert(id, creature);
+        }
+        world.grid.walls.insert(Position::new(2, 0));
+        let state = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+        let shot = |target| Targeting {
+            actor: 0,
+            target,
+            range: 60,
+            melee: false,
+        };
+        assert_eq!(validator.validate(&state, &shot(1)), Err(InvalidAction::NoValidTarget));
+        assert_eq!(validator.validate(&state, &shot(2)), Ok(()));
+    }
+}
@@ -0,0 +1,181 @@
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+use crate::engine::{Base, Engine, Rule, Tag};
+use crate::tags::TagSet;
+use crate::world::CreatureId;
+
+// How long a temporary rule lasts, counted from when it is added
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Duration {
+    Turns(u32),              // Until that many more turns have ended
+    StartOfTurn(CreatureId), // Until the creature's next turn begins
+    EndOfTurn(CreatureId),   // Until the creature's next turn, not any current one, ends
+}
+
+// When a temporary rule comes off
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Expiry {
+    Turn(u32), // Once the turn counter reaches it
+    TurnStart(CreatureId),
+    TurnEnd { creature: CreatureId, started: bool }, // Only once its turn has started since
+}
+
+impl<T: Base> Engine<T> {
+    fn remove_rule(&mut self, rule: &Rule<T>) {
+        self.rules.retain(|r| !Arc::ptr_eq(r, rule));
+    }
+
+    /**
+     * Adds the rule for the duration of `f` only.
+     * It is removed however `f` finishes, even by panicking.
+     */
+    pub(crate) fn with_temp_rule(&mut self, rule: Rule<T>, f: impl FnOnce(&mut Self)) {
+        self.rules.push(rule.clone());
+        let result = catch_unwind(AssertUnwindSafe(|| f(self)));
+        self.remove_rule(&rule);
+        if let Err(panic) = result {
+            resume_unwind(panic);
+        }
+    }
+
+    // Adds the rule until `turns` more turns have ended
+    pub(crate) fn add_rule_for(&mut self, rule: Rule<T>, turns: u32) {
+        self.add_rule_until(rule, Duration::Turns(turns));
+    }
+
+    /**
+     * Adds the rule for the duration. Turn-anchored durations follow the anchor's
+     * own turns, however many other creatures act in between: they are tracked
+     * through `Tag::TurnStart` updates and whoever's turn is ending at `end_turn`.
+     */
+    pub(crate) fn add_rule_until(&mut self, rule: Rule<T>, duration: Duration) {
+        let expiry = match duration {
+            Duration::Turns(turns) => Expiry::Turn(self.state.meta.turn + turns),
+            Duration::StartOfTurn(creature) => Expiry::TurnStart(creature),
+            Duration::EndOfTurn(creature) => Expiry::TurnEnd {
+                creature,
+                started: false,
+            },
+        };
+        self.rules.push(rule.clone());
+        self.expiring.push((rule, expiry));
+    }
+
+    // Removes every rule whose expiry has come
+    fn expire(&mut self, expired: impl Fn(&Expiry) -> bool) {
+        let (expired, kept) = std::mem::take(&mut self.expiring)
+            .into_iter()
+            .partition(|(_, expiry)| expired(expiry));
+        self.expiring = kept;
+        for (rule, _) in expired {
+            self.remove_rule(&rule);
+        }
+    }
+
+    // At the end of a turn, whether counted or the current creature's
+    pub(crate) fn expire_rules(&mut self) {
+        let turn = self.state.meta.turn;
+        let ending = T::current_turn(&self.state);
+        self.expire(|expiry| match expiry {
+            Expiry::Turn(until) => *until <= turn,
+            Expiry::TurnStart(_) => false,
+            Expiry::TurnEnd { creature, started } => *started && ending == Some(*creature),
+        });
+    }
+
+    // Once an update starting a turn has been processed
+    pub(crate) fn expire_at_turn_start(&mut self, tags: &TagSet) {
+        let started = |creature: &CreatureId| tags.contains(&Tag::TurnStart(*creature));
+        for (_, expiry) in self.expiring.iter_mut() {
+            if let Expiry::TurnEnd { creature, started: seen } = expiry {
+                *seen |= started(creature);
+            }
+        }
+        self.expire(|expiry| matches!(expiry, Expiry::TurnStart(creature) if started(creature)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Action, ActionResponse, RuleResponse, TestBase, UpdateBuilder};
+    use crate::world::{Creature, World, WorldBuilder};
+
+    fn engine() -> Engine<TestBase> {
+        Engine::for_base(TestBase::default())
+    }
+
+    // Runs a one-update action, returning whether any rule marked it flat-footed
+    fn flatfooted(engine: &mut Engine<TestBase>) -> bool {
+        engine.act(Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(|state| state))
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        })));
+        engine.step();
+        engine.step();
+        engine.updates[0].tags.contains(&Tag::Flatfooted(None))
+    }
+
+    fn rule() -> Rule<TestBase> {
+        Arc::new(|_, _| RuleResponse::Attach(Tag::Flatfooted(None)))
+    }
+
+    #[test]
+    fn temp_rule_only_applies_inside_its_scope() {
+        let mut engine = engine();
+        let mut inside = false;
+        engine.with_temp_rule(rule(), |engine| inside = flatfooted(engine));
+        assert!(inside);
+        assert!(engine.rules.is_empty());
+        assert!(!flatfooted(&mut engine));
+
+        let unwound = catch_unwind(AssertUnwindSafe(|| {
+            engine.with_temp_rule(rule(), |_| panic!("the action failed"))
+        }));
+        assert!(unwound.is_err());
+        assert!(engine.rules.is_empty());
+    }
+
+    #[test]
+    fn anchored_rules_follow_their_creatures_turns() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Wizard", 16, 15))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .add_creature(2, Creature::new("Fighter", 20, 18))
+            .build();
+        let mut engine = Engine::new_encounter(world, vec![(0, 20), (1, 15), (2, 10)]);
+        let rule = || -> Rule<World> { Arc::new(|_, _| RuleResponse::Skip) };
+        engine.add_rule_until(rule(), Duration::StartOfTurn(0));
+        engine.add_rule_until(rule(), Duration::EndOfTurn(1));
+        // The wizard's own turn ending now doesn't count as its next
+        engine.add_rule_until(rule(), Duration::EndOfTurn(0));
+        let next_turn = |engine: &mut Engine<World>| {
+            engine.end_turn();
+            let creature = engine.state.base.initiative.advance().unwrap();
+            engine.act(Action::start_turn(creature));
+            while !engine.is_idle() {
+                engine.step();
+            }
+            engine.rules.len()
+        };
+        assert_eq!(next_turn(&mut engine), 3); // The goblin's
+        assert_eq!(next_turn(&mut engine), 2); // The fighter's
+        assert_eq!(next_turn(&mut engine), 1); // The wizard's again
+        assert_eq!(next_turn(&mut engine), 0);
+    }
+
+    #[test]
+    fn turn_scoped_rule_expires() {
+        let mut engine = engine();
+        engine.add_rule_for(rule(), 2);
+        assert!(flatfooted(&mut engine));
+        engine.end_turn();
+        assert!(flatfooted(&mut engine));
+        engine.end_turn();
+        assert!(!flatfooted(&mut engine));
+        assert!(engine.expiring.is_empty());
+    }
+}
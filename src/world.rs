@@ -0,0 +1,979 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::affliction::Affliction;
+use crate::aura::{refresh_auras, Aura};
+use crate::bulk::Item;
+use crate::condition::ConditionRegistry;
+use crate::counteract::MagicalEffect;
+use crate::defense::{Defense, Defenses};
+use crate::diff::{diff_world, StateDiff};
+use crate::fingerprint::fingerprint_world;
+use crate::initiative::Initiative;
+use crate::persistent::PersistentDamage;
+use crate::pool::NonStackingPool;
+use crate::regen::Regen;
+use crate::resistance::{Material, Resistance};
+use crate::size::Size;
+use crate::engine::{
+    Action, ActionResponse, Base, Engine, Filter, InvalidAction, Resolved, State, Tag, Update,
+    UpdateBuilder,
+};
+use crate::field::Ability;
+use crate::stats::{Proficiency, Statistic};
+
+pub(crate) type CreatureId = usize;
+
+// A square on the battle grid
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Position {
+    pub(crate) x: i32,
+    pub(crate) y: i32,
+}
+
+impl Position {
+    pub(crate) fn new(x: i32, y: i32) -> Self {
+        Position { x, y }
+    }
+
+    // Touching, diagonals included (a square is not adjacent to itself)
+    pub(crate) fn adjacent(&self, other: &Position) -> bool {
+        self != other && (self.x - other.x).abs() <= 1 && (self.y - other.y).abs() <= 1
+    }
+
+    // In feet, with every second diagonal costing 10
+    pub(crate) fn distance(&self, other: &Position) -> i32 {
+        let dx = (self.x - other.x).abs();
+        let dy = (self.y - other.y).abs();
+        let diagonals = dx.min(dy);
+        5 * (dx.max(dy) - diagonals) + 5 * diagonals + 5 * (diagonals / 2)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Creature {
+    pub(crate) name: String,
+    pub(crate) hp: i32,
+    pub(crate) max_hp: i32,
+    pub(crate) temp_hp: NonStackingPool, // Lost before hp, and gone once the encounter ends
+    pub(crate) ac: i32,
+    pub(crate) level: i32,
+    pub(crate) abilities: [u8; 6], // Scores, indexed by `Ability as usize`
+    pub(crate) key_ability: Ability,
+    pub(crate) proficiencies: BTreeMap<Statistic, Proficiency>, // Missing means untrained
+    pub(crate) conditions: Vec<Tag>, // Conditions that persist on the creature between chains
+    pub(crate) from_auras: Vec<(CreatureId, Tag)>, // Conditions auras gave it, by source
+    pub(crate) position: Position,
+    pub(crate) team: u8, // Creatures on different teams are enemies
+    pub(crate) speed: i32, // Land Speed in feet, before any penalties
+    pub(crate) items: Vec<Item>,
+    pub(crate) regeneration: Option<Regen>,
+    pub(crate) damage_taken: Vec<DamageType>, // Each type of damage taken since its last turn
+    pub(crate) movement_left: i32, // Feet of movement still available this turn
+    pub(crate) afflictions: Vec<Affliction>,
+    pub(crate) effects: Vec<MagicalEffect>, // Magic on the creature that can be counteracted
+    pub(crate) persistent_damage: Vec<PersistentDamage>, // At most one of each damage type
+    pub(crate) immunities: Vec<DamageType>,
+    pub(crate) resistances: Vec<Resistance>,
+    pub(crate) dead: bool, // Dead creatures ignore every update but one that revives them
+    pub(crate) size: Size,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) defenses: Defenses, // AC and saves as last worked out from the rest
+}
+
+impl Creature {
+    pub(crate) fn new(name: &str, max_hp: i32, ac: i32) -> Self {
+        Self {
+            name: String::from(name),
+            hp: max_hp,
+            max_hp,
+            temp_hp: NonStackingPool::default(),
+            ac,
+            level: 1,
+            abilities: [10; 6],
+            key_ability: Ability::Strength,
+            proficiencies: BTreeMap::new(),
+            conditions: vec![],
+            from_auras: vec![],
+            position: Position::default(),
+            team: 0,
+            speed: 25,
+            items: vec![],
+            regeneration: None,
+            damage_taken: vec![],
+            movement_left: 25,
+            afflictions: vec![],
+            effects: vec![],
+            persistent_damage: vec![],
+            immunities: vec![],
+            resistances: vec![],
+            dead: false,
+            size: Size::Medium,
+            defenses: Defenses::new(),
+        }
+    }
+
+    // Everything a creature gets back, or stops benefiting from, as its turn starts
+    pub(crate) fn reset_turn(&mut self) {
+        self.damage_taken.clear();
+        self.movement_left = self.land_speed();
+        self.clear_condition(&Tag::ShieldRaised);
+    }
+
+    // What wears off as the creature's turn ends: frightened drops by one, as do custom
+    // conditions defined to decrement at end of turn
+    pub(crate) fn end_turn(&mut self, registry: &ConditionRegistry) {
+        let fades = |c: &Tag| matches!(c, Tag::Frightened(_)) || registry.decrements(c);
+        for condition in self.conditions.iter_mut().filter(|c| fades(c)) {
+            *condition = condition.with_value(condition.value().saturating_sub(1));
+        }
+        self.conditions.retain(|c| !fades(c) || c.value() > 0);
+    }
+
+    // Temporary hit points soak up the damage first
+    pub(crate) fn lose_hp(&mut self, amount: i32) {
+        let soaked = self.temp_hp.spend(amount);
+        self.hp = self.hp.saturating_sub(amount - soaked).max(0);
+    }
+
+    pub(crate) fn stunned(&self) -> u8 {
+        self.conditions
+            .iter()
+            .find_map(|c| match c {
+                Tag::Stunned(value) => Some(*value),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    // Loses up to `actions` to being stunned, each reducing Stunned by one; returns how many
+    pub(crate) fn lose_to_stun(&mut self, actions: u8) -> u8 {
+        let stunned = self.stunned();
+        let lost = stunned.min(actions);
+        self.clear_condition(&Tag::Stunned(0));
+        if stunned > lost {
+            self.add_condition(Tag::Stunned(stunned - lost));
+        }
+        lost
+    }
+
+    // How much of one instance of typed damage gets through
+    pub(crate) fn damage_after_defenses(
+        &self,
+        amount: i32,
+        kind: DamageType,
+        material: Option<Material>,
+    ) -> i32 {
+        if self.immunities.contains(&kind) {
+            return 0;
+        }
+        (amount - self.resistance_to(kind, material)).max(0)
+    }
+
+    pub(crate) fn is_enemy(&self, other: &Creature) -> bool {
+        self.team != other.team
+    }
+
+    // A copy of the creature as a template, told apart by the suffix, like "Goblin Warrior 2"
+    pub(crate) fn spawn(&self, name_suffix: &str) -> Creature {
+        Creature {
+            name: format!("{} {name_suffix}", self.name),
+            ..self.clone()
+        }
+    }
+
+    /**
+     * Adds a condition, keeping the higher value if it is already present.
+     * Flat-footed from different sources are kept apart, since they apply to different attackers.
+     */
+    pub(crate) fn add_condition(&mut self, tag: Tag) {
+        let present = |c: &&mut Tag| match (&**c, &tag) {
+            (Tag::Flatfooted(held), Tag::Flatfooted(source)) => held == source,
+            _ => c.same_kind(&tag),
+        };
+        match self.conditions.iter_mut().find(present) {
+            Some(existing) => *existing = existing.clone().max(tag),
+            None => self.conditions.push(tag),
+        }
+    }
+
+    // Starts the creature off with the condition, as for a scenario, at no more than its reduced HP
+    pub(crate) fn with_condition(mut self, tag: Tag) -> Self {
+        self.add_condition(tag);
+        self.hp = self.hp.min(self.effective_max_hp());
+        self
+    }
+
+    // Flat-footed to everyone, or to this attacker in particular
+    pub(crate) fn is_flatfooted_to(&self, attacker: CreatureId) -> bool {
+        self.conditions.iter().any(|condition| match condition {
+            Tag::Flatfooted(source) => source.is_none_or(|source| source == attacker),
+            _ => false,
+        })
+    }
+
+    // AC against a melee attack, after the circumstance modifiers that depend on who is attacking
+    pub(crate) fn ac_against(&self, attacker: CreatureId) -> i32 {
+        self.ac_against_attack(attacker, true)
+    }
+
+    // As `ac_against`, for a melee or ranged attack; lying prone only helps ranged attackers
+    pub(crate) fn ac_against_attack(&self, attacker: CreatureId, melee: bool) -> i32 {
+        let prone = melee && self.conditions.contains(&Tag::Prone);
+        let flatfooted = if self.is_flatfooted_to(attacker) || prone { 2 } else { 0 };
+        let shield = if self.conditions.contains(&Tag::ShieldRaised) { 2 } else { 0 };
+        self.armor_class() - flatfooted + shield.max(self.cover_bonus())
+    }
+
+    // AC before anything that depends on the attack, item bonus included
+    pub(crate) fn armor_class(&self) -> i32 {
+        self.defenses.get(Defense::ArmorClass, self)
+    }
+
+    // Raises the condition by `by`, from 0 if it's missing, never past its cap
+    pub(crate) fn increase_condition(&mut self, tag: &Tag, by: u8) {
+        let held = self.conditions.iter().position(|c| c.same_kind(tag));
+        let value = held.map_or(0, |at| self.conditions[at].value()).saturating_add(by);
+        match held {
+            Some(at) => self.conditions[at] = tag.with_value(value),
+            None => self.conditions.push(tag.with_value(value)),
+        }
+    }
+
+    // Removes every condition of the same kind as `tag`
+    pub(crate) fn clear_condition(&mut self, tag: &Tag) {
+        self.conditions.retain(|condition| !condition.same_kind(tag));
+    }
+}
+
+// A condition as the rules write it, like "frightened 1"
+pub(crate) fn condition_name(condition: &Tag) -> String {
+    match condition {
+        Tag::Flatfooted(None) => String::from("flat-footed"),
+        Tag::Flatfooted(Some(source)) => format!("flat-footed to {source}"),
+        Tag::Unconscious => String::from("unconscious"),
+        Tag::Paralyzed => String::from("paralyzed"),
+        Tag::ShieldRaised => String::from("shield raised"),
+        Tag::Grabbed => String::from("grabbed"),
+        Tag::Prone => String::from("prone"),
+        Tag::Fortune => String::from("fortune"),
+        Tag::Misfortune => String::from("misfortune"),
+        Tag::Concealed => String::from("concealed"),
+        Tag::Cover(level) => level.name(),
+        Tag::Custom { key, value: 0 } => key.clone(),
+        Tag::Custom { key, value } => format!("{key} {value}"),
+        valued => {
+            let name = format!("{valued:?}");
+            let name = name.split('(').next().unwrap_or_default().to_lowercase();
+            format!("{name} {}", valued.value())
+        }
+    }
+}
+
+// One line for logs and test failures, like "Goblin 6/8 HP, AC 16 [frightened 1]"
+impl fmt::Display for Creature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}/{} HP, AC {}", self.name, self.hp, self.max_hp, self.armor_class())?;
+        if !self.conditions.is_empty() {
+            let conditions: Vec<String> = self.conditions.iter().map(condition_name).collect();
+            write!(f, " [{}]", conditions.join(", "))?;
+        }
+        if self.dead {
+            write!(f, " (dead)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(dead_code)] // Every damage type in the rules, whether or not anything deals it yet
+pub(crate) enum DamageType {
+    Bludgeoning,
+    Piercing,
+    Slashing,
+    Acid,
+    Cold,
+    Electricity,
+    Fire,
+    Sonic,
+    Force,
+}
+
+impl DamageType {
+    pub(crate) fn is_physical(self) -> bool {
+        matches!(self, DamageType::Bludgeoning | DamageType::Piercing | DamageType::Slashing)
+    }
+}
+
+/**
+ * Runs `f` on just the one creature, leaving the rest of the world alone.
+ * Does nothing if there is no such creature.
+ */
+pub(crate) fn target_filter(
+    id: CreatureId,
+    f: impl Fn(&mut Creature) + Send + Sync + 'static,
+) -> Filter<World> {
+    Arc::new(move |mut state: State<World>| {
+        if let Some(creature) = state.base.creature_mut(id) {
+            f(creature);
+        }
+        state
+    })
+}
+
+// Reduces the target's HP, never below 0
+pub(crate) fn damage(target: CreatureId, amount: i32) -> Filter<World> {
+    target_filter(target, move |creature| creature.lose_hp(amount))
+}
+
+/**
+ * As `damage`, after the target's immunity or resistance to the type.
+ * Any damage that gets through is remembered until the target's next turn.
+ */
+pub(crate) fn typed_damage(target: CreatureId, amount: i32, kind: DamageType) -> Filter<World> {
+    typed_material_damage(target, amount, kind, None)
+}
+
+// As `typed_damage`, from a weapon of a special material such as silver
+pub(crate) fn typed_material_damage(
+    target: CreatureId,
+    amount: i32,
+    kind: DamageType,
+    material: Option<Material>,
+) -> Filter<World> {
+    target_filter(target, move |creature| {
+        let amount = creature.damage_after_defenses(amount, kind, material);
+        creature.lose_hp(amount);
+        if amount > 0 && !creature.damage_taken.contains(&kind) {
+            creature.damage_taken.push(kind);
+        }
+    })
+}
+
+// Restores HP, never above the maximum, as lowered by Drained
+pub(crate) fn heal(target: CreatureId, amount: i32) -> Filter<World> {
+    target_filter(target, move |creature| {
+        creature.hp = creature.hp.saturating_add(amount).min(creature.effective_max_hp());
+    })
+}
+
+// Brings a dead creature back with the given hit points, no longer dying
+pub(crate) fn revive(target: CreatureId, hp: i32) -> Filter<World> {
+    target_filter(target, move |creature| {
+        creature.dead = false;
+        creature.hp = hp.min(creature.effective_max_hp());
+        creature.clear_condition(&Tag::Dying(0));
+    })
+}
+
+// Raises the target's condition, as being frightened again while frightened does
+pub(crate) fn increase_condition(target: CreatureId, tag: Tag, by: u8) -> Filter<World> {
+    target_filter(target, move |creature| creature.increase_condition(&tag, by))
+}
+
+// The state-level counterpart to `RuleResponse::Detach`
+pub(crate) fn clear_condition(target: CreatureId, tag: Tag) -> Filter<World> {
+    target_filter(target, move |creature| creature.clear_condition(&tag))
+}
+
+impl Action<World> {
+    /**
+     * Starts the creature's turn. Rules watching for `Tag::TurnStart` act
+     * before it applies; applying it resets the creature's movement and damage taken,
+     * and gives back its three actions less any it loses to being stunned,
+     * along with its per-turn and per-round abilities.
+     * Auras are then refreshed for everyone they now reach or have stopped reaching.
+     */
+    pub(crate) fn start_turn(creature: CreatureId) -> Self {
+        Action::new(Arc::new(move |state: &State<World>, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(move |mut state: State<World>| {
+                    if let Some(creature) = state.base.creature_mut(creature) {
+                        creature.reset_turn();
+                        state.meta.actions_remaining = 3 - creature.lose_to_stun(3);
+                        state.meta.reset_turn();
+                    }
+                    state.meta.reactions_used.retain(|id| *id != creature);
+                    state
+                }))
+                .target(creature)
+                .tag(Tag::TurnStart(creature))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            let mut updates = vec![update];
+            for id in state.base.aura_changes() {
+                updates.push(
+                    UpdateBuilder::new()
+                        .filter(refresh_auras(id))
+                        .target(id)
+                        .resolved(Resolved::Resolved)
+                        .build(context.next_id()),
+                );
+            }
+            ActionResponse::Valid(updates)
+        }))
+    }
+}
+
+impl Engine<World> {
+    /**
+     * Stuns the creature. On its own turn it loses the actions straight away;
+     * an activity it is partway through that still needs more than are left
+     * is cancelled with `NotEnoughActions`, undoing what it committed, before the stun lands.
+     */
+    pub(crate) fn stun(&mut self, target: CreatureId, value: u8) {
+        let on_turn = self.state.base.initiative.current() == Some(target);
+        // Like other valued conditions, it doesn't stack; the higher value counts
+        let stunned = self.state.base.creature(target).map_or(0, |c| c.stunned()).max(value);
+        let left = self.state.meta.actions_remaining.saturating_sub(stunned);
+        let needed = self.action.as_ref().filter(|_| self.input.is_some()).map(|a| a.cost);
+        if on_turn && needed.is_some_and(|needed| needed > left) {
+            self.cancel_action();
+            self.rejected = Some(InvalidAction::NotEnoughActions);
+        }
+        self.apply_filter(Arc::new(move |mut state: State<World>| {
+            if let Some(creature) = state.base.creature_mut(target) {
+                creature.add_condition(Tag::Stunned(value));
+                if on_turn {
+                    let lost = creature.lose_to_stun(state.meta.actions_remaining);
+                    state.meta.actions_remaining -= lost;
+                }
+            }
+            state
+        }));
+    }
+}
+
+// The battle map's fixed features
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Grid {
+    pub(crate) walls: BTreeSet<Position>, // Squares that block line of effect
+}
+
+/**
+ * Whether anything can pass in a straight line from one square's centre to another's.
+ * Walls in the squares between block it; a line that only grazes a corner is blocked
+ * just when the squares on both sides of that corner are walls.
+ */
+pub(crate) fn line_of_effect(from: Position, to: Position, grid: &Grid) -> bool {
+    let (dx, dy) = ((to.x - from.x).abs(), (to.y - from.y).abs());
+    let (sx, sy) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+    let wall = |x: i32, y: i32| grid.walls.contains(&Position::new(x, y));
+    let (mut x, mut y) = (from.x, from.y);
+    let (mut ix, mut iy) = (0, 0);
+    while ix < dx || iy < dy {
+        // Which edge the line crosses next, compared in whole numbers
+        let decision = (1 + 2 * ix) * dy - (1 + 2 * iy) * dx;
+        if decision == 0 {
+            if wall(x + sx, y) && wall(x, y + sy) {
+                return false;
+            }
+            x += sx;
+            y += sy;
+            ix += 1;
+            iy += 1;
+        } else if decision < 0 {
+            x += sx;
+            ix += 1;
+        } else {
+            y += sy;
+            iy += 1;
+        }
+        if Position::new(x, y) != to && wall(x, y) {
+            return false;
+        }
+    }
+    true
+}
+
+/** World
+ *  The base type for an encounter: every creature, keyed by id.
+ *  A BTreeMap keeps iteration order stable between runs.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct World {
+    pub(crate) creatures: BTreeMap<CreatureId, Creature>,
+    pub(crate) difficult_terrain: BTreeSet<Position>,
+    pub(crate) greater_difficult_terrain: BTreeSet<Position>, // Counts over plain difficult
+    pub(crate) grid: Grid,
+    pub(crate) auras: Vec<Aura>,
+    pub(crate) initiative: Initiative,
+    pub(crate) custom_conditions: ConditionRegistry,
+}
+
+impl World {
+    pub(crate) fn creature(&self, id: CreatureId) -> Option<&Creature> {
+        self.creatures.get(&id)
+    }
+
+    pub(crate) fn creature_mut(&mut self, id: CreatureId) -> Option<&mut Creature> {
+        self.creatures.get_mut(&id)
+    }
+
+    // How many times over entering the square costs: 2 in difficult terrain, 3 in greater
+    pub(crate) fn terrain_multiplier(&self, square: &Position) -> i32 {
+        if self.greater_difficult_terrain.contains(square) {
+            3
+        } else if self.difficult_terrain.contains(square) {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/** WorldBuilder
+ *  Sets up a world with several creatures at once, for tests and scenarios.
+ *  Positions set before their creature is added are dropped.
+ */
+#[derive(Default)]
+pub(crate) struct WorldBuilder {
+    world: World,
+}
+
+impl WorldBuilder {
+    pub(crate) fn new() -> Self {
+        WorldBuilder::default()
+    }
+
+    pub(crate) fn add_creature(mut self, id: CreatureId, creature: Creature) -> Self {
+        self.world.creatures.insert(id, creature);
+        self
+    }
+
+    // Adds a copy of the template under the new id; see `Creature::spawn`
+    pub(crate) fn spawn(self, new_id: CreatureId, template: &Creature, name_suffix: &str) -> Self {
+        self.add_creature(new_id, template.spawn(name_suffix))
+    }
+
+    pub(crate) fn set_position(mut self, id: CreatureId, position: Position) -> Self {
+        if let Some(creature) = self.world.creature_mut(id) {
+            creature.position = position;
+        }
+        self
+    }
+
+    pub(crate) fn build(self) -> World {
+        self.world
+    }
+}
+
+impl Base for World {
+    /**
+     * The filter sees the whole world and everything it leaves is kept: initiative,
+     * auras, terrain and the rest, and its target. Other creatures are the exception;
+     * an update only changes the creature it is routed to, so theirs are put back.
+     * Only conditions the update applies land on the target, see `UpdateBuilder::applies`;
+     * the rest of its tags are there for rules to read.
+     */
+    fn apply(state: State<Self>, update: &Update<Self>) -> State<Self> {
+        let target = update.target;
+        let bystanders: Vec<(CreatureId, Creature)> = state
+            .base
+            .creatures
+            .iter()
+            .filter(|(id, _)| **id != target)
+            .map(|(id, creature)| (*id, creature.clone()))
+            .collect();
+        let mut state = (update.filter)(state);
+        state.base.creatures.retain(|id, _| *id == target);
+        state.base.creatures.extend(bystanders);
+        if let Some(creature) = state.base.creature_mut(target) {
+            for tag in update.sorted_tags() {
+                if tag.is_condition() && update.applies.contains(tag) {
+                    creature.add_condition(tag.clone());
+                }
+            }
+            if creature.dying() >= 4 {
+                creature.dead = true;
+            }
+        }
+        state
+    }
+
+    fn no_effect(
+        before: &State<Self>,
+        after: &State<Self>,
+        update: &Update<Self>,
+    ) -> Option<String> {
+        let dead = |state: &State<Self>| state.base.creature(update.target).is_some_and(|c| c.dead);
+        // Only an update that brings the creature back gets through
+        (dead(before) && dead(after)).then(|| String::from("no effect (dead)"))
+    }
+
+    fn incapacitated(state: &State<Self>, actor: CreatureId) -> Option<Tag> {
+        let creature = state.base.creature(actor)?;
+        creature.conditions.iter().find(|c| c.is_incapacitating()).cloned()
+    }
+
+    fn diff(before: &State<Self>, after: &State<Self>) -> StateDiff {
+        diff_world(before, after)
+    }
+
+    fn current_turn(state: &State<Self>) -> Option<CreatureId> {
+        state.base.initiative.current()
+    }
+
+    fn fingerprint(&self) -> u64 {
+        fingerprint_world(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{Weapon, WeaponGroup};
+    use crate::metadata::Metadata;
+    use crate::tags::TagSet;
+    use crate::engine::{Engine, Input, InputKind, InvalidAction, Meta, RuleResponse, UserInput};
+
+    #[test]
+    fn spawned_copies_are_their_own_creatures() {
+        let goblin = Creature::new("Goblin Warrior", 6, 16).with_condition(Tag::Frightened(1));
+        let mut world = (1..=3)
+            .fold(WorldBuilder::new(), |world, n| world.spawn(n, &goblin, &n.to_string()))
+            .build();
+        world.creature_mut(2).unwrap().lose_hp(4);
+        world.creature_mut(3).unwrap().conditions.clear();
+        let names: Vec<_> = world.creatures.iter().map(|(id, c)| (*id, c.name.clone())).collect();
+        assert_eq!(
+            names,
+            vec![
+                (1, String::from("Goblin Warrior 1")),
+                (2, String::from("Goblin Warrior 2")),
+                (3, String::from("Goblin Warrior 3")),
+            ]
+        );
+        let hp: Vec<_> = world.creatures.values().map(|c| (c.hp, c.conditions.len())).collect();
+        assert_eq!(hp, vec![(6, 1), (2, 1), (6, 0)]);
+        assert_eq!(goblin.conditions, vec![Tag::Frightened(1)]);
+    }
+
+    #[test]
+    fn updates_only_touch_their_target() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        // Both filters damage everyone, but each is routed to one creature
+        for (target, damage) in [(0, 3), (1, 5)] {
+            engine.updates.push(Update {
+                filter: Arc::new(move |mut state: State<World>| {
+                    for creature in state.base.creatures.values_mut() {
+                        creature.hp -= damage;
+                    }
+                    state
+                }),
+                id: target,
+                target,
+                tags: TagSet::new(),
+                applies: TagSet::new(),
+                resolved: Resolved::Resolved,
+                meta: Metadata::new(),
+                outcome: Default::default(),
+            });
+        }
+        engine.step();
+        engine.step();
+        let world = &engine.state.base;
+        assert_eq!(world.creature(0).unwrap().hp, 17);
+        assert_eq!(world.creature(1).unwrap().hp, 3);
+    }
+
+    #[test]
+    fn updates_keep_the_rest_of_the_world_and_only_applied_conditions() {
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        world.initiative = Initiative::new(vec![(0, 15), (1, 10)]);
+        let mut engine = Engine::for_base(world);
+        // For later rules to read, not for the goblin to keep
+        engine.rules.push(Arc::new(|_, _| RuleResponse::Attach(Tag::Frightened(1))));
+        engine.act(Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(Arc::new(|mut state: State<World>| {
+                    state.base.initiative.advance();
+                    state.base.difficult_terrain.insert(Position::new(2, 2));
+                    for creature in state.base.creatures.values_mut() {
+                        creature.hp -= 3;
+                    }
+                    state
+                }))
+                .target(1)
+                .applies(Tag::Clumsy(1))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        })));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        let world = &engine.state.base;
+        assert_eq!(world.initiative.current(), Some(1));
+        assert!(world.difficult_terrain.contains(&Position::new(2, 2)));
+        assert_eq!(world.creature(0).unwrap().hp, 20);
+        assert_eq!(world.creature(1).unwrap().hp, 5);
+        assert_eq!(world.creature(1).unwrap().conditions, vec![Tag::Clumsy(1)]);
+    }
+
+    #[test]
+    fn diagonals_alternate_five_and_ten_feet() {
+        let origin = Position::new(0, 0);
+        assert_eq!(origin.distance(&Position::new(3, 0)), 15);
+        assert_eq!(origin.distance(&Position::new(1, 1)), 5);
+        assert_eq!(origin.distance(&Position::new(2, 2)), 15);
+        assert_eq!(origin.distance(&Position::new(3, 1)), 15);
+        assert!(origin.adjacent(&Position::new(1, -1)));
+        assert!(!origin.adjacent(&Position::new(2, 0)));
+    }
+
+    #[test]
+    fn walls_block_lines_but_not_grazed_corners() {
+        let mut grid = Grid::default();
+        grid.walls.insert(Position::new(2, 0));
+        let origin = Position::new(0, 0);
+        assert!(!line_of_effect(origin, Position::new(4, 0), &grid));
+        assert!(line_of_effect(origin, Position::new(4, 2), &grid));
+        assert!(line_of_effect(origin, origin, &grid));
+        // One wall beside a diagonal is grazed; walls on both sides close it off
+        grid.walls.insert(Position::new(1, 0));
+        assert!(line_of_effect(origin, Position::new(2, 2), &grid));
+        grid.walls.insert(Position::new(0, 1));
+        assert!(!line_of_effect(origin, Position::new(2, 2), &grid));
+    }
+
+    #[test]
+    fn targeted_filters_leave_everyone_else_alone() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        let state = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+        let before = state.base.clone();
+        let state = target_filter(1, |goblin| goblin.ac -= 2)(state);
+        assert_eq!(state.base.creature(1).unwrap().ac, 14);
+        assert_eq!(state.base.creature(0), before.creature(0));
+        // No such creature, so nothing changes
+        let state = target_filter(7, |creature| creature.hp = 0)(state);
+        assert_eq!(state.base.creatures.len(), 2);
+        assert_eq!(state.base.creature(0), before.creature(0));
+    }
+
+    #[test]
+    fn clear_condition_removes_persistent_conditions() {
+        let mut goblin = Creature::new("Goblin", 8, 16);
+        goblin.conditions = vec![Tag::Frightened(2), Tag::Flatfooted(None)];
+        let world = WorldBuilder::new().add_creature(0, goblin).build();
+        let state = clear_condition(0, Tag::Frightened(0))(State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        });
+        assert_eq!(state.base.creature(0).unwrap().conditions, vec![Tag::Flatfooted(None)]);
+    }
+
+    #[test]
+    fn display_sums_a_creature_up() {
+        let mut goblin = Creature::new("Goblin", 8, 16);
+        goblin.hp = 6;
+        assert_eq!(goblin.to_string(), "Goblin 6/8 HP, AC 16");
+        goblin.conditions = vec![Tag::Frightened(1), Tag::Flatfooted(Some(2))];
+        assert_eq!(goblin.to_string(), "Goblin 6/8 HP, AC 16 [frightened 1, flat-footed to 2]");
+    }
+
+    #[test]
+    fn raised_conditions_stop_at_their_cap() {
+        let world = WorldBuilder::new().add_creature(0, Creature::new("Goblin", 8, 16)).build();
+        let mut state = State {
+            meta: Meta::default(),
+            base: world,
+            pre_chain: None,
+        };
+        for by in [3, 2, u8::MAX] {
+            state = increase_condition(0, Tag::Frightened(0), by)(state);
+        }
+        state = damage(0, i32::MAX)(state);
+        let goblin = state.base.creature(0).unwrap();
+        assert_eq!(goblin.conditions, vec![Tag::Frightened(4)]);
+        assert_eq!(goblin.hp, 0);
+    }
+
+    #[test]
+    fn filters_can_read_the_pre_chain_state() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.hp = 10;
+        let world = WorldBuilder::new()
+            .add_creature(0, fighter)
+            .add_creature(1, Creature::new("Ogre", 30, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        // The heal lands first, but the damage is the HP missing before it
+        engine.act(Action::new(Arc::new(|_, context| {
+            let retaliate = Arc::new(|state: State<World>| {
+                let fighter = state.pre_state().base.creature(0).unwrap();
+                let missing = fighter.max_hp - fighter.hp;
+                damage(1, missing)(state)
+            });
+            ActionResponse::Valid(vec![
+                UpdateBuilder::new()
+                    .filter(heal(0, 5))
+                    .target(0)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id()),
+                UpdateBuilder::new()
+                    .filter(retaliate)
+                    .target(1)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id()),
+            ])
+        })));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!(engine.state.base.creature(0).unwrap().hp, 15);
+        assert_eq!(engine.state.base.creature(1).unwrap().hp, 20);
+        assert!(engine.state.pre_chain.is_none());
+    }
+
+    #[test]
+    fn stunned_creatures_cannot_afford_long_activities() {
+        let mut fighter = Creature::new("Fighter", 20, 18);
+        fighter.conditions = vec![Tag::Stunned(2)];
+        let world = WorldBuilder::new().add_creature(0, fighter).build();
+        let mut engine = Engine::for_base(world);
+        engine.act(Action::start_turn(0));
+        while !engine.is_idle() {
+            engine.step();
+        }
+        assert_eq!(engine.state.meta.actions_remaining, 1);
+        assert!(engine.state.base.creature(0).unwrap().conditions.is_empty());
+
+        let activity = Action::new(Arc::new(|_, context| {
+            let update = UpdateBuilder::new()
+                .filter(heal(0, 5))
+                .resolved(Resolved::Resolved)
+                .build(context.next_id());
+            ActionResponse::Valid(vec![update])
+        }));
+        engine.act(activity.with_cost(3));
+        engine.step();
+        assert_eq!(engine.rejected, Some(InvalidAction::NotEnoughActions));
+        assert_eq!(engine.state.meta.actions_remaining, 1);
+        assert!(engine.is_idle());
+    }
+
+    #[test]
+    fn a_stun_midway_cancels_an_activity_it_can_no_longer_afford() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::new_encounter(world, vec![(0, 20), (1, 15)]);
+        let whirlwind = Action::new(Arc::new(|_, context| match context.input {
+            Some(Input::Target(target)) => {
+                let update = UpdateBuilder::new()
+                    .filter(damage(*target, 5))
+                    .target(*target)
+                    .resolved(Resolved::Resolved)
+                    .build(context.next_id());
+                ActionResponse::Valid(vec![update])
+            }
+            _ => ActionResponse::RequestInput(UserInput {
+                label: String::from("Target"),
+                kind: InputKind::Target(vec![1]),
+            }),
+        }));
+        engine.act(whirlwind.with_cost(3));
+        engine.step();
+        assert!(engine.pending_input().is_some());
+
+        // Stunned 2 leaves one action, too few to finish
+        engine.stun(0, 2);
+        assert_eq!(engine.rejected, Some(InvalidAction::NotEnoughActions));
+        assert!(!engine.provide_input(Input::Target(1)));
+        assert!(engine.is_idle());
+        assert_eq!(engine.state.meta.actions_remaining, 1);
+        let fighter = engine.state.base.creature(0).unwrap();
+        assert!(fighter.conditions.is_empty());
+        assert_eq!(engine.state.base.creature(1).unwrap().hp, 8);
+    }
+
+    #[test]
+    fn pending_input_lists_target_candidates() {
+        let world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(3, Creature::new("Goblin", 8, 16))
+            .add_creature(4, Creature::new("Goblin", 8, 16))
+            .build();
+        let mut engine = Engine::for_base(world);
+        engine.act(Action::new(Arc::new(|state: &State<World>, context| {
+            let Some(Input::Target(target)) = context.input else {
+                return ActionResponse::RequestInput(UserInput {
+                    label: String::from("Choose a creature to Strike"),
+                    kind: InputKind::Target(
+                        state.base.creatures.keys().copied().filter(|id| *id != 0).collect(),
+                    ),
+                });
+            };
+            let target = *target;
+            ActionResponse::Valid(vec![Update {
+                filter: Arc::new(move |mut state: State<World>| {
+                    state.base.creature_mut(target).unwrap().hp -= 5;
+                    state
+                }),
+                id: 1,
+                target,
+                tags: TagSet::new(),
+                applies: TagSet::new(),
+                resolved: Resolved::Resolved,
+                meta: Metadata::new(),
+                outcome: Default::default(),
+            }])
+        })));
+        engine.step();
+        let prompt = engine.pending_input().unwrap();
+        assert_eq!(prompt.label, "Choose a creature to Strike");
+        assert_eq!(prompt.kind, InputKind::Target(vec![3, 4]));
+
+        engine.step(); // Still waiting
+        assert!(engine.pending_input().is_some());
+        assert!(engine.provide_input(Input::Target(4)));
+        engine.step();
+        engine.step();
+        assert_eq!(engine.state.base.creature(3).unwrap().hp, 8);
+        assert_eq!(engine.state.base.creature(4).unwrap().hp, 3);
+        assert!(engine.is_idle());
+    }
+
+    #[test]
+    fn unconscious_creatures_can_only_take_free_actions() {
+        let mut world = WorldBuilder::new()
+            .add_creature(0, Creature::new("Fighter", 20, 18))
+            .add_creature(1, Creature::new("Goblin", 8, 16))
+            .build();
+        world.creature_mut(0).unwrap().conditions = vec![Tag::Unconscious];
+        let mut engine = Engine::for_base(world);
+        let club = Weapon::new("Club", WeaponGroup::Club, 1, 6);
+        engine.act(Action::strike(0, 1, club));
+        engine.step();
+        assert_eq!(engine.rejected, Some(InvalidAction::Incapacitated(Tag::Unconscious)));
+        assert_eq!(engine.state.meta.actions_remaining, 3);
+
+        let free = Action::new(Arc::new(|_, _| ActionResponse::Valid(vec![])));
+        engine.act(free.with_cost(0).with_target(0, 1, 5));
+        engine.step();
+        assert_eq!(engine.rejected, None);
+    }
+}